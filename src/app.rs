@@ -1,101 +1,833 @@
-use crate::components::ui::UiState;
-use std::collections::VecDeque;
+use crate::components::ui::{
+    DEFAULT_EDGE_THICKNESS, DEFAULT_SIDEBAR_WIDTH, EdgeStyle, LabelKind, UiState,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
     Network,
     components::{
-        network::{Node, load_network_links},
+        bundling::{BundleCache, Segment, bezier_chain},
+        generators::{barabasi_albert, erdos_renyi},
+        geometry::{
+            arrow_head, canvas_region, fan_offsets, grid_lines, inset_area, lerp_along_path,
+            perpendicular_offset, snap_to_grid,
+        },
+        history::History,
+        http::Download,
+        minimap::Minimap,
+        network::{
+            CombinePolicy, LayoutConfig, LayoutProgress, LayoutRun, Link, NODE_RADIUS,
+            NetworkError, Node, format_weight, load_adjacency_matrix, load_edge_list,
+            load_network_links, load_networks, load_positions, save_links, save_positions,
+            save_routing_tables,
+        },
+        theme::{
+            CAPACITY_HIGH, CAPACITY_LOW, COMMUNITY_PALETTE, capacity_color, capacity_thickness,
+            heatmap_color,
+        },
         ui::init_ui,
     },
 };
-use petgraph::graph::NodeIndex;
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const LAYOUT_PATH: &str = "configuration/layout.csv";
+const NETWORK_PATH: &str = "configuration/network.csv";
+const COORDINATES_PATH: &str = "configuration/coordinates.csv";
+const SETTINGS_PATH: &str = "configuration/settings.json";
+/// Screen pixels the mouse must travel from where a node was pressed before it is dragged.
+const DRAG_THRESHOLD: f32 = 4.0;
+/// Translucent discs stacked around each node for the centrality heatmap, and how far the
+/// outermost reaches in node radii.
+const HEATMAP_RINGS: usize = 5;
+const HEATMAP_SPREAD: f32 = 5.0;
+/// Range of the camera zoom, in pixels per world unit.
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 4.0;
+/// How much one notch of the mouse wheel, or one press of `+`/`-`, zooms in or out.
+const ZOOM_STEP: f64 = 1.2;
+
+/// Preferences kept between runs.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    #[serde(default)]
+    freeze_layout: bool,
+}
+
+/// Reads the settings at `path`, falling back to the defaults when there are none yet.
+fn load_settings(path: &str) -> Settings {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+            log::warn!("Ignoring invalid settings in {}: {}", path, err);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+fn save_settings(path: &str, settings: &Settings) -> Result<(), NetworkError> {
+    std::fs::write(path, serde_json::to_string_pretty(settings)?)?;
+    Ok(())
+}
+
+/// One automatic layout pass over a `canvas` sized area, skipped entirely while the layout
+/// is frozen so nodes stay where the user put them. Returns how the pass went, or `None`
+/// when it was skipped.
+fn relax_layout(
+    network: &mut Network,
+    canvas: (f64, f64),
+    config: &LayoutConfig,
+    pinned_node: Option<NodeIndex>,
+    frozen: bool,
+) -> Option<LayoutRun> {
+    if frozen {
+        return None;
+    }
+    Some(network.apply_force_directed_layout(canvas.0, canvas.1, config, pinned_node))
+}
 
 pub struct AppModel {
     network: Network,
     pub rl: RaylibHandle,
     pub rthread: RaylibThread,
     dragged_node: Option<(NodeIndex, f64, f64)>,
+    /// A node pressed but not dragged yet, with the grab offset and the screen position
+    /// of the press.
+    pressed_node: Option<(NodeIndex, f64, f64, Vector2)>,
+    selection_start: Option<(f64, f64)>,
     canvas_offset_x: i32,
+    view_offset: (f64, f64),
+    /// Pixels per world unit.
+    zoom: f64,
     uistate: UiState,
     highlighted_path: Option<Vec<(NodeIndex, NodeIndex)>>,
+    show_stats_overlay: bool,
+    layout_run: LayoutRun,
+    /// The startup layout, advanced one iteration per frame until it settles.
+    layout_progress: Option<LayoutProgress>,
+    /// Set when an edit should make the layout relax again on the next frame.
+    layout_unsettled: bool,
+    hovered_node: Option<NodeIndex>,
+    hovered_edge: Option<EdgeIndex>,
+    /// The full network while an isolated neighbourhood is shown instead, with the ids of
+    /// the nodes that were isolated so edits can be written back.
+    full_network: Option<(Network, HashSet<String>)>,
+    packet: Option<PacketAnimation>,
+    /// Nodes reachable from the chosen start node; everything else is dimmed.
+    reachable: Option<HashSet<NodeIndex>>,
+    /// Links read for an import the user is still previewing.
+    pending_import: Option<Vec<Link>>,
+    /// A network being fetched from a URL in the background.
+    download: Option<Download>,
+    /// Links of the shortest-path tree being shown; every other link is dimmed.
+    path_tree: Option<HashSet<EdgeIndex>>,
+    /// Capacity left on each link by the last max-flow computation.
+    flow_residuals: Option<HashMap<EdgeIndex, u64>>,
+    /// Node moves that can be undone, one step per drag.
+    history: History,
+    /// Ids of nodes added since the layout last ran, which "Place new" lays out alone.
+    new_nodes: HashSet<String>,
+    /// Edge bundles from the last frame drawn with bundling on.
+    bundles: BundleCache,
+    /// Bundled curves of the links as last drawn, for hit-testing; empty while drawn straight.
+    bundled: HashMap<EdgeIndex, Vec<(f64, f64)>>,
 }
 
+/// A packet travelling along `path`, `progress` (0..=1) of the way through leg `segment`.
 #[derive(Debug)]
+struct PacketAnimation {
+    path: Vec<NodeIndex>,
+    segment: usize,
+    progress: f64,
+}
+
+#[derive(Debug, PartialEq)]
 pub enum AppMsg {
     AddPoint((f64, f64)),
+    AddNamedNode(String, (f64, f64)),
+    AddNode(String),
+    AddLink(String, String, u64, f64),
+    DeleteNode(String),
+    ContractLink(EdgeIndex),
+    CollapseCommunity(NodeIndex),
+    Simplify(CombinePolicy),
+    DuplicateNode(NodeIndex),
     StartDrag(NodeIndex, f64, f64),
     UpdateDrag(f64, f64),
     ComputeShortestPath(String, String),
+    ComputeKShortestPaths(String, String, usize),
+    ShowAlternativePath(usize),
+    ComputeMaxFlow(String, String),
+    ComputeBellmanFord(String, String),
+    ComputeWidestPath(String, String),
     EndDrag,
+    CenterView(f64, f64),
+    ZoomToFit,
+    /// Zooms by a factor around a point in canvas pixels.
+    Zoom(f64, (f64, f64)),
+    ResetView,
+    SelectInRect(f32, f32, f32, f32),
+    MoveSelection(f64, f64),
+    SelectNode(NodeIndex),
+    DeleteLink(EdgeIndex),
+    SetLinkWeight(EdgeIndex, f64),
+    SnapshotLayout,
+    RoundPositions,
+    RestoreLayout,
+    ToggleStatsOverlay,
+    /// Shows the statistics once, as opposed to toggling the overlay; headless mode prints
+    /// them.
+    ShowStats,
+    LoadAdjacencyMatrix(String),
+    LoadD3Json(String),
+    LoadGexf(String),
+    LoadEdgeList(String),
+    LoadUrl(String),
+    /// Checks whether the download started by `LoadUrl` has finished.
+    PollDownload,
+    LoadNetworks(Vec<String>),
+    PreviewImport(String),
+    ApplyImport,
+    CancelImport,
+    FitToScreen,
+    TreeLayout(NodeIndex),
+    Isolate(NodeIndex, usize),
+    GenerateErdosRenyi(usize, f64),
+    GenerateBarabasiAlbert(usize, usize),
+    ShowFullNetwork,
+    TogglePin(NodeIndex),
+    ToggleAnchor(NodeIndex),
+    RunLayout,
+    RelaxLayout,
+    StepLayout,
+    LayoutNewNodes,
+    SaveSettings,
+    PlayPacket,
+    ShowReachable(String),
+    ClearReachable,
+    ShowPathTree(String),
+    ClearPathTree,
+    AdvancePacket(f64),
+    GeographicLayout,
+    SpectralLayout,
+    SavePng(String),
+    ExportLayoutJson(String),
+    ExportRoutingTables(String),
+    ExportReport(String),
+    ExportSelection(String),
+    SaveSession(String),
+    LoadSession(String),
+    Undo,
+    Redo,
 }
 
-impl AppModel {
-    pub fn init(title: impl AsRef<str>) -> AppModel {
-        let mut network = Network::new();
+/// Window size used by [`AppModel::init`].
+const DEFAULT_WINDOW_SIZE: (i32, i32) = (800, 600);
 
-        let network_links = load_network_links("configuration/network.csv").unwrap();
-        let canvas_offset_x = 250;
-        for link in &network_links {
-            if !network.node_indices.contains_key(&link.source_node) {
-                let source_node = Node {
-                    id: link.source_node.clone(),
-                    point: (
-                        rand::random_range(50..(750 - canvas_offset_x - 50)),
-                        rand::random_range(50..550),
-                    ),
-                };
-                network.add_node(source_node);
-            }
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
 
-            if !network.node_indices.contains_key(&link.destination_node) {
-                let destination_node = Node {
-                    id: link.destination_node.clone(),
-                    point: (
-                        rand::random_range(50..(750 - canvas_offset_x - 50)),
-                        rand::random_range(50..550),
-                    ),
-                };
-                network.add_node(destination_node);
-            }
+/// Reads the links the app starts with from `path`. A missing, unreadable or empty file is
+/// not fatal: the app starts with an empty network and the message is shown in the sidebar.
+fn load_startup_links(path: &str) -> (Vec<Link>, Option<String>) {
+    match load_network_links(path) {
+        Ok(links) if links.is_empty() => (links, Some(format!("{} has no links", path))),
+        Ok(links) => (links, None),
+        Err(err) => {
+            log::warn!("Failed to load {}: {}", path, err);
+            (Vec::new(), Some(format!("Could not load {}", path)))
         }
+    }
+}
+
+/// Places `links` at random inside a `canvas` sized area, inset by the margin the force
+/// layout keeps. Every load that starts from bare links goes through here, so a canvas
+/// shrunk below the margins puts nodes on its middle instead of failing.
+fn place_on_canvas(
+    links: Vec<Link>,
+    canvas: (f64, f64),
+    rng: &mut impl Rng,
+) -> Result<Network, NetworkError> {
+    Network::with_random_placement(links, inset_area((0.0, 0.0, canvas.0, canvas.1), 50.0), rng)
+}
+
+/// Places `links` at random inside the canvas of a `width` x `height` window whose sidebar
+/// is `canvas_offset_x` wide, ready for the force layout over that same canvas. When any of
+/// `coordinates` match a node the geographic layout is used instead. Returns the network
+/// and, unless it was placed geographically, the force layout still to run on it.
+fn initial_network(
+    links: Vec<Link>,
+    coordinates: &[(String, f64, f64)],
+    width: i32,
+    height: i32,
+    canvas_offset_x: i32,
+    rng: &mut impl Rng,
+) -> Result<(Network, Option<LayoutProgress>), NetworkError> {
+    let (_, _, canvas_width, canvas_height) = canvas_region(width, height, canvas_offset_x);
+    let (canvas_width, canvas_height) = (canvas_width as f64, canvas_height as f64);
+    let mut network = place_on_canvas(links, (canvas_width, canvas_height), rng)?;
+    for warning in network.validate() {
+        log::warn!("{}", warning);
+    }
+    network.apply_coordinates(coordinates);
+    if network.has_coordinates() {
+        network.apply_geographic_layout(canvas_width, canvas_height);
+        // Sites sharing coordinates would otherwise be drawn on top of each other
+        network.spread_overlapping(2.0 * NODE_RADIUS);
+        return Ok((network, None));
+    }
+    // Coincident nodes push each other in no particular direction
+    network.spread_overlapping(2.0 * NODE_RADIUS);
+    // Run a frame at a time by the app so the window shows up right away
+    let layout = LayoutProgress::new(canvas_width, canvas_height, LayoutConfig::default(), None);
+    Ok((network, Some(layout)))
+}
 
-        for link in network_links {
-            network.add_link(link).expect("Failed to add link");
+/// Maps between screen pixels and world coordinates, the space `Node::point` lives in. The
+/// world origin is the canvas' top-left corner, right of the sidebar, moved by the pan, and
+/// one world unit spans `zoom` pixels.
+#[derive(Debug, Clone, Copy)]
+struct ViewTransform {
+    origin: (f64, f64),
+    zoom: f64,
+}
+
+impl ViewTransform {
+    fn new(canvas_offset_x: i32, view_offset: (f64, f64), zoom: f64) -> Self {
+        ViewTransform {
+            origin: (canvas_offset_x as f64 + view_offset.0, view_offset.1),
+            zoom,
         }
+    }
 
-        let (rl, rthread) = raylib::init().size(800, 600).title(title.as_ref()).build();
+    fn screen_to_world(&self, screen: Vector2) -> (f64, f64) {
+        (
+            (screen.x as f64 - self.origin.0) / self.zoom,
+            (screen.y as f64 - self.origin.1) / self.zoom,
+        )
+    }
+
+    fn world_to_screen(&self, (x, y): (f64, f64)) -> Vector2 {
+        Vector2::new(
+            (x * self.zoom + self.origin.0) as f32,
+            (y * self.zoom + self.origin.1) as f32,
+        )
+    }
+}
+
+/// Zoom and pan that fit the world rectangle `bounds` (`(min_x, min_y, max_x, max_y)`)
+/// into a `canvas` sized view with `margin` pixels to spare on every side, centered. The
+/// zoom stays within `MIN_ZOOM..=MAX_ZOOM`, so a lone node is not blown up to fill the view.
+fn fit_view(bounds: (f64, f64, f64, f64), canvas: (f64, f64), margin: f64) -> (f64, (f64, f64)) {
+    let (min_x, min_y, max_x, max_y) = bounds;
+    let fit = |room: f64, extent: f64| (room - 2.0 * margin).max(1.0) / extent.max(f64::EPSILON);
+    let zoom = fit(canvas.0, max_x - min_x)
+        .min(fit(canvas.1, max_y - min_y))
+        .clamp(MIN_ZOOM, MAX_ZOOM);
+    let center = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    (
+        zoom,
+        (
+            canvas.0 / 2.0 - center.0 * zoom,
+            canvas.1 / 2.0 - center.1 * zoom,
+        ),
+    )
+}
+
+/// Zoom and pan after zooming `zoom` by `factor` around `anchor`, a point in canvas pixels,
+/// so the world point under the anchor stays where it is. The zoom stays within
+/// `MIN_ZOOM..=MAX_ZOOM`.
+fn zoom_around(zoom: f64, pan: (f64, f64), factor: f64, anchor: (f64, f64)) -> (f64, (f64, f64)) {
+    let new_zoom = (zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    let world = ((anchor.0 - pan.0) / zoom, (anchor.1 - pan.1) / zoom);
+    (
+        new_zoom,
+        (anchor.0 - world.0 * new_zoom, anchor.1 - world.1 * new_zoom),
+    )
+}
+
+/// One-line readout of where the mouse at screen position `mouse` lands in world space and
+/// which node or link, if any, is under it.
+fn status_text(
+    network: &Network,
+    transform: ViewTransform,
+    mouse: Vector2,
+    hovered: (Option<NodeIndex>, Option<EdgeIndex>),
+) -> String {
+    let (x, y) = transform.screen_to_world(mouse);
+    let mut text = format!("x: {:.1}  y: {:.1}", x, y);
+    if let Some(node) = hovered.0.and_then(|idx| network.graph.node_weight(idx)) {
+        text.push_str(&format!("  node: {}", node.id));
+    } else if let Some(link) = hovered.1.and_then(|edge| network.graph.edge_weight(edge)) {
+        text.push_str(&format!("  link: {}", link.link_id));
+    }
+    text
+}
+
+/// Text drawn on a node: its id, cut short by [`fit_label`] to `max_width`, followed by its
+/// degree in parentheses when given. Only the id is shortened, so the degree always shows.
+fn node_label(
+    id: &str,
+    degree: Option<usize>,
+    max_width: i32,
+    font_size: i32,
+    measure: impl Fn(&str, i32) -> i32,
+) -> String {
+    let id = fit_label(id, max_width, font_size, measure);
+    match degree {
+        Some(degree) => format!("{} ({})", id, degree),
+        None => id,
+    }
+}
+
+/// `text` cut short with a trailing `...` so that it measures at most `max_width` pixels at
+/// `font_size`, or `text` itself when it already fits. `measure` is raylib's text width,
+/// passed in so this works without a window. Empty when not even the ellipsis fits.
+fn fit_label(
+    text: &str,
+    max_width: i32,
+    font_size: i32,
+    measure: impl Fn(&str, i32) -> i32,
+) -> String {
+    if measure(text, font_size) <= max_width {
+        return text.to_string();
+    }
+    text.char_indices()
+        .rev()
+        .map(|(end, _)| format!("{}...", &text[..end]))
+        .find(|label| measure(label, font_size) <= max_width)
+        .unwrap_or_default()
+}
+
+/// Where a node grabbed `offset` away from its center lands with the mouse at `mouse`, all
+/// in world coordinates: snapped to a `snap` spaced grid if given, then kept inside
+/// `bounds` (`(min_x, min_y, max_x, max_y)`) if given.
+fn drag_position(
+    mouse: (f64, f64),
+    offset: (f64, f64),
+    snap: Option<f64>,
+    bounds: Option<(f64, f64, f64, f64)>,
+) -> (f64, f64) {
+    let mut point = (mouse.0 - offset.0, mouse.1 - offset.1);
+    if let Some(spacing) = snap {
+        point = (
+            snap_to_grid(point.0, spacing),
+            snap_to_grid(point.1, spacing),
+        );
+    }
+    if let Some((min_x, min_y, max_x, max_y)) = bounds {
+        point = (point.0.clamp(min_x, max_x), point.1.clamp(min_y, max_y));
+    }
+    point
+}
+
+/// Whether the mouse has moved far enough from the screen position `press` for a press on a
+/// node to become a drag rather than a click.
+fn exceeds_drag_threshold(press: Vector2, mouse: Vector2, threshold: f32) -> bool {
+    (mouse.x - press.x).hypot(mouse.y - press.y) > threshold
+}
+
+/// Weight a link of `weight` gets after the mouse wheel moved by `wheel`: one step up or
+/// down per frame whatever the scroll distance, never going below zero.
+fn wheel_weight(weight: f64, wheel: f32) -> f64 {
+    let step = if wheel > 0.0 {
+        1.0
+    } else if wheel < 0.0 {
+        -1.0
+    } else {
+        0.0
+    };
+    (weight + step).max(0.0)
+}
+
+/// Why a link stands out, in order of precedence, which decides how it is stroked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LinkEmphasis {
+    /// Part of the highlighted path.
+    Path,
+    /// Saturated by the last max-flow computation.
+    Saturated,
+    /// A fragile link that would split the network if it failed.
+    Bridge,
+    /// Touching the hovered node.
+    Hovered,
+    /// Colored and sized by its capacity within the network's capacity range.
+    Capacity(u64, (u64, u64)),
+    Plain,
+}
+
+/// Color and line width of a link drawn with `emphasis`. `thickness` is the width of a
+/// plain link in `edge_color`; every other width scales along with it.
+fn link_stroke(emphasis: LinkEmphasis, edge_color: Color, thickness: f32) -> (Color, f32) {
+    let (color, width) = match emphasis {
+        LinkEmphasis::Path => (Color::RED, 4.0),
+        LinkEmphasis::Saturated => (Color::PURPLE, 4.0),
+        LinkEmphasis::Bridge => (Color::ORANGE, 3.0),
+        LinkEmphasis::Hovered => (Color::GOLD, 3.0),
+        LinkEmphasis::Capacity(capacity, range) => (
+            capacity_color(capacity, range),
+            capacity_thickness(capacity, range),
+        ),
+        LinkEmphasis::Plain => (edge_color, DEFAULT_EDGE_THICKNESS),
+    };
+    (color, width * thickness / DEFAULT_EDGE_THICKNESS)
+}
+
+/// Node under the mouse at world position `mouse`, if it should be hover-highlighted.
+/// Nothing is hovered while a drag is in progress or when the mouse is over the sidebar.
+fn hover_target(
+    network: &Network,
+    mouse: (f64, f64),
+    over_canvas: bool,
+    radius: f64,
+    dragging: bool,
+) -> Option<NodeIndex> {
+    if dragging || !over_canvas {
+        return None;
+    }
+    network.find_node_at_point(mouse.0, mouse.1, radius)
+}
+
+impl AppModel {
+    pub fn init(title: impl AsRef<str>) -> AppModel {
+        AppModel::init_with_seed(title, time_seed())
+    }
+
+    /// Like [`AppModel::init`], but node placement is driven by an RNG seeded with `seed`
+    /// so that the same seed always reproduces the same initial layout.
+    pub fn init_with_seed(title: impl AsRef<str>, seed: u64) -> AppModel {
+        AppModel::init_with_options(title, DEFAULT_WINDOW_SIZE.0, DEFAULT_WINDOW_SIZE.1, seed)
+    }
+
+    /// Like [`AppModel::init`], but opens a `width` x `height` window. The sidebar keeps
+    /// its width and the canvas takes up the rest.
+    pub fn init_with_size(title: impl AsRef<str>, width: i32, height: i32) -> AppModel {
+        AppModel::init_with_options(title, width, height, time_seed())
+    }
+
+    fn init_with_options(title: impl AsRef<str>, width: i32, height: i32, seed: u64) -> AppModel {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let (network_links, startup_error) = load_startup_links(NETWORK_PATH);
+        // Optional id,lat,lon file for geographic placement
+        let coordinates = load_positions(COORDINATES_PATH).unwrap_or_default();
+        let canvas_offset_x = DEFAULT_SIDEBAR_WIDTH as i32;
+        let (network, layout_progress) = initial_network(
+            network_links,
+            &coordinates,
+            width,
+            height,
+            canvas_offset_x,
+            &mut rng,
+        )
+        .expect("Failed to add link");
+
+        let (rl, rthread) = raylib::init()
+            .size(width, height)
+            .resizable()
+            .msaa_4x()
+            .title(title.as_ref())
+            .build();
+
+        let mut uistate = UiState::default();
+        uistate.startup_error = startup_error;
+        uistate.freeze_layout = load_settings(SETTINGS_PATH).freeze_layout;
 
         AppModel {
             network,
             rl,
             rthread,
             dragged_node: None,
+            pressed_node: None,
+            selection_start: None,
             canvas_offset_x,
-            uistate: UiState::default(),
+            view_offset: (0.0, 0.0),
+            zoom: 1.0,
+            uistate,
             highlighted_path: None,
+            show_stats_overlay: false,
+            layout_run: LayoutRun::default(),
+            layout_progress,
+            layout_unsettled: false,
+            hovered_node: None,
+            hovered_edge: None,
+            full_network: None,
+            packet: None,
+            reachable: None,
+            pending_import: None,
+            download: None,
+            path_tree: None,
+            flow_residuals: None,
+            history: History::default(),
+            new_nodes: HashSet::new(),
+            bundles: BundleCache::default(),
+            bundled: HashMap::new(),
+        }
+    }
+
+    /// Swaps in a freshly loaded network, lays it out to fit the canvas and drops any
+    /// state that referred to nodes or links of the previous one.
+    fn replace_network(&mut self, mut network: Network) {
+        for warning in network.validate() {
+            log::warn!("{}", warning);
+        }
+        let (canvas_width, canvas_height) = self.canvas_size();
+        network.spread_overlapping(2.0 * NODE_RADIUS);
+        self.layout_run = network.apply_force_directed_layout(
+            canvas_width,
+            canvas_height,
+            &self.layout_config(),
+            None,
+        );
+
+        self.full_network = None;
+        self.uistate.isolated = false;
+        self.swap_network(network);
+    }
+
+    /// Renders `network` from now on, as is, and returns the previous one. Indices into the
+    /// old network are meaningless in the new one, so selection and highlights are dropped.
+    fn swap_network(&mut self, network: Network) -> Network {
+        self.forget_indices();
+        self.new_nodes.clear();
+        self.layout_progress = None;
+        self.uistate.layout_progress = None;
+        std::mem::replace(&mut self.network, network)
+    }
+
+    /// Drops every node and edge index held outside the network, for when an edit made
+    /// petgraph fill freed slots and they may point at different nodes or links now.
+    fn forget_indices(&mut self) {
+        self.dragged_node = None;
+        self.pressed_node = None;
+        self.hovered_node = None;
+        self.hovered_edge = None;
+        self.clear_highlighted_path();
+        self.uistate.selected_node = None;
+        self.uistate.selected_nodes.clear();
+        self.uistate.pinned_node = None;
+        self.history.clear();
+        self.update(AppMsg::ClearReachable);
+    }
+
+    fn transform(&self) -> ViewTransform {
+        ViewTransform::new(self.canvas_offset_x, self.view_offset, self.zoom)
+    }
+
+    fn screen_to_world(&self, screen: Vector2) -> (f64, f64) {
+        self.transform().screen_to_world(screen)
+    }
+
+    /// Back to the unpanned, unzoomed view, e.g. after a layout placed nodes for it.
+    fn reset_view(&mut self) {
+        self.view_offset = (0.0, 0.0);
+        self.zoom = 1.0;
+    }
+
+    /// Drawable size of the canvas, i.e. the live window minus the sidebar.
+    fn canvas_size(&self) -> (f64, f64) {
+        let (_, _, width, height) = canvas_region(
+            self.rl.get_screen_width(),
+            self.rl.get_screen_height(),
+            self.canvas_offset_x,
+        );
+        (width as f64, height as f64)
+    }
+
+    /// Highlights the path through `nodes` and shows its latency in the sidebar.
+    fn highlight_path(&mut self, nodes: &[NodeIndex]) {
+        self.uistate.path_latency = Some(self.network.path_latency(nodes));
+        self.highlighted_path = Some(nodes.windows(2).map(|hop| (hop[0], hop[1])).collect());
+    }
+
+    fn clear_highlighted_path(&mut self) {
+        self.highlighted_path = None;
+        self.packet = None;
+        self.flow_residuals = None;
+        self.path_tree = None;
+        self.uistate.showing_path_tree = false;
+        self.uistate.max_flow = None;
+        self.uistate.path_status = None;
+        self.uistate.path_latency = None;
+        self.uistate.alternative_paths.clear();
+    }
+
+    /// Force layout settings for the cooling schedule picked in the sidebar.
+    fn layout_config(&self) -> LayoutConfig {
+        LayoutConfig {
+            cooling: self.uistate.layout_cooling,
+            ..LayoutConfig::default()
         }
     }
 
+    /// Visible world region of the canvas as `(min_x, min_y, max_x, max_y)`.
+    fn viewport(&self) -> (f64, f64, f64, f64) {
+        let (canvas_width, canvas_height) = self.canvas_size();
+        (
+            -self.view_offset.0 / self.zoom,
+            -self.view_offset.1 / self.zoom,
+            (canvas_width - self.view_offset.0) / self.zoom,
+            (canvas_height - self.view_offset.1) / self.zoom,
+        )
+    }
+
+    fn minimap(&self) -> Minimap {
+        Minimap::new(
+            self.rl.get_screen_width() as f64,
+            self.rl.get_screen_height() as f64,
+            self.network.bounds(),
+            self.viewport(),
+        )
+    }
+
     pub fn handle_input(&mut self, message_queue: &mut VecDeque<AppMsg>) {
+        // The sidebar may have been resized by dragging its edge last frame
+        self.canvas_offset_x = self.uistate.canvas_offset();
         let mouse_pos = self.rl.get_mouse_position();
-        if self
-            .rl
-            .is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+        let minimap = self.minimap();
+        let (world_x, world_y) = self.screen_to_world(mouse_pos);
+        let over_canvas =
+            mouse_pos.x as f64 >= self.canvas_offset_x as f64 && !self.uistate.mouse_over_ui;
+
+        self.hovered_node = hover_target(
+            &self.network,
+            (world_x, world_y),
+            over_canvas,
+            self.uistate.node_radius / self.zoom,
+            self.dragged_node.is_some(),
+        );
+        self.hovered_edge =
+            if self.hovered_node.is_none() && self.dragged_node.is_none() && over_canvas {
+                self.network
+                    .find_edge_at_point(world_x, world_y, 5.0 / self.zoom, &self.bundled)
+            } else {
+                None
+            };
+
+        // Scrolling over a link edits its weight, elsewhere on the canvas it zooms; over the
+        // sidebar or a window imgui scrolls
+        let wheel = self.rl.get_mouse_wheel_move();
+        if wheel != 0.0 && over_canvas {
+            if let Some(edge) = self.hovered_edge
+                && let Some(link) = self.network.graph.edge_weight(edge)
+            {
+                let weight = wheel_weight(link.weight, wheel);
+                if weight != link.weight {
+                    message_queue.push_back(AppMsg::SetLinkWeight(edge, weight));
+                }
+            } else {
+                let anchor = (
+                    (mouse_pos.x - self.canvas_offset_x as f32) as f64,
+                    mouse_pos.y as f64,
+                );
+                message_queue.push_back(AppMsg::Zoom(ZOOM_STEP.powf(wheel as f64), anchor));
+            }
+        }
+
+        if std::mem::take(&mut self.layout_unsettled) {
+            message_queue.push_back(AppMsg::RelaxLayout);
+        }
+
+        if self.layout_progress.is_some() {
+            message_queue.push_back(AppMsg::StepLayout);
+        }
+
+        if self.download.is_some() {
+            message_queue.push_back(AppMsg::PollDownload);
+        }
+
+        if self.packet.is_some() {
+            message_queue.push_back(AppMsg::AdvancePacket(self.rl.get_frame_time() as f64));
+        }
+
+        if self.rl.is_key_pressed(KeyboardKey::KEY_F3) {
+            message_queue.push_back(AppMsg::ToggleStatsOverlay);
+        }
+
+        // Typing into a text field must not move the camera
+        if !self.uistate.keyboard_over_ui {
+            let pressed =
+                |keys: &[KeyboardKey]| keys.iter().any(|&key| self.rl.is_key_pressed(key));
+            let (canvas_width, canvas_height) = self.canvas_size();
+            let center = (canvas_width / 2.0, canvas_height / 2.0);
+            if pressed(&[KeyboardKey::KEY_F]) {
+                message_queue.push_back(AppMsg::ZoomToFit);
+            } else if pressed(&[KeyboardKey::KEY_EQUAL, KeyboardKey::KEY_KP_ADD]) {
+                message_queue.push_back(AppMsg::Zoom(ZOOM_STEP, center));
+            } else if pressed(&[KeyboardKey::KEY_MINUS, KeyboardKey::KEY_KP_SUBTRACT]) {
+                message_queue.push_back(AppMsg::Zoom(1.0 / ZOOM_STEP, center));
+            } else if pressed(&[KeyboardKey::KEY_ZERO, KeyboardKey::KEY_KP_0]) {
+                message_queue.push_back(AppMsg::ResetView);
+            }
+        }
+
+        let ctrl = self.rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || self.rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl && self.rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            message_queue.push_back(AppMsg::Undo);
+        } else if ctrl && self.rl.is_key_pressed(KeyboardKey::KEY_Y) {
+            message_queue.push_back(AppMsg::Redo);
+        }
+
+        if over_canvas
+            && self
+                .rl
+                .is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
         {
-            if let Some(node_idx) = self.network.find_node_at_point(
-                mouse_pos.x as f64 - self.canvas_offset_x as f64,
-                mouse_pos.y as f64,
-                18.0,
+            if minimap.contains(mouse_pos.x as f64, mouse_pos.y as f64) {
+                let (center_x, center_y) = minimap.to_world(mouse_pos.x as f64, mouse_pos.y as f64);
+                message_queue.push_back(AppMsg::CenterView(center_x, center_y));
+            } else if let Some(node_idx) = self.network.find_node_at_point(
+                world_x,
+                world_y,
+                self.uistate.node_radius / self.zoom,
             ) {
                 // Calculate offset from node center to mouse click
                 let node = self.network.graph.node_weight(node_idx).unwrap();
-                let offset_x =
-                    mouse_pos.x as f64 - node.point.0 as f64 - self.canvas_offset_x as f64;
-                let offset_y = mouse_pos.y as f64 - node.point.1 as f64;
+                let offset_x = world_x - node.point.0;
+                let offset_y = world_y - node.point.1;
+                message_queue.push_back(AppMsg::SelectNode(node_idx));
+                self.pressed_node = Some((node_idx, offset_x, offset_y, mouse_pos));
+            } else {
+                self.selection_start = Some((world_x, world_y));
+            }
+        }
+
+        if let Some((node_idx, offset_x, offset_y, press)) = self.pressed_node {
+            if !self.rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                // Released without moving far: the press was a click
+                self.pressed_node = None;
+            } else if exceeds_drag_threshold(press, mouse_pos, DRAG_THRESHOLD) {
+                self.pressed_node = None;
                 message_queue.push_back(AppMsg::StartDrag(node_idx, offset_x, offset_y));
             }
         }
+
+        if let Some((start_x, start_y)) = self.selection_start
+            && self
+                .rl
+                .is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT)
+        {
+            self.selection_start = None;
+            message_queue.push_back(AppMsg::SelectInRect(
+                start_x as f32,
+                start_y as f32,
+                world_x as f32,
+                world_y as f32,
+            ));
+        }
         match (
             self.rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
             self.rl
@@ -103,10 +835,17 @@ impl AppModel {
             self.dragged_node.is_some(),
         ) {
             (true, _, true) => {
-                message_queue.push_back(AppMsg::UpdateDrag(
-                    mouse_pos.x as f64 - self.canvas_offset_x as f64,
-                    mouse_pos.y as f64,
-                ));
+                let (node_idx, offset_x, offset_y) = self.dragged_node.unwrap();
+                if self.uistate.selected_nodes.contains(&node_idx) {
+                    // Move the whole selection by the dragged node's displacement
+                    let node = &self.network.graph[node_idx];
+                    message_queue.push_back(AppMsg::MoveSelection(
+                        world_x - offset_x - node.point.0,
+                        world_y - offset_y - node.point.1,
+                    ));
+                } else {
+                    message_queue.push_back(AppMsg::UpdateDrag(world_x, world_y));
+                }
             }
             (_, true, true) => {
                 message_queue.push_back(AppMsg::EndDrag);
@@ -120,43 +859,668 @@ impl AppModel {
             AppMsg::AddPoint((x, y)) => {
                 let node = Node {
                     id: format!("node{}", self.network.graph.node_count()),
-                    point: (x as i32, y as i32),
+                    point: (x, y),
+                    ..Node::default()
                 };
+                self.new_nodes.insert(node.id.clone());
                 self.network.add_node(node);
             }
+            AppMsg::AddNamedNode(id, point) => {
+                self.new_nodes.insert(id.clone());
+                self.network.add_node(Node {
+                    id,
+                    point,
+                    ..Node::default()
+                });
+            }
+            AppMsg::AddNode(id) => {
+                let (min_x, min_y, max_x, max_y) = self.viewport();
+                self.update(AppMsg::AddNamedNode(
+                    id,
+                    ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0),
+                ));
+            }
+            AppMsg::AddLink(source, destination, capacity, weight) => {
+                let link = Link {
+                    link_id: self.network.next_link_id(),
+                    source_node: source,
+                    destination_node: destination,
+                    capacity,
+                    weight,
+                    ..Link::default()
+                };
+                if let Err(err) = self.network.add_link(link) {
+                    log::warn!("Failed to add link: {}", err);
+                }
+            }
+            AppMsg::DeleteNode(id) => {
+                if let Some(&node_idx) = self.network.node_indices.get(&id) {
+                    self.network.remove_node(node_idx);
+                    // Node and edge indices shift when petgraph fills the freed slot
+                    self.forget_indices();
+                }
+            }
+            AppMsg::DuplicateNode(node_idx) => {
+                if self.network.graph.node_weight(node_idx).is_some() {
+                    let copy = self.network.duplicate_node(node_idx);
+                    self.new_nodes.insert(self.network.graph[copy].id.clone());
+                    self.uistate.selected_node = Some(copy);
+                }
+            }
             AppMsg::StartDrag(node_idx, offset_x, offset_y) => {
                 self.dragged_node = Some((node_idx, offset_x, offset_y));
+                // Dragging a selected node moves the whole selection
+                if self.uistate.selected_nodes.contains(&node_idx) {
+                    self.history
+                        .begin_drag(&self.network, self.uistate.selected_nodes.iter().copied());
+                } else {
+                    self.history.begin_drag(&self.network, [node_idx]);
+                }
             }
             AppMsg::UpdateDrag(mouse_x, mouse_y) => {
                 if let Some((node_idx, offset_x, offset_y)) = self.dragged_node {
+                    let snap = self
+                        .uistate
+                        .snaps_to_grid()
+                        .then_some(self.uistate.grid_spacing);
+                    let bounds = self
+                        .uistate
+                        .clamps_drag()
+                        .then(|| inset_area(self.viewport(), 50.0));
                     if let Some(node) = self.network.graph.node_weight_mut(node_idx) {
-                        // Set node position to mouse position minus offset
-                        node.point.0 = (mouse_x - offset_x) as i32;
-                        node.point.1 = (mouse_y - offset_y) as i32;
-                        // Clamp to screen bounds
-                        node.point.0 = node
-                            .point
-                            .0
-                            .clamp(50, self.rl.get_screen_width() - self.canvas_offset_x - 50);
-                        node.point.1 = node.point.1.clamp(50, self.rl.get_screen_height() - 50);
+                        node.point =
+                            drag_position((mouse_x, mouse_y), (offset_x, offset_y), snap, bounds);
                     }
                 }
             }
             AppMsg::EndDrag => {
                 self.dragged_node = None;
+                self.history.end_drag(&self.network);
+            }
+            AppMsg::Undo => {
+                self.history.undo(&mut self.network);
+            }
+            AppMsg::Redo => {
+                self.history.redo(&mut self.network);
             }
             AppMsg::ComputeShortestPath(start_id, end_id) => {
-                match self.network.find_shortest_path(&start_id, &end_id) {
-                    Ok(path) => self.highlighted_path = Some(path),
-                    Err(_) => self.highlighted_path = None,
+                self.clear_highlighted_path();
+                if let Ok(path) = self.network.find_shortest_path(&start_id, &end_id) {
+                    let nodes: Vec<NodeIndex> = path
+                        .first()
+                        .map(|&(start, _)| start)
+                        .into_iter()
+                        .chain(path.iter().map(|&(_, end)| end))
+                        .collect();
+                    self.highlight_path(&nodes);
+                }
+            }
+            AppMsg::ComputeKShortestPaths(start_id, end_id, k) => {
+                self.clear_highlighted_path();
+                if let (Some(&start), Some(&end)) = (
+                    self.network.node_indices.get(&start_id),
+                    self.network.node_indices.get(&end_id),
+                ) {
+                    self.uistate.alternative_paths = self.network.k_shortest_paths(start, end, k);
+                    self.update(AppMsg::ShowAlternativePath(0));
+                }
+            }
+            AppMsg::ComputeBellmanFord(start_id, end_id) => {
+                self.clear_highlighted_path();
+                match self.network.shortest_path_bellman_ford(&start_id, &end_id) {
+                    Ok(Some((nodes, cost))) => {
+                        self.highlight_path(&nodes);
+                        self.uistate.path_status = Some(format!("Cost: {}", cost));
+                    }
+                    Ok(None) => {
+                        self.uistate.path_status =
+                            Some(format!("{} is unreachable from {}", end_id, start_id));
+                    }
+                    Err(err) => self.uistate.path_status = Some(err.to_string()),
+                }
+            }
+            AppMsg::ComputeWidestPath(start_id, end_id) => {
+                self.clear_highlighted_path();
+                if let (Some(&start), Some(&end)) = (
+                    self.network.node_indices.get(&start_id),
+                    self.network.node_indices.get(&end_id),
+                ) {
+                    self.uistate.path_status = Some(match self.network.widest_path(start, end) {
+                        Some((nodes, width)) => {
+                            self.highlight_path(&nodes);
+                            format!("Bottleneck: {} Mbps", width)
+                        }
+                        None => format!("{} is unreachable from {}", end_id, start_id),
+                    });
+                }
+            }
+            AppMsg::ComputeMaxFlow(source_id, sink_id) => {
+                self.clear_highlighted_path();
+                if let (Some(&source), Some(&sink)) = (
+                    self.network.node_indices.get(&source_id),
+                    self.network.node_indices.get(&sink_id),
+                ) {
+                    let (flow, residuals) = self.network.max_flow_with_residuals(source, sink);
+                    self.uistate.max_flow = Some(flow);
+                    self.flow_residuals = Some(residuals);
+                }
+            }
+            AppMsg::ShowAlternativePath(index) => {
+                if let Some((nodes, _)) = self.uistate.alternative_paths.get(index).cloned() {
+                    self.uistate.alternative_path_index = index;
+                    self.highlight_path(&nodes);
+                }
+            }
+            AppMsg::SelectInRect(x0, y0, x1, y1) => {
+                self.uistate.selected_nodes = self
+                    .network
+                    .nodes_in_rect(x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+            }
+            AppMsg::MoveSelection(dx, dy) => {
+                for &node_idx in &self.uistate.selected_nodes {
+                    if let Some(node) = self.network.graph.node_weight_mut(node_idx) {
+                        node.point.0 += dx;
+                        node.point.1 += dy;
+                    }
+                }
+            }
+            AppMsg::SelectNode(node_idx) => {
+                self.uistate.selected_node = Some(node_idx);
+            }
+            AppMsg::ContractLink(edge) => {
+                if let Some(merged) = self.network.contract_edge(edge) {
+                    // Node and edge indices shift as when deleting a node
+                    self.forget_indices();
+                    self.uistate.selected_node = Some(merged);
+                }
+            }
+            AppMsg::CollapseCommunity(node_idx) => {
+                let communities = self.network.louvain_communities();
+                let members: HashSet<NodeIndex> = communities
+                    .iter()
+                    .filter(|&(_, community)| Some(community) == communities.get(&node_idx))
+                    .map(|(&idx, _)| idx)
+                    .collect();
+                if let Some(collapsed) = self.network.collapse_group(&members) {
+                    // Node and edge indices shift as when deleting a node
+                    self.forget_indices();
+                    self.uistate.selected_node = Some(collapsed);
+                }
+            }
+            AppMsg::Simplify(combine) => {
+                if self.network.simplify(combine) > 0 {
+                    // Edge indices shift as links are removed
+                    self.clear_highlighted_path();
+                    self.hovered_edge = None;
+                }
+            }
+            AppMsg::DeleteLink(edge) => {
+                if self.network.remove_link(edge).is_some() {
+                    // The highlighted path may run through the removed link
+                    self.clear_highlighted_path();
+                    self.hovered_edge = None;
+                }
+            }
+            AppMsg::SetLinkWeight(edge, weight) => {
+                if self.network.set_link_weight(edge, weight) && self.uistate.live_layout {
+                    self.layout_unsettled = true;
+                }
+            }
+            AppMsg::SnapshotLayout => {
+                if let Err(err) = save_positions(LAYOUT_PATH, &self.network.export_positions()) {
+                    log::warn!("Failed to snapshot layout: {}", err);
+                }
+            }
+            AppMsg::RoundPositions => self.network.round_positions(),
+            AppMsg::RestoreLayout => match load_positions(LAYOUT_PATH) {
+                Ok(positions) => self.network.apply_positions(&positions),
+                Err(err) => log::warn!("Failed to restore layout: {}", err),
+            },
+            AppMsg::ToggleStatsOverlay => {
+                self.show_stats_overlay = !self.show_stats_overlay;
+            }
+            AppMsg::ShowStats => self.show_stats_overlay = true,
+            AppMsg::LoadAdjacencyMatrix(path) => match load_adjacency_matrix(&path) {
+                Ok(network) => self.replace_network(network),
+                Err(err) => log::warn!("Failed to load adjacency matrix {}: {}", path, err),
+            },
+            AppMsg::LoadEdgeList(path) => {
+                let canvas = self.canvas_size();
+                match load_edge_list(&path)
+                    .and_then(|links| place_on_canvas(links, canvas, &mut rand::rng()))
+                {
+                    Ok(network) => self.replace_network(network),
+                    Err(err) => log::warn!("Failed to load edge list {}: {}", path, err),
+                }
+            }
+            AppMsg::LoadUrl(url) => {
+                // A download still running is abandoned; its result is dropped
+                self.uistate.downloading = Some(url.clone());
+                self.uistate.download_error = None;
+                self.download = Some(Download::start(url));
+            }
+            AppMsg::PollDownload => {
+                let Some(download) = &self.download else {
+                    return;
+                };
+                let Some(result) = download.poll() else {
+                    return;
+                };
+                let url = download.url().to_string();
+                self.download = None;
+                self.uistate.downloading = None;
+                let canvas = self.canvas_size();
+                match result.and_then(|links| place_on_canvas(links, canvas, &mut rand::rng())) {
+                    Ok(network) => self.replace_network(network),
+                    Err(err) => {
+                        self.uistate.download_error =
+                            Some(format!("Could not load {}: {}", url, err));
+                    }
+                }
+            }
+            AppMsg::LoadD3Json(path) => {
+                match std::fs::read_to_string(&path)
+                    .map_err(NetworkError::from)
+                    .and_then(|json| Network::from_d3_json(&json))
+                {
+                    Ok(network) => self.replace_network(network),
+                    Err(err) => log::warn!("Failed to load D3 JSON {}: {}", path, err),
+                }
+            }
+            AppMsg::LoadGexf(path) => match Network::from_gexf(&path) {
+                Ok(network) => self.replace_network(network),
+                Err(err) => log::warn!("Failed to load GEXF {}: {}", path, err),
+            },
+            AppMsg::LoadNetworks(paths) => {
+                let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+                match load_networks(&paths) {
+                    Ok(network) => self.replace_network(network),
+                    Err(err) => log::warn!("Failed to load {}: {}", paths.join(", "), err),
+                }
+            }
+            AppMsg::PreviewImport(path) => match load_network_links(&path) {
+                Ok(links) => {
+                    self.uistate.import_preview = Some(self.network.dry_run_load(&links));
+                    self.pending_import = Some(links);
+                }
+                Err(err) => log::warn!("Failed to read {}: {}", path, err),
+            },
+            AppMsg::ApplyImport => {
+                if let Some(links) = self.pending_import.take() {
+                    let (min_x, min_y, max_x, max_y) = self.viewport();
+                    self.network
+                        .apply_load(&links, ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0));
+                }
+                self.uistate.import_preview = None;
+            }
+            AppMsg::CancelImport => {
+                self.pending_import = None;
+                self.uistate.import_preview = None;
+            }
+            AppMsg::FitToScreen => {
+                let (canvas_width, canvas_height) = self.canvas_size();
+                self.network
+                    .fit_to_bounds(canvas_width, canvas_height, 50.0);
+                self.reset_view();
+            }
+            AppMsg::GeographicLayout => {
+                let (canvas_width, canvas_height) = self.canvas_size();
+                self.network
+                    .apply_geographic_layout(canvas_width, canvas_height);
+                self.reset_view();
+            }
+            AppMsg::SpectralLayout => {
+                let (canvas_width, canvas_height) = self.canvas_size();
+                self.network
+                    .apply_spectral_layout(canvas_width, canvas_height);
+                self.reset_view();
+            }
+            AppMsg::GenerateErdosRenyi(n, p) => {
+                self.replace_network(erdos_renyi(n, p, time_seed()));
+            }
+            AppMsg::GenerateBarabasiAlbert(n, m) => {
+                self.replace_network(barabasi_albert(n, m, time_seed()));
+            }
+            AppMsg::Isolate(center, radius) => {
+                let subgraph = self.network.ego_subgraph(center, radius);
+                let region = subgraph.nodes().map(|node| node.id.clone()).collect();
+                let previous = self.swap_network(subgraph);
+                // Isolating again from an isolated view keeps its edits and still returns to
+                // the full network
+                let full = match self.full_network.take() {
+                    Some((mut full, previous_region)) => {
+                        full.write_back(&previous_region, &previous);
+                        full
+                    }
+                    None => previous,
+                };
+                self.full_network = Some((full, region));
+                self.uistate.isolated = true;
+            }
+            AppMsg::ShowReachable(start_id) => {
+                if let Some(&start) = self.network.node_indices.get(&start_id) {
+                    let reachable = self.network.reachable_from(start);
+                    self.uistate.reachable_count = Some(reachable.len());
+                    self.reachable = Some(reachable);
+                }
+            }
+            AppMsg::ShowPathTree(source_id) => {
+                self.clear_highlighted_path();
+                if let Some(&source) = self.network.node_indices.get(&source_id) {
+                    let graph = &self.network.graph;
+                    // Of parallel links the lightest is the one on the shortest path
+                    let edges = self
+                        .network
+                        .shortest_path_tree(source)
+                        .into_iter()
+                        .filter_map(|(node, previous)| {
+                            graph
+                                .edges_connecting(previous?, node)
+                                .min_by(|a, b| a.weight().weight.total_cmp(&b.weight().weight))
+                                .map(|edge| edge.id())
+                        })
+                        .collect();
+                    self.path_tree = Some(edges);
+                    self.uistate.showing_path_tree = true;
+                }
+            }
+            AppMsg::ClearPathTree => self.clear_highlighted_path(),
+            AppMsg::ClearReachable => {
+                self.reachable = None;
+                self.uistate.reachable_count = None;
+            }
+            AppMsg::PlayPacket => {
+                if let Some(path) = &self.highlighted_path {
+                    let nodes: Vec<NodeIndex> = path
+                        .first()
+                        .map(|&(start, _)| start)
+                        .into_iter()
+                        .chain(path.iter().map(|&(_, end)| end))
+                        .collect();
+                    if nodes.len() > 1 {
+                        self.packet = Some(PacketAnimation {
+                            path: nodes,
+                            segment: 0,
+                            progress: 0.0,
+                        });
+                    }
+                }
+            }
+            AppMsg::AdvancePacket(frame_time) => {
+                let mut distance = self.uistate.packet_speed as f64 * frame_time;
+                while let Some(packet) = &mut self.packet {
+                    let (Some(&from), Some(&to)) = (
+                        packet.path.get(packet.segment),
+                        packet.path.get(packet.segment + 1),
+                    ) else {
+                        self.packet = None;
+                        break;
+                    };
+                    let (ax, ay) = self.network.graph[from].point;
+                    let (bx, by) = self.network.graph[to].point;
+                    let length = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt().max(1.0);
+
+                    packet.progress += distance / length;
+                    if packet.progress < 1.0 {
+                        break;
+                    }
+                    // Carry what is left of this frame's travel into the next leg
+                    distance = (packet.progress - 1.0) * length;
+                    packet.segment += 1;
+                    packet.progress = 0.0;
+                }
+            }
+            AppMsg::TogglePin(node_idx) => {
+                self.uistate.pinned_node =
+                    (self.uistate.pinned_node != Some(node_idx)).then_some(node_idx);
+            }
+            AppMsg::ToggleAnchor(node_idx) => {
+                if let Some(node) = self.network.graph.node_weight_mut(node_idx) {
+                    node.anchored = !node.anchored;
+                }
+            }
+            AppMsg::RunLayout => {
+                let (canvas_width, canvas_height) = self.canvas_size();
+                let config = self.layout_config();
+                self.layout_run = self.network.apply_force_directed_layout(
+                    canvas_width,
+                    canvas_height,
+                    &config,
+                    self.uistate.pinned_node,
+                );
+                self.new_nodes.clear();
+                // A full run supersedes what is left of the startup layout
+                self.layout_progress = None;
+            }
+            AppMsg::LayoutNewNodes => {
+                // Frozen, nodes stay put; they still count as new once the layout thaws
+                if self.uistate.freeze_layout {
+                    return;
+                }
+                let (canvas_width, canvas_height) = self.canvas_size();
+                let config = self.layout_config();
+                let movable: HashSet<NodeIndex> = self
+                    .new_nodes
+                    .drain()
+                    .filter_map(|id| self.network.node_indices.get(&id).copied())
+                    .collect();
+                if !movable.is_empty() {
+                    self.layout_run = self.network.apply_layout_to(
+                        canvas_width,
+                        canvas_height,
+                        &config,
+                        &movable,
+                    );
+                }
+            }
+            AppMsg::StepLayout => {
+                // Freezing the layout also stops the one still running from startup
+                if let Some(progress) = &mut self.layout_progress
+                    && !self.uistate.freeze_layout
+                    && self.network.layout_step(progress)
+                {
+                    self.layout_run = progress.run;
+                    self.uistate.layout_progress = Some(progress.fraction());
+                } else {
+                    if let Some(progress) = self.layout_progress.take() {
+                        self.layout_run = progress.run;
+                    }
+                    self.uistate.layout_progress = None;
+                }
+            }
+            AppMsg::RelaxLayout => {
+                let canvas = self.canvas_size();
+                let config = self.layout_config();
+                if let Some(run) = relax_layout(
+                    &mut self.network,
+                    canvas,
+                    &config,
+                    self.uistate.pinned_node,
+                    self.uistate.freeze_layout,
+                ) {
+                    self.layout_run = run;
+                    self.new_nodes.clear();
+                }
+            }
+            AppMsg::SaveSettings => {
+                let settings = Settings {
+                    freeze_layout: self.uistate.freeze_layout,
+                };
+                if let Err(err) = save_settings(SETTINGS_PATH, &settings) {
+                    log::warn!("Failed to save settings: {}", err);
+                }
+            }
+            AppMsg::ShowFullNetwork => {
+                if let Some((full, region)) = self.full_network.take() {
+                    let isolated = self.swap_network(full);
+                    self.network.write_back(&region, &isolated);
+                    self.uistate.isolated = false;
                 }
             }
+            AppMsg::TreeLayout(root) => {
+                let (canvas_width, _) = self.canvas_size();
+                self.network.apply_tree_layout(root, canvas_width, 80.0);
+                self.reset_view();
+            }
+            AppMsg::ExportSelection(path) => {
+                let selection = self.network.induced_subgraph(&self.uistate.selected_nodes);
+                let links: Vec<Link> = selection.graph.edge_weights().cloned().collect();
+                if let Err(err) = save_links(&path, &links) {
+                    log::warn!("Failed to export selection to {}: {}", path, err);
+                }
+            }
+            AppMsg::ExportLayoutJson(path) => {
+                if let Err(err) = std::fs::write(&path, self.network.export_layout_json()) {
+                    log::warn!("Failed to export positions to {}: {}", path, err);
+                }
+            }
+            AppMsg::SaveSession(path) => {
+                if let Err(err) = std::fs::write(&path, self.network.to_json()) {
+                    log::warn!("Failed to save session to {}: {}", path, err);
+                }
+            }
+            AppMsg::LoadSession(path) => {
+                match std::fs::read_to_string(&path)
+                    .map_err(NetworkError::from)
+                    .and_then(|json| Network::from_json(&json))
+                {
+                    Ok(network) => self.replace_network(network),
+                    Err(err) => log::warn!("Failed to load session {}: {}", path, err),
+                }
+            }
+            AppMsg::ExportRoutingTables(path) => {
+                if let Err(err) = save_routing_tables(&path, &self.network) {
+                    log::warn!("Failed to export routing tables to {}: {}", path, err);
+                }
+            }
+            AppMsg::ExportReport(path) => {
+                let report = serde_json::to_string_pretty(&self.network.analysis_report())
+                    .expect("report serializes to JSON");
+                if let Err(err) = std::fs::write(&path, report) {
+                    log::warn!("Failed to export report to {}: {}", path, err);
+                }
+            }
+            AppMsg::SavePng(path) => {
+                let (x, y, width, height) = canvas_region(
+                    self.rl.get_screen_width(),
+                    self.rl.get_screen_height(),
+                    self.canvas_offset_x,
+                );
+                let mut image = self.rl.load_image_from_screen(&self.rthread);
+                image.crop(Rectangle::new(
+                    x as f32,
+                    y as f32,
+                    width as f32,
+                    height as f32,
+                ));
+                if !image.export_image(&path) {
+                    log::warn!("Failed to save canvas to {}", path);
+                }
+            }
+            AppMsg::CenterView(x, y) => {
+                let (canvas_width, canvas_height) = self.canvas_size();
+                self.view_offset = (
+                    canvas_width / 2.0 - x * self.zoom,
+                    canvas_height / 2.0 - y * self.zoom,
+                );
+            }
+            AppMsg::ZoomToFit => {
+                if self.network.graph.node_count() > 0 {
+                    (self.zoom, self.view_offset) =
+                        fit_view(self.network.bounds(), self.canvas_size(), 50.0);
+                }
+            }
+            AppMsg::Zoom(factor, anchor) => {
+                (self.zoom, self.view_offset) =
+                    zoom_around(self.zoom, self.view_offset, factor, anchor);
+            }
+            AppMsg::ResetView => self.reset_view(),
         }
     }
 
     pub fn init_network_canvas(&mut self, message_queue: &mut VecDeque<AppMsg>) {
+        let minimap = self.minimap();
+        let viewport = self.viewport();
+        let transform = self.transform();
+        let edge_style = self.uistate.edge_style;
+        let mouse_pos = self.rl.get_mouse_position();
+        let node_radius = self.uistate.node_radius;
+        let edge_thickness = self.uistate.edge_thickness;
+        let parallel_slots = self.network.parallel_edge_slots();
+        let show_edge_labels = self.uistate.shows_label(LabelKind::Edge);
+        let show_node_labels = self.uistate.shows_label(LabelKind::Node);
+        let degrees = self
+            .uistate
+            .shows_degrees()
+            .then(|| self.network.degree_centrality());
+        let grid_spacing = self
+            .uistate
+            .shows_grid()
+            .then_some(self.uistate.grid_spacing);
+        let show_arrows = self.uistate.shows_arrows();
+        let articulation_points = self.network.articulation_points();
+        let bridges = self.network.bridges();
+        // While hovering, everything not touching the hovered node is dimmed
+        let hovered_node = self.hovered_node;
+        let hover_neighbors: HashSet<NodeIndex> = hovered_node
+            .map(|hovered| self.network.graph.neighbors(hovered).collect())
+            .unwrap_or_default();
+        let colors = self.uistate.theme.colors();
+        let capacity_range = self
+            .uistate
+            .colors_by_capacity()
+            .then(|| self.network.capacity_range());
+        let communities = self
+            .uistate
+            .colors_communities()
+            .then(|| self.network.louvain_communities());
+        // Closeness scaled so the most central node glows at full strength
+        let heat: Option<HashMap<NodeIndex, f32>> = self.uistate.shows_heatmap().then(|| {
+            let closeness = self.network.closeness_centrality();
+            let peak = closeness.values().copied().fold(0.0, f64::max);
+            closeness
+                .into_iter()
+                .map(|(node, value)| {
+                    let intensity = if peak > 0.0 { value / peak } else { 0.0 };
+                    (node, intensity as f32)
+                })
+                .collect()
+        });
+        // Bundled curves of the links otherwise drawn as one straight line; self-loops and
+        // parallel links keep their own shapes. Bundling reruns whenever a node moves, too
+        // slow for every frame of a drag or a running layout, so links stay straight until
+        // those settle.
+        let settled = self.dragged_node.is_none() && self.layout_progress.is_none();
+        self.bundled = if self.uistate.bundles_edges() && settled {
+            let graph = &self.network.graph;
+            let (edges, segments): (Vec<EdgeIndex>, Vec<Segment>) = graph
+                .edge_references()
+                .filter(|edge| edge.source() != edge.target() && parallel_slots[&edge.id()].1 == 1)
+                .map(|edge| {
+                    let segment = (graph[edge.source()].point, graph[edge.target()].point);
+                    (edge.id(), segment)
+                })
+                .unzip();
+            edges
+                .into_iter()
+                .zip(self.bundles.curves(segments).iter().cloned())
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        let bundles = &self.bundled;
+        let reachable = self.reachable.as_ref();
+        let path_tree = self.path_tree.as_ref();
+        let unreachable =
+            |node: NodeIndex| reachable.is_some_and(|reachable| !reachable.contains(&node));
+        let hover_dims = |a: NodeIndex, b: NodeIndex| {
+            hovered_node.is_some_and(|hovered| a != hovered && b != hovered)
+                || unreachable(a)
+                || unreachable(b)
+        };
+
         self.rl.draw(&self.rthread, |mut rhandle| {
-            rhandle.clear_background(Color::BLACK);
+            rhandle.clear_background(colors.background);
 
             let canvas_width = rhandle.get_screen_width() - self.canvas_offset_x;
             let canvas_height = rhandle.get_screen_height();
@@ -167,85 +1531,893 @@ impl AppModel {
                 canvas_width,
                 canvas_height,
                 |mut handle| {
-                    for (link, src_node, dest_node) in self.network.links() {
-                        let start_pos = Vector2 {
-                            x: src_node.point.0 as f32 + self.canvas_offset_x as f32,
-                            y: src_node.point.1 as f32,
-                        };
-                        let end_pos = Vector2 {
-                            x: dest_node.point.0 as f32 + self.canvas_offset_x as f32,
-                            y: dest_node.point.1 as f32,
-                        };
+                    if let Some(spacing) = grid_spacing {
+                        let grid_color = colors.grid;
+                        for x in grid_lines(viewport.0, viewport.2, spacing) {
+                            let screen_x = transform.world_to_screen((x, 0.0)).x as i32;
+                            handle.draw_line(screen_x, 0, screen_x, canvas_height, grid_color);
+                        }
+                        for y in grid_lines(viewport.1, viewport.3, spacing) {
+                            let screen_y = transform.world_to_screen((0.0, y)).y as i32;
+                            handle.draw_line(
+                                self.canvas_offset_x,
+                                screen_y,
+                                self.canvas_offset_x + canvas_width,
+                                screen_y,
+                                grid_color,
+                            );
+                        }
+
+                        // Small cross at the world origin
+                        let origin = transform.world_to_screen((0.0, 0.0));
+                        let (origin_x, origin_y) = (origin.x as i32, origin.y as i32);
+                        handle.draw_line(
+                            origin_x - 8,
+                            origin_y,
+                            origin_x + 8,
+                            origin_y,
+                            colors.edge_label,
+                        );
+                        handle.draw_line(
+                            origin_x,
+                            origin_y - 8,
+                            origin_x,
+                            origin_y + 8,
+                            colors.edge_label,
+                        );
+                    }
+
+                    // Stacked translucent discs approximate a radial gradient around each node
+                    if let Some(heat) = &heat {
+                        for (&node_idx, &intensity) in heat {
+                            let center =
+                                transform.world_to_screen(self.network.graph[node_idx].point);
+                            let color = heatmap_color(intensity);
+                            // Each disc carries a share of the alpha so the overlap peaks
+                            // at the node itself
+                            let ring_color = Color {
+                                a: color.a / HEATMAP_RINGS as u8,
+                                ..color
+                            };
+                            for ring in (1..=HEATMAP_RINGS).rev() {
+                                let radius = node_radius as f32 * HEATMAP_SPREAD * ring as f32
+                                    / HEATMAP_RINGS as f32;
+                                handle.draw_circle(
+                                    center.x as i32,
+                                    center.y as i32,
+                                    radius,
+                                    ring_color,
+                                );
+                            }
+                        }
+                    }
+
+                    for (edge, link, src_node, dest_node) in self.network.links() {
+                        let (src_idx, dest_idx) = (
+                            self.network.node_indices[&src_node.id],
+                            self.network.node_indices[&dest_node.id],
+                        );
+                        let dimmed = hover_dims(src_idx, dest_idx)
+                            || path_tree.is_some_and(|tree| !tree.contains(&edge));
+
+                        if src_node.id == dest_node.id {
+                            // Self-loop: a small circle sitting on top of the node
+                            let loop_radius = 12.0;
+                            let center = transform.world_to_screen((
+                                src_node.point.0,
+                                src_node.point.1 - node_radius - loop_radius / 2.0,
+                            ));
+                            let (center_x, center_y) = (center.x as i32, center.y as i32);
+                            handle.draw_circle_lines(
+                                center_x,
+                                center_y,
+                                loop_radius as f32,
+                                if dimmed {
+                                    colors.edge.fade(0.2)
+                                } else {
+                                    colors.edge
+                                },
+                            );
+
+                            if show_edge_labels {
+                                let weight_text = format_weight(link.weight);
+                                let font_size = 18;
+                                let text_width =
+                                    handle.measure_text(weight_text.as_str(), font_size);
+                                handle.draw_text(
+                                    weight_text.as_str(),
+                                    center_x - text_width / 2,
+                                    center_y - loop_radius as i32 - font_size,
+                                    font_size,
+                                    colors.edge_label,
+                                );
+                            }
+                            continue;
+                        }
+
+                        let start_pos = transform.world_to_screen(src_node.point);
+                        let end_pos = transform.world_to_screen(dest_node.point);
 
-                        let is_highlighted = self.highlighted_path.as_ref().map_or(false, |path| {
-                            let src_idx = self.network.node_indices[&src_node.id];
-                            let dest_idx = self.network.node_indices[&dest_node.id];
+                        let residual = self
+                            .flow_residuals
+                            .as_ref()
+                            .and_then(|residuals| residuals.get(&edge).copied());
+                        let is_highlighted = self.highlighted_path.as_ref().is_some_and(|path| {
                             path.contains(&(src_idx, dest_idx))
                                 || path.contains(&(dest_idx, src_idx))
                         });
 
-                        let (line_color, line_thickness) = if is_highlighted {
-                            (Color::RED, 4.0)
+                        let emphasis = if is_highlighted {
+                            LinkEmphasis::Path
+                        } else if residual == Some(0) && link.capacity > 0 {
+                            LinkEmphasis::Saturated
+                        } else if bridges.contains(&edge) {
+                            LinkEmphasis::Bridge
+                        } else if hovered_node.is_some() && !dimmed {
+                            LinkEmphasis::Hovered
+                        } else if let Some(range) = capacity_range {
+                            LinkEmphasis::Capacity(link.capacity, range)
                         } else {
-                            (Color::WHEAT, 2.0)
+                            LinkEmphasis::Plain
+                        };
+                        // A color from the file replaces the theme's for plain links
+                        let edge_color = link
+                            .color
+                            .map_or(colors.edge, |(r, g, b)| Color::new(r, g, b, 255));
+                        let (line_color, line_thickness) =
+                            link_stroke(emphasis, edge_color, edge_thickness);
+                        let line_color = if dimmed {
+                            line_color.fade(0.2)
+                        } else {
+                            line_color
                         };
 
-                        handle.draw_line_bezier(start_pos, end_pos, line_thickness, line_color);
+                        let mid = transform.world_to_screen(edge_style.point_at(
+                            src_node.point,
+                            dest_node.point,
+                            0.5,
+                        ));
+                        let (mid_x, mid_y) = (mid.x as f64, mid.y as f64);
+                        let (normal_x, normal_y) =
+                            perpendicular_offset(src_node.point, dest_node.point, 1.0);
 
-                        let mid_x =
-                            (src_node.point.0 + dest_node.point.0) / 2 + self.canvas_offset_x;
+                        // Parallel links bow out on either side of the straight line
+                        let (slot, count) = parallel_slots[&edge];
+                        let fan_offset = fan_offsets(count, 30.0)[slot];
+                        // The curve's apex lies halfway towards the control point
+                        let control = Vector2 {
+                            x: (mid_x + normal_x * fan_offset * 2.0) as f32,
+                            y: (mid_y + normal_y * fan_offset * 2.0) as f32,
+                        };
+                        if let Some(curve) = bundles.get(&edge) {
+                            let chain: Vec<Vector2> = bezier_chain(curve)
+                                .into_iter()
+                                .map(|point| transform.world_to_screen(point))
+                                .collect();
+                            handle.draw_spline_bezier_quadratic(&chain, line_thickness, line_color);
+                        } else if fan_offset == 0.0 {
+                            match edge_style {
+                                EdgeStyle::Straight => handle.draw_line_ex(
+                                    start_pos,
+                                    end_pos,
+                                    line_thickness,
+                                    line_color,
+                                ),
+                                EdgeStyle::Bezier => handle.draw_line_bezier(
+                                    start_pos,
+                                    end_pos,
+                                    line_thickness,
+                                    line_color,
+                                ),
+                            }
+                        } else {
+                            handle.draw_spline_segment_bezier_quadratic(
+                                start_pos,
+                                control,
+                                end_pos,
+                                line_thickness,
+                                line_color,
+                            );
+                        }
 
-                        let mid_y = (src_node.point.1 + dest_node.point.1) / 2;
+                        if show_arrows && let Some((from, _)) = self.network.link_direction(edge) {
+                            // The link may be stored the other way round from how it was declared
+                            let (tail, head) = if from == src_idx {
+                                (start_pos, end_pos)
+                            } else {
+                                (end_pos, start_pos)
+                            };
+                            let head = (head.x as f64, head.y as f64);
+                            let approach = if let Some(curve) = bundles.get(&edge) {
+                                // Come in along the last stretch of the bundle
+                                let near_head = if from == src_idx {
+                                    curve[curve.len() - 2]
+                                } else {
+                                    curve[1]
+                                };
+                                let near_head = transform.world_to_screen(near_head);
+                                (near_head.x as f64, near_head.y as f64)
+                            } else if fan_offset == 0.0 {
+                                edge_style.point_at((tail.x as f64, tail.y as f64), head, 0.9)
+                            } else {
+                                (control.x as f64, control.y as f64)
+                            };
+                            // Stop at the rim of the destination node
+                            let (dx, dy) = (head.0 - approach.0, head.1 - approach.1);
+                            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+                            let tip = (
+                                head.0 - dx / length * node_radius,
+                                head.1 - dy / length * node_radius,
+                            );
+                            if let Some(corners) = arrow_head(tip, approach, 12.0, 8.0) {
+                                let [a, b, c] =
+                                    corners.map(|(x, y)| Vector2::new(x as f32, y as f32));
+                                handle.draw_triangle(a, b, c, line_color);
+                            }
+                        }
 
-                        let offset = if link.link_id.as_bytes()[0] % 2 == 0 {
-                            30.0
-                        } else {
-                            -30.0
-                        };
+                        if !show_edge_labels {
+                            continue;
+                        }
 
-                        handle.draw_line_bezier(start_pos, end_pos, 2.0, Color::WHEAT);
+                        let label_offset = fan_offset + 20.0_f64.copysign(fan_offset);
+                        let offset_x = normal_x * label_offset;
+                        let offset_y = normal_y * label_offset;
 
-                        let weight_text = link.weight.to_string();
+                        let weight_text = match residual {
+                            Some(residual) => {
+                                format!("{}/{}", link.capacity - residual, link.capacity)
+                            }
+                            None => format_weight(link.weight),
+                        };
                         let font_size = 18;
                         let text_width = handle.measure_text(weight_text.as_str(), font_size);
                         let text_height = font_size;
 
-                        let text_x = mid_x - text_width / 2;
-                        let text_y = (mid_y as f32 + offset - text_height as f32 / 2.0) as i32;
+                        let text_x = (mid_x + offset_x) as i32 - text_width / 2;
+                        let text_y = (mid_y + offset_y) as i32 - text_height / 2;
 
                         handle.draw_text(
                             weight_text.as_str(),
                             text_x,
                             text_y,
                             font_size,
-                            Color::RAYWHITE,
+                            colors.edge_label,
                         );
                     }
 
                     // Draw nodes
-                    for node in self.network.nodes() {
+                    for &node_idx in &self.uistate.selected_nodes {
+                        let node = &self.network.graph[node_idx];
+                        handle.draw_ring(
+                            transform.world_to_screen(node.point),
+                            node_radius as f32 + 1.0,
+                            node_radius as f32 + 5.0,
+                            0.0,
+                            360.0,
+                            32,
+                            Color::SKYBLUE,
+                        );
+                    }
+
+                    for node_idx in [self.uistate.pinned_node, self.uistate.selected_node]
+                        .into_iter()
+                        .flatten()
+                    {
+                        let Some(node) = self.network.graph.node_weight(node_idx) else {
+                            continue;
+                        };
+                        let ring = colors.ring_color(
+                            self.uistate.pinned_node == Some(node_idx),
+                            self.uistate.selected_node == Some(node_idx),
+                        );
+                        if let Some(ring) = ring {
+                            handle.draw_ring(
+                                transform.world_to_screen(node.point),
+                                node_radius as f32 + 2.0,
+                                node_radius as f32 + 5.0,
+                                0.0,
+                                360.0,
+                                32,
+                                ring,
+                            );
+                        }
+                    }
+
+                    for node_idx in self.network.graph.node_indices() {
+                        let node = &self.network.graph[node_idx];
+                        // Single points of failure stand out in a warning color
+                        let community = communities
+                            .as_ref()
+                            .map(|communities| communities[&node_idx]);
+                        let fill = if let Some(community) = community {
+                            COMMUNITY_PALETTE[community % COMMUNITY_PALETTE.len()]
+                        } else if articulation_points.contains(&node_idx) {
+                            Color::ORANGE
+                        } else {
+                            colors.node_fill
+                        };
+                        let fill = if (hovered_node.is_some_and(|hovered| hovered != node_idx)
+                            && !hover_neighbors.contains(&node_idx))
+                            || unreachable(node_idx)
+                        {
+                            fill.fade(0.2)
+                        } else {
+                            fill
+                        };
+                        let center = transform.world_to_screen(node.point);
                         handle.draw_circle(
-                            node.point.0 + self.canvas_offset_x,
-                            node.point.1,
-                            18.0,
-                            Color::WHITE,
+                            center.x as i32,
+                            center.y as i32,
+                            node_radius as f32,
+                            fill,
                         );
 
-                        let text = node.id.as_str();
+                        if !show_node_labels {
+                            continue;
+                        }
+
+                        // The hover tooltip still shows the full id
                         let font_size = 12;
-                        let text_width = handle.measure_text(text, font_size);
+                        let degree = degrees
+                            .as_ref()
+                            .and_then(|degrees| degrees.get(&node_idx).copied());
+                        let text = node_label(
+                            &node.id,
+                            degree,
+                            (2.0 * node_radius) as i32,
+                            font_size,
+                            |text, size| handle.measure_text(text, size),
+                        );
+                        let text_width = handle.measure_text(&text, font_size);
                         let text_height = font_size;
 
-                        let text_x = node.point.0 + self.canvas_offset_x - text_width / 2;
-                        let text_y = node.point.1 - text_height / 2;
+                        let text_x = center.x as i32 - text_width / 2;
+                        let text_y = center.y as i32 - text_height / 2;
+
+                        let text_color = if community.is_some() {
+                            Color::BLACK
+                        } else {
+                            colors.node_text
+                        };
+                        handle.draw_text(&text, text_x, text_y, font_size, text_color);
+                    }
+
+                    if let Some(packet) = &self.packet {
+                        let points: Vec<(f64, f64)> = packet
+                            .path
+                            .iter()
+                            .map(|&idx| self.network.graph[idx].point)
+                            .collect();
+                        if let Some(point) =
+                            lerp_along_path(&points, packet.segment, packet.progress)
+                        {
+                            let center = transform.world_to_screen(point);
+                            handle.draw_circle(center.x as i32, center.y as i32, 6.0, Color::LIME);
+                        }
+                    }
+
+                    if let Some((start_x, start_y)) = self.selection_start {
+                        let start = transform.world_to_screen((start_x, start_y));
+                        let (start_x, start_y) = (start.x as i32, start.y as i32);
+                        let (end_x, end_y) = (mouse_pos.x as i32, mouse_pos.y as i32);
+                        handle.draw_rectangle_lines(
+                            start_x.min(end_x),
+                            start_y.min(end_y),
+                            (end_x - start_x).abs(),
+                            (end_y - start_y).abs(),
+                            Color::SKYBLUE,
+                        );
+                    }
+
+                    minimap.draw(&mut handle, &self.network, viewport);
+
+                    // Status line along the bottom of the canvas
+                    let status = status_text(
+                        &self.network,
+                        transform,
+                        mouse_pos,
+                        (self.hovered_node, self.hovered_edge),
+                    );
+                    handle.draw_text(
+                        &status,
+                        self.canvas_offset_x + 10,
+                        canvas_height - 18,
+                        12,
+                        colors.edge_label,
+                    );
+
+                    if let Some((min, max)) = capacity_range {
+                        // Capacity key in the bottom-left corner, above the status line
+                        let x = self.canvas_offset_x + 10;
+                        let y = canvas_height - 110;
+                        let label_color = colors.edge_label;
+                        handle.draw_text("Capacity", x, y, 14, label_color);
+                        handle.draw_rectangle_gradient_h(
+                            x,
+                            y + 20,
+                            120,
+                            10,
+                            CAPACITY_LOW,
+                            CAPACITY_HIGH,
+                        );
+                        handle.draw_text(&min.to_string(), x, y + 34, 12, label_color);
+                        let max_text = max.to_string();
+                        let max_width = handle.measure_text(&max_text, 12);
+                        handle.draw_text(&max_text, x + 120 - max_width, y + 34, 12, label_color);
+                        let middle = min + (max - min) / 2;
+                        for (i, capacity) in [min, middle, max].into_iter().enumerate() {
+                            let sample_y = (y + 58 + i as i32 * 10) as f32;
+                            handle.draw_line_ex(
+                                Vector2::new(x as f32, sample_y),
+                                Vector2::new(x as f32 + 40.0, sample_y),
+                                capacity_thickness(capacity, (min, max)),
+                                label_color,
+                            );
+                            handle.draw_text(
+                                &capacity.to_string(),
+                                x + 48,
+                                sample_y as i32 - 5,
+                                10,
+                                label_color,
+                            );
+                        }
+                    }
 
-                        handle.draw_text(text, text_x, text_y, font_size, Color::BLACK);
+                    if self.show_stats_overlay {
+                        let lines = [
+                            format!("FPS: {}", handle.get_fps()),
+                            format!("Nodes: {}", self.network.graph.node_count()),
+                            format!("Edges: {}", self.network.graph.edge_count()),
+                            format!(
+                                "Layout: {} iterations, max displacement {:.2}",
+                                self.layout_run.iterations, self.layout_run.max_displacement
+                            ),
+                        ];
+                        for (i, line) in lines.iter().enumerate() {
+                            handle.draw_text(
+                                line,
+                                self.canvas_offset_x + 10,
+                                10 + i as i32 * 20,
+                                16,
+                                Color::LIME,
+                            );
+                        }
                     }
 
                     // init imgui
-                    init_ui(&handle, message_queue, &self.network, &mut self.uistate);
+                    init_ui(
+                        &handle,
+                        message_queue,
+                        &self.network,
+                        &mut self.uistate,
+                        viewport,
+                        (self.hovered_node, self.hovered_edge),
+                    );
                 },
             );
         });
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ring_links(count: usize) -> Vec<Link> {
+        (0..count)
+            .map(|i| Link {
+                link_id: format!("link{}", i),
+                source_node: format!("N{}", i),
+                destination_node: format!("N{}", (i + 1) % count),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .collect()
+    }
+
+    /// The network of an [`initial_network`] result once its layout has run to the end.
+    fn laid_out((mut network, layout): (Network, Option<LayoutProgress>)) -> Network {
+        if let Some(mut progress) = layout {
+            while network.layout_step(&mut progress) {}
+        }
+        network
+    }
+
+    #[test]
+    fn test_initial_network_uses_window_size() {
+        for (width, height) in [(800, 600), (1600, 1000), (500, 400)] {
+            let mut rng = StdRng::seed_from_u64(7);
+            let network = laid_out(
+                initial_network(
+                    ring_links(12),
+                    &[],
+                    width,
+                    height,
+                    DEFAULT_SIDEBAR_WIDTH as i32,
+                    &mut rng,
+                )
+                .unwrap(),
+            );
+
+            // The force layout keeps a 50px margin inside the canvas it was given
+            let canvas_width = (width - DEFAULT_SIDEBAR_WIDTH as i32) as f64;
+            let (min_x, min_y, max_x, max_y) = network.bounds();
+            assert!(min_x >= 50.0 && max_x <= canvas_width - 50.0);
+            assert!(min_y >= 50.0 && max_y <= height as f64 - 50.0);
+        }
+
+        // A larger window gives the layout room to spread out further
+        let mut rng = StdRng::seed_from_u64(7);
+        let small = laid_out(
+            initial_network(
+                ring_links(12),
+                &[],
+                500,
+                400,
+                DEFAULT_SIDEBAR_WIDTH as i32,
+                &mut rng,
+            )
+            .unwrap(),
+        );
+        let mut rng = StdRng::seed_from_u64(7);
+        let large = laid_out(
+            initial_network(
+                ring_links(12),
+                &[],
+                1600,
+                1000,
+                DEFAULT_SIDEBAR_WIDTH as i32,
+                &mut rng,
+            )
+            .unwrap(),
+        );
+        let span = |network: &Network| {
+            let (min_x, _, max_x, _) = network.bounds();
+            max_x - min_x
+        };
+        assert!(span(&large) > span(&small));
+    }
+
+    #[test]
+    fn test_hover_target() {
+        let mut network = Network::new();
+        let a = network.add_node(Node {
+            id: "A".to_string(),
+            point: (100.0, 100.0),
+            ..Node::default()
+        });
+        let radius = 18.0;
+
+        assert_eq!(
+            hover_target(&network, (105.0, 95.0), true, radius, false),
+            Some(a)
+        );
+        assert_eq!(
+            hover_target(&network, (150.0, 100.0), true, radius, false),
+            None
+        );
+        // No hover highlight while dragging
+        assert_eq!(
+            hover_target(&network, (100.0, 100.0), true, radius, true),
+            None
+        );
+        // A node scrolled underneath the sidebar is not hoverable
+        assert_eq!(
+            hover_target(&network, (100.0, 100.0), false, radius, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_view_transform_round_trip() {
+        let offset = DEFAULT_SIDEBAR_WIDTH as i32;
+        let transform = ViewTransform::new(offset, (20.0, -10.0), 1.0);
+
+        // Screen coordinates are shifted by the sidebar and the view offset
+        let screen = transform.world_to_screen((100.0, 100.0));
+        assert_eq!((screen.x, screen.y), (offset as f32 + 120.0, 90.0));
+        assert_eq!(transform.screen_to_world(screen), (100.0, 100.0));
+
+        for (x, y) in [(0.0, 0.0), (-37.5, 412.25), (800.0, -3.0)] {
+            assert_eq!(
+                transform.screen_to_world(transform.world_to_screen((x, y))),
+                (x, y)
+            );
+            let screen = Vector2::new(x as f32, y as f32);
+            let back = transform.world_to_screen(transform.screen_to_world(screen));
+            assert_eq!((back.x, back.y), (screen.x, screen.y));
+        }
+    }
+
+    #[test]
+    fn test_fit_view() {
+        // A 400 x 200 graph in an 800 x 600 canvas with a 50px margin: width decides
+        let (zoom, pan) = fit_view((100.0, 100.0, 500.0, 300.0), (800.0, 600.0), 50.0);
+        assert_eq!(zoom, 1.75);
+        assert_eq!(pan, (400.0 - 300.0 * 1.75, 300.0 - 200.0 * 1.75));
+        let transform = ViewTransform::new(0, pan, zoom);
+        let corner = transform.world_to_screen((100.0, 100.0));
+        assert_eq!((corner.x, corner.y), (50.0, 125.0));
+
+        // A tall graph is limited by the height and zooms out
+        let (zoom, _) = fit_view((0.0, 0.0, 100.0, 2500.0), (800.0, 600.0), 50.0);
+        assert_eq!(zoom, 0.2);
+
+        // A single point gets the closest zoom, centered
+        let (zoom, pan) = fit_view((10.0, 20.0, 10.0, 20.0), (800.0, 600.0), 50.0);
+        assert_eq!(zoom, MAX_ZOOM);
+        assert_eq!(pan, (400.0 - 40.0, 300.0 - 80.0));
+    }
+
+    #[test]
+    fn test_missing_network_file_starts_empty() {
+        let (links, error) = load_startup_links("configuration/does-not-exist.csv");
+        assert!(links.is_empty());
+        assert!(error.is_some_and(|message| message.contains("does-not-exist.csv")));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let (network, _) = initial_network(links, &[], 800, 600, 250, &mut rng).unwrap();
+        assert_eq!(network.graph.node_count(), 0);
+        assert_eq!(network.graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_startup_in_small_window() {
+        // 300px wide leaves a 50px canvas next to the sidebar, too narrow for the margins
+        for (width, height) in [(300, 600), (800, 80), (200, 600)] {
+            let mut rng = StdRng::seed_from_u64(1);
+            let (mut network, layout) =
+                initial_network(ring_links(6), &[], width, height, 250, &mut rng).unwrap();
+            let mut layout = layout.expect("Expected a force layout to run");
+            while network.layout_step(&mut layout) {}
+            for node in network.nodes() {
+                assert!(node.point.0.is_finite() && node.point.1.is_finite());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reload_and_relayout_after_shrinking() {
+        // The window was shrunk until the canvas is narrower than both margins
+        let canvas = (30.0, 400.0);
+        let mut network = place_on_canvas(ring_links(5), canvas, &mut StdRng::seed_from_u64(2))
+            .expect("Failed to place network");
+        assert!(network.nodes().all(|node| node.point.0 == 15.0));
+
+        let config = LayoutConfig::default();
+        assert!(relax_layout(&mut network, canvas, &config, None, false).is_some());
+        for node in network.nodes() {
+            assert_eq!(node.point.0, 15.0);
+            assert!((50.0..=350.0).contains(&node.point.1), "{:?}", node);
+        }
+    }
+
+    #[test]
+    fn test_relax_layout_skipped_when_frozen() {
+        let mut network = Network::with_random_placement(
+            ring_links(6),
+            (50.0, 50.0, 550.0, 550.0),
+            &mut StdRng::seed_from_u64(3),
+        )
+        .unwrap();
+        let before = network.export_positions();
+
+        let config = LayoutConfig::default();
+        assert_eq!(
+            relax_layout(&mut network, (600.0, 600.0), &config, None, true),
+            None
+        );
+        assert_eq!(network.export_positions(), before);
+
+        assert!(relax_layout(&mut network, (600.0, 600.0), &config, None, false).is_some());
+        assert_ne!(network.export_positions(), before);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let path = std::env::temp_dir().join("net_modeler-settings.json");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(load_settings(path), Settings::default());
+
+        let settings = Settings {
+            freeze_layout: true,
+        };
+        save_settings(path, &settings).unwrap();
+        assert_eq!(load_settings(path), settings);
+        std::fs::remove_file(path).expect("Failed to remove settings");
+    }
+
+    #[test]
+    fn test_status_text() {
+        let mut network = Network::new();
+        let a = network.add_node(Node {
+            id: "A".to_string(),
+            point: (100.0, 100.0),
+            ..Node::default()
+        });
+        network.add_node(Node {
+            id: "B".to_string(),
+            ..Node::default()
+        });
+        network
+            .add_link(Link {
+                link_id: "ab".to_string(),
+                source_node: "A".to_string(),
+                destination_node: "B".to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .unwrap();
+        let edge = network.link_indices["ab"];
+        let transform = ViewTransform::new(250, (-20.0, 10.0), 1.0);
+
+        // The readout shows world coordinates, not screen ones
+        let mouse = Vector2::new(330.0, 110.0);
+        assert_eq!(
+            status_text(&network, transform, mouse, (None, None)),
+            "x: 100.0  y: 100.0"
+        );
+        assert_eq!(
+            status_text(&network, transform, mouse, (Some(a), None)),
+            "x: 100.0  y: 100.0  node: A"
+        );
+        assert_eq!(
+            status_text(
+                &network,
+                transform,
+                Vector2::new(250.0, 0.0),
+                (None, Some(edge))
+            ),
+            "x: 20.0  y: -10.0  link: ab"
+        );
+    }
+
+    #[test]
+    fn test_exceeds_drag_threshold() {
+        let press = Vector2::new(100.0, 100.0);
+        assert!(!exceeds_drag_threshold(press, press, DRAG_THRESHOLD));
+        assert!(!exceeds_drag_threshold(
+            press,
+            Vector2::new(102.0, 103.0),
+            DRAG_THRESHOLD
+        ));
+        assert!(exceeds_drag_threshold(
+            press,
+            Vector2::new(103.0, 103.0),
+            DRAG_THRESHOLD
+        ));
+        assert!(exceeds_drag_threshold(
+            press,
+            Vector2::new(90.0, 100.0),
+            DRAG_THRESHOLD
+        ));
+    }
+
+    #[test]
+    fn test_zoom_around() {
+        let (zoom, pan) = zoom_around(1.0, (10.0, 20.0), 2.0, (110.0, 70.0));
+        assert_eq!(zoom, 2.0);
+        // The world point under the anchor, (100, 50), stays under it
+        let transform = ViewTransform::new(0, pan, zoom);
+        assert_eq!(
+            transform.world_to_screen((100.0, 50.0)),
+            Vector2::new(110.0, 70.0)
+        );
+
+        // Zooming past the limits stops at them, still around the anchor
+        let (zoom, pan) = zoom_around(MAX_ZOOM, (0.0, 0.0), ZOOM_STEP, (200.0, 100.0));
+        assert_eq!((zoom, pan), (MAX_ZOOM, (0.0, 0.0)));
+        let (zoom, _) = zoom_around(MIN_ZOOM, (0.0, 0.0), 1.0 / ZOOM_STEP, (200.0, 100.0));
+        assert_eq!(zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_wheel_weight() {
+        assert_eq!(wheel_weight(10.0, 1.0), 11.0);
+        assert_eq!(wheel_weight(10.0, -1.0), 9.0);
+        // A long scroll or a fraction of a notch still moves one step
+        assert_eq!(wheel_weight(10.0, 3.0), 11.0);
+        assert_eq!(wheel_weight(10.0, -0.25), 9.0);
+        assert_eq!(wheel_weight(10.0, 0.0), 10.0);
+        // Fractional weights keep their fraction, and nothing goes below zero
+        assert_eq!(wheel_weight(2.5, 1.0), 3.5);
+        assert_eq!(wheel_weight(0.5, -1.0), 0.0);
+        assert_eq!(wheel_weight(0.0, -1.0), 0.0);
+    }
+
+    #[test]
+    fn test_drag_position_clamped_and_free() {
+        let bounds = Some((50.0, 50.0, 550.0, 350.0));
+        // Inside the canvas both modes follow the mouse minus the grab offset
+        assert_eq!(
+            drag_position((210.0, 120.0), (10.0, -5.0), None, bounds),
+            (200.0, 125.0)
+        );
+        assert_eq!(
+            drag_position((210.0, 120.0), (10.0, -5.0), None, None),
+            (200.0, 125.0)
+        );
+
+        // Past the margin a clamped drag stops while free placement keeps going
+        assert_eq!(
+            drag_position((900.0, -40.0), (0.0, 0.0), None, bounds),
+            (550.0, 50.0)
+        );
+        assert_eq!(
+            drag_position((900.0, -40.0), (0.0, 0.0), None, None),
+            (900.0, -40.0)
+        );
+
+        // Snapping happens before clamping
+        assert_eq!(
+            drag_position((112.0, 38.0), (0.0, 0.0), Some(25.0), bounds),
+            (100.0, 50.0)
+        );
+        assert_eq!(
+            drag_position((112.0, 38.0), (0.0, 0.0), Some(25.0), None),
+            (100.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn test_node_label_with_degree() {
+        // Every character is half as wide as the font size is tall, the node 36px across
+        let measure = |text: &str, font_size: i32| text.chars().count() as i32 * font_size / 2;
+        let label = |id: &str, degree: Option<usize>| node_label(id, degree, 36, 12, measure);
+        assert_eq!(label("A", None), "A");
+        assert_eq!(label("A", Some(3)), "A (3)");
+        assert_eq!(label("Core-1", Some(0)), "Core-1 (0)");
+        // A long id is cut short, but the degree is kept
+        assert_eq!(label("Router-12", Some(3)), "Rou... (3)");
+        assert_eq!(label("Router-12", None), "Rou...");
+
+        let network = Network::with_random_placement(
+            ring_links(5),
+            (0.0, 0.0, 100.0, 100.0),
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+        let degrees = network.degree_centrality();
+        let idx = network.node_indices["N0"];
+        assert_eq!(label("N0", degrees.get(&idx).copied()), "N0 (2)");
+    }
+
+    #[test]
+    fn test_fit_label() {
+        // Every character is as wide as the font size is tall
+        let measure = |text: &str, font_size: i32| text.chars().count() as i32 * font_size;
+
+        assert_eq!(fit_label("R1", 36, 12, measure), "R1");
+        assert_eq!(fit_label("Router", 72, 12, measure), "Router");
+        assert_eq!(fit_label("Router", 71, 12, measure), "Ro...");
+        assert_eq!(fit_label("Router", 48, 12, measure), "R...");
+        assert_eq!(fit_label("Router", 36, 12, measure), "...");
+        assert_eq!(fit_label("Router", 20, 12, measure), "");
+        // Cut on character boundaries
+        assert_eq!(fit_label("Zürich-Core", 60, 12, measure), "Zü...");
+    }
+
+    #[test]
+    fn test_link_stroke_scales_with_edge_thickness() {
+        let edge = Color::GRAY;
+        assert_eq!(
+            link_stroke(LinkEmphasis::Plain, edge, DEFAULT_EDGE_THICKNESS),
+            (edge, DEFAULT_EDGE_THICKNESS)
+        );
+        assert_eq!(link_stroke(LinkEmphasis::Plain, edge, 5.0), (edge, 5.0));
+        // Emphasized links keep their proportion to plain ones
+        assert_eq!(
+            link_stroke(LinkEmphasis::Path, edge, DEFAULT_EDGE_THICKNESS),
+            (Color::RED, 4.0)
+        );
+        assert_eq!(
+            link_stroke(LinkEmphasis::Path, edge, 5.0),
+            (Color::RED, 10.0)
+        );
+        assert_eq!(link_stroke(LinkEmphasis::Bridge, edge, 1.0).1, 1.5);
+    }
+}
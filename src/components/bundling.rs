@@ -0,0 +1,267 @@
+/// A straight link between two points, as `(start, end)`.
+pub type Segment = ((f64, f64), (f64, f64));
+
+/// Tuning for [`bundle_edges`], after Holten and van Wijk's force-directed edge bundling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BundleConfig {
+    /// Rounds of refinement; each one doubles the points an edge is split into.
+    pub cycles: usize,
+    /// Force steps in the first cycle, dropping by a third every cycle after.
+    pub iterations: usize,
+    /// How far a point moves per unit of force in the first cycle, halved every cycle after.
+    pub step: f64,
+    /// Spring constant keeping each edge's points together.
+    pub stiffness: f64,
+    /// Edges less compatible than this, see [`compatibility`], do not pull on each other.
+    pub threshold: f64,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        BundleConfig {
+            cycles: 5,
+            iterations: 50,
+            step: 0.1,
+            stiffness: 0.1,
+            threshold: 0.6,
+        }
+    }
+}
+
+/// How readily two edges bundle, from 0.0 to 1.0: the product of how parallel they are, how
+/// close in length, and how close together. 0.0 when either edge has no length.
+pub fn compatibility(a: Segment, b: Segment) -> f64 {
+    let (ax, ay) = (a.1.0 - a.0.0, a.1.1 - a.0.1);
+    let (bx, by) = (b.1.0 - b.0.0, b.1.1 - b.0.1);
+    let (length_a, length_b) = ((ax * ax + ay * ay).sqrt(), (bx * bx + by * by).sqrt());
+    if length_a == 0.0 || length_b == 0.0 {
+        return 0.0;
+    }
+
+    let angle = (ax * bx + ay * by).abs() / (length_a * length_b);
+    let average = (length_a + length_b) / 2.0;
+    let scale = 2.0 / (average / length_a.min(length_b) + length_a.max(length_b) / average);
+    let (mid_a, mid_b) = (midpoint(a.0, a.1), midpoint(b.0, b.1));
+    let gap = ((mid_a.0 - mid_b.0).powi(2) + (mid_a.1 - mid_b.1).powi(2)).sqrt();
+    let position = average / (average + gap);
+    angle * scale * position
+}
+
+/// Control points of every segment after bundling, endpoints included, in the order of
+/// `segments`. Compatible edges attract each other point by point while springs keep each
+/// edge smooth, so nearly parallel edges merge into shared bundles. Only the drawing
+/// changes; the endpoints stay where they are.
+pub fn bundle_edges(segments: &[Segment], config: &BundleConfig) -> Vec<Vec<(f64, f64)>> {
+    // Partners of each edge, and whether they run the other way so their points pair up
+    // in reverse
+    let partners: Vec<Vec<(usize, bool)>> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, &a)| {
+            segments
+                .iter()
+                .enumerate()
+                .filter(|&(j, &b)| i != j && compatibility(a, b) >= config.threshold)
+                .map(|(j, &b)| (j, runs_against(a, b)))
+                .collect()
+        })
+        .collect();
+
+    let mut points: Vec<Vec<(f64, f64)>> = segments
+        .iter()
+        .map(|&(start, end)| vec![start, end])
+        .collect();
+    let mut step = config.step;
+    let mut iterations = config.iterations as f64;
+    for cycle in 0..config.cycles {
+        let inner = 1 << cycle;
+        for polyline in &mut points {
+            *polyline = resample(polyline, inner + 2);
+        }
+
+        for _ in 0..iterations.round() as usize {
+            let forces: Vec<Vec<(f64, f64)>> = (0..segments.len())
+                .map(|i| edge_forces(i, segments[i], &points, &partners[i], config.stiffness))
+                .collect();
+            for (polyline, forces) in points.iter_mut().zip(forces) {
+                for (point, (fx, fy)) in polyline.iter_mut().zip(forces) {
+                    point.0 += step * fx;
+                    point.1 += step * fy;
+                }
+            }
+        }
+        step /= 2.0;
+        iterations *= 2.0 / 3.0;
+    }
+    points
+}
+
+/// Force on every point of edge `i`: springs to its neighbours along the edge plus a unit
+/// pull towards the matching point of each partner. The endpoints get none.
+fn edge_forces(
+    i: usize,
+    segment: Segment,
+    points: &[Vec<(f64, f64)>],
+    partners: &[(usize, bool)],
+    stiffness: f64,
+) -> Vec<(f64, f64)> {
+    let polyline = &points[i];
+    let last = polyline.len() - 1;
+    let (dx, dy) = (segment.1.0 - segment.0.0, segment.1.1 - segment.0.1);
+    let spring = stiffness / ((dx * dx + dy * dy).sqrt().max(1.0) * last as f64);
+
+    let mut forces = vec![(0.0, 0.0); polyline.len()];
+    for k in 1..last {
+        let (prev, point, next) = (polyline[k - 1], polyline[k], polyline[k + 1]);
+        let mut fx = spring * (prev.0 + next.0 - 2.0 * point.0);
+        let mut fy = spring * (prev.1 + next.1 - 2.0 * point.1);
+        for &(j, reversed) in partners {
+            let other = points[j][if reversed { last - k } else { k }];
+            let (ox, oy) = (other.0 - point.0, other.1 - point.1);
+            let distance = (ox * ox + oy * oy).sqrt();
+            if distance > 1e-6 {
+                fx += ox / distance;
+                fy += oy / distance;
+            }
+        }
+        forces[k] = (fx, fy);
+    }
+    forces
+}
+
+/// `count` points spaced evenly along the polyline `points`, starting and ending exactly on
+/// its first and last point.
+fn resample(points: &[(f64, f64)], count: usize) -> Vec<(f64, f64)> {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let lengths: Vec<f64> = points
+        .windows(2)
+        .map(|leg| ((leg[1].0 - leg[0].0).powi(2) + (leg[1].1 - leg[0].1).powi(2)).sqrt())
+        .collect();
+    let total: f64 = lengths.iter().sum();
+
+    let mut resampled = vec![first];
+    let (mut leg, mut walked) = (0, 0.0);
+    for n in 1..count - 1 {
+        let target = total * n as f64 / (count - 1) as f64;
+        while leg < lengths.len() - 1 && walked + lengths[leg] < target {
+            walked += lengths[leg];
+            leg += 1;
+        }
+        let t = if lengths[leg] == 0.0 {
+            0.0
+        } else {
+            ((target - walked) / lengths[leg]).clamp(0.0, 1.0)
+        };
+        let (start, end) = (points[leg], points[leg + 1]);
+        resampled.push((
+            start.0 + (end.0 - start.0) * t,
+            start.1 + (end.1 - start.1) * t,
+        ));
+    }
+    resampled.push(last);
+    resampled
+}
+
+/// Points for drawing the control polyline `points` as a smooth chain of quadratic Béziers,
+/// laid out as raylib's `draw_spline_bezier_quadratic` expects: every inner control point
+/// steers one curve, and consecutive curves meet halfway between their control points. A
+/// straight two-point line gets its midpoint as the control.
+pub fn bezier_chain(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    match points {
+        [] | [_] => points.to_vec(),
+        [start, end] => vec![*start, midpoint(*start, *end), *end],
+        [start, inner @ .., end] => {
+            let mut chain = vec![*start, inner[0]];
+            for pair in inner.windows(2) {
+                chain.push(midpoint(pair[0], pair[1]));
+                chain.push(pair[1]);
+            }
+            chain.push(*end);
+            chain
+        }
+    }
+}
+
+/// Whether `b` points roughly the opposite way to `a`.
+fn runs_against(a: Segment, b: Segment) -> bool {
+    (a.1.0 - a.0.0) * (b.1.0 - b.0.0) + (a.1.1 - a.0.1) * (b.1.1 - b.0.1) < 0.0
+}
+
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// The bundled curves of the last segments asked for, so the bundling only reruns when a
+/// link is added, removed or moved.
+#[derive(Debug, Default)]
+pub struct BundleCache {
+    segments: Vec<Segment>,
+    curves: Vec<Vec<(f64, f64)>>,
+}
+
+impl BundleCache {
+    /// Control points of every segment in `segments`, see [`bundle_edges`].
+    pub fn curves(&mut self, segments: Vec<Segment>) -> &[Vec<(f64, f64)>] {
+        if segments != self.segments {
+            self.curves = bundle_edges(&segments, &BundleConfig::default());
+            self.segments = segments;
+        }
+        &self.curves
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_two_parallel_edges_bundle() {
+        let segments = [((0.0, 0.0), (100.0, 0.0)), ((0.0, 10.0), (100.0, 10.0))];
+        let config = BundleConfig::default();
+        let curves = bundle_edges(&segments, &config);
+
+        // Five cycles split each edge into 16 inner points
+        assert_eq!(curves.len(), 2);
+        for (curve, (start, end)) in curves.iter().zip(segments) {
+            assert_eq!(curve.len(), 18);
+            assert_eq!((curve[0], curve[17]), (start, end));
+        }
+
+        // The middles are pulled together until they nearly meet, evenly from either side
+        let (low, high) = (curves[0][8], curves[1][8]);
+        assert!(low.1 > 4.0 && high.1 < 6.0, "{:?} {:?}", low, high);
+        assert!((low.1 + high.1 - 10.0).abs() < 1e-9);
+        assert!((low.0 - high.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_crossing_edges_stay_straight() {
+        let segments = [((0.0, 50.0), (100.0, 50.0)), ((50.0, 0.0), (50.0, 100.0))];
+        assert_eq!(compatibility(segments[0], segments[1]), 0.0);
+
+        let curves = bundle_edges(&segments, &BundleConfig::default());
+        assert!(curves[0].iter().all(|&(_, y)| (y - 50.0).abs() < 1e-9));
+        assert!(curves[1].iter().all(|&(x, _)| (x - 50.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_bezier_chain() {
+        let line = [(0.0, 0.0), (10.0, 0.0)];
+        assert_eq!(
+            bezier_chain(&line),
+            vec![(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)]
+        );
+
+        let points = [(0.0, 0.0), (10.0, 10.0), (20.0, 10.0), (30.0, 0.0)];
+        assert_eq!(
+            bezier_chain(&points),
+            vec![
+                (0.0, 0.0),
+                (10.0, 10.0),
+                (15.0, 10.0),
+                (20.0, 10.0),
+                (30.0, 0.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    app::AppMsg,
+    components::network::{parse_capacity, parse_weight},
+};
+
+/// Parses one line typed into the command box into the message it stands for.
+///
+/// Supported commands:
+/// - `add <id>`: add a node at the center of the canvas
+/// - `link <src> <dst> <capacity> <weight>`: link two existing nodes; the capacity may carry
+///   a unit, like `1Gbps`
+/// - `del <id>`: delete a node and its links
+/// - `path <src> <dst>`: highlight the shortest path
+/// - `maxflow <src> <dst>`: compute the maximum flow between two nodes
+/// - `load <path>...`: replace the network with one or more merged link CSVs
+/// - `stats`: show the network statistics
+pub fn parse_command(input: &str) -> Result<AppMsg, String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let Some((&verb, args)) = words.split_first() else {
+        return Err("Empty command".to_string());
+    };
+
+    match (verb, args) {
+        ("add", [id]) => Ok(AppMsg::AddNode(id.to_string())),
+        ("add", _) => Err("Usage: add <id>".to_string()),
+        ("link", [source, destination, capacity, weight]) => Ok(AppMsg::AddLink(
+            source.to_string(),
+            destination.to_string(),
+            parse_capacity(capacity)?,
+            parse_weight(weight)?,
+        )),
+        ("link", _) => Err("Usage: link <src> <dst> <capacity> <weight>".to_string()),
+        ("del", [id]) => Ok(AppMsg::DeleteNode(id.to_string())),
+        ("del", _) => Err("Usage: del <id>".to_string()),
+        ("path", [start, end]) => Ok(AppMsg::ComputeShortestPath(
+            start.to_string(),
+            end.to_string(),
+        )),
+        ("path", _) => Err("Usage: path <src> <dst>".to_string()),
+        ("maxflow", [source, sink]) => {
+            Ok(AppMsg::ComputeMaxFlow(source.to_string(), sink.to_string()))
+        }
+        ("maxflow", _) => Err("Usage: maxflow <src> <dst>".to_string()),
+        ("load", []) => Err("Usage: load <path>...".to_string()),
+        ("load", paths) => Ok(AppMsg::LoadNetworks(
+            paths.iter().map(|path| path.to_string()).collect(),
+        )),
+        ("stats", []) => Ok(AppMsg::ShowStats),
+        ("stats", _) => Err("Usage: stats".to_string()),
+        _ => Err(format!("Unknown command '{}'", verb)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_add() {
+        assert_eq!(parse_command("add A"), Ok(AppMsg::AddNode("A".to_string())));
+        assert_eq!(
+            parse_command("  add   Router-1 "),
+            Ok(AppMsg::AddNode("Router-1".to_string()))
+        );
+        assert!(parse_command("add").is_err());
+        assert!(parse_command("add A B").is_err());
+    }
+
+    #[test]
+    fn test_parse_link() {
+        assert_eq!(
+            parse_command("link A B 100 10"),
+            Ok(AppMsg::AddLink("A".to_string(), "B".to_string(), 100, 10.0))
+        );
+        assert!(parse_command("link A B 100").is_err());
+        assert_eq!(
+            parse_command("link A B 1Gbps 10"),
+            Ok(AppMsg::AddLink(
+                "A".to_string(),
+                "B".to_string(),
+                1000,
+                10.0
+            ))
+        );
+        assert!(parse_command("link A B fast 10").is_err());
+        assert_eq!(
+            parse_command("link A B 100 2.5"),
+            Ok(AppMsg::AddLink("A".to_string(), "B".to_string(), 100, 2.5))
+        );
+        assert_eq!(
+            parse_command("link A B 100 x"),
+            Err("Invalid weight 'x': expected a non-negative number".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_del_and_path() {
+        assert_eq!(
+            parse_command("del A"),
+            Ok(AppMsg::DeleteNode("A".to_string()))
+        );
+        assert!(parse_command("del").is_err());
+        assert_eq!(
+            parse_command("path A B"),
+            Ok(AppMsg::ComputeShortestPath(
+                "A".to_string(),
+                "B".to_string()
+            ))
+        );
+        assert!(parse_command("path A").is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert_eq!(parse_command(""), Err("Empty command".to_string()));
+        assert_eq!(parse_command("   "), Err("Empty command".to_string()));
+        assert_eq!(
+            parse_command("explode A"),
+            Err("Unknown command 'explode'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_analysis_commands() {
+        assert_eq!(
+            parse_command("maxflow S T"),
+            Ok(AppMsg::ComputeMaxFlow("S".to_string(), "T".to_string()))
+        );
+        assert!(parse_command("maxflow S").is_err());
+        assert_eq!(
+            parse_command("load west.csv east.csv"),
+            Ok(AppMsg::LoadNetworks(vec![
+                "west.csv".to_string(),
+                "east.csv".to_string()
+            ]))
+        );
+        assert!(parse_command("load").is_err());
+        assert_eq!(parse_command("stats"), Ok(AppMsg::ShowStats));
+    }
+}
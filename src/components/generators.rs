@@ -0,0 +1,123 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::components::network::{Link, Network, Node};
+
+/// Erdős–Rényi G(n, p) graph: `n` nodes where every pair is linked independently with
+/// probability `p`. Positions, capacities and weights are random but fixed by `seed`.
+pub fn erdos_renyi(n: usize, p: f64, seed: u64) -> Network {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut network = random_nodes(n, &mut rng);
+
+    for a in 0..n {
+        for b in a + 1..n {
+            if rng.random_bool(p.clamp(0.0, 1.0)) {
+                add_random_link(&mut network, a, b, &mut rng);
+            }
+        }
+    }
+    network
+}
+
+/// Barabási–Albert scale-free graph: starts from a clique of `m + 1` nodes, then each new
+/// node links to `m` distinct existing nodes chosen with probability proportional to their
+/// degree, until there are `n` nodes. Randomness is fixed by `seed`.
+pub fn barabasi_albert(n: usize, m: usize, seed: u64) -> Network {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut network = random_nodes(n, &mut rng);
+
+    // Every link end is listed once, so sampling from it is degree-proportional
+    let mut ends: Vec<usize> = Vec::new();
+    let initial = n.min(m + 1);
+    for a in 0..initial {
+        for b in a + 1..initial {
+            add_random_link(&mut network, a, b, &mut rng);
+            ends.extend([a, b]);
+        }
+    }
+
+    for node in initial..n {
+        let mut targets: Vec<usize> = Vec::new();
+        while targets.len() < m {
+            let target = ends[rng.random_range(0..ends.len())];
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for target in targets {
+            add_random_link(&mut network, node, target, &mut rng);
+            ends.extend([node, target]);
+        }
+    }
+    network
+}
+
+/// `n` nodes named `n0`, `n1`, ... scattered over a 500px square.
+fn random_nodes(n: usize, rng: &mut impl Rng) -> Network {
+    let mut network = Network::new();
+    for i in 0..n {
+        network.add_node(Node {
+            id: format!("n{}", i),
+            point: (rng.random_range(0.0..500.0), rng.random_range(0.0..500.0)),
+            ..Node::default()
+        });
+    }
+    network
+}
+
+fn add_random_link(network: &mut Network, a: usize, b: usize, rng: &mut impl Rng) {
+    let link = Link {
+        link_id: (network.graph.edge_count() + 1).to_string(),
+        source_node: format!("n{}", a),
+        destination_node: format!("n{}", b),
+        capacity: rng.random_range(10..=100),
+        weight: f64::from(rng.random_range(1..=20_u8)),
+        ..Link::default()
+    };
+    // Both endpoints were created by `random_nodes`
+    network
+        .add_link(link)
+        .expect("generated link endpoints exist");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_erdos_renyi_counts() {
+        let network = erdos_renyi(100, 0.1, 42);
+        assert_eq!(network.graph.node_count(), 100);
+        // 4950 pairs at p = 0.1 gives 495 links on average, with a standard deviation of 21
+        let edges = network.graph.edge_count();
+        assert!(
+            (400..=600).contains(&edges),
+            "unexpected edge count {}",
+            edges
+        );
+
+        assert_eq!(erdos_renyi(20, 0.0, 1).graph.edge_count(), 0);
+        assert_eq!(erdos_renyi(20, 1.0, 1).graph.edge_count(), 190);
+    }
+
+    #[test]
+    fn test_erdos_renyi_is_reproducible() {
+        let a = erdos_renyi(30, 0.2, 7);
+        let b = erdos_renyi(30, 0.2, 7);
+        assert_eq!(a.export_positions(), b.export_positions());
+        assert_eq!(a.graph.edge_count(), b.graph.edge_count());
+    }
+
+    #[test]
+    fn test_barabasi_albert_counts() {
+        let network = barabasi_albert(50, 2, 3);
+        assert_eq!(network.graph.node_count(), 50);
+        // A 3-clique, then 47 nodes adding 2 links each
+        assert_eq!(network.graph.edge_count(), 3 + 47 * 2);
+        assert!(
+            network
+                .graph
+                .node_indices()
+                .all(|idx| network.graph.edges(idx).count() >= 2)
+        );
+    }
+}
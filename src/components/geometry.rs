@@ -0,0 +1,287 @@
+/// Offset of length `distance` perpendicular to the segment `start`-`end`, used to place a
+/// label beside an edge instead of on top of it. The normal is oriented so the label sits
+/// above the edge, or to its right when the edge is vertical, regardless of which endpoint
+/// comes first. A zero-length segment gets an offset straight up.
+pub fn perpendicular_offset(start: (f64, f64), end: (f64, f64), distance: f64) -> (f64, f64) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return (0.0, -distance);
+    }
+
+    let (mut nx, mut ny) = (-dy / length, dx / length);
+    // Screen y grows downwards, so "above" means a negative y component
+    if ny > 0.0 || (ny == 0.0 && nx < 0.0) {
+        nx = -nx;
+        ny = -ny;
+    }
+    (nx * distance, ny * distance)
+}
+
+/// Evenly spaced offsets, centered on zero, for fanning out `count` parallel edges so each
+/// one is drawn `spacing` apart from its neighbours.
+pub fn fan_offsets(count: usize, spacing: f64) -> Vec<f64> {
+    let center = (count as f64 - 1.0) / 2.0;
+    (0..count)
+        .map(|slot| (slot as f64 - center) * spacing)
+        .collect()
+}
+
+/// Shrinks `area` (`(min_x, min_y, max_x, max_y)`) by `margin` on every side. An axis too
+/// small to fit both margins collapses to its midpoint, so the result is never inverted.
+pub fn inset_area(area: (f64, f64, f64, f64), margin: f64) -> (f64, f64, f64, f64) {
+    let inset_axis = |min: f64, max: f64| {
+        if max - min >= 2.0 * margin {
+            (min + margin, max - margin)
+        } else {
+            let mid = (min + max) / 2.0;
+            (mid, mid)
+        }
+    };
+    let (min_x, max_x) = inset_axis(area.0, area.2);
+    let (min_y, max_y) = inset_axis(area.1, area.3);
+    (min_x, min_y, max_x, max_y)
+}
+
+/// Screen rectangle `(x, y, width, height)` of the canvas, i.e. everything right of the
+/// `sidebar_width` wide sidebar. A sidebar wider than the screen leaves an empty region.
+pub fn canvas_region(
+    screen_width: i32,
+    screen_height: i32,
+    sidebar_width: i32,
+) -> (i32, i32, i32, i32) {
+    let x = sidebar_width.clamp(0, screen_width.max(0));
+    (x, 0, (screen_width - x).max(0), screen_height.max(0))
+}
+
+/// Shortest distance from `point` to the segment `start`-`end`.
+pub fn distance_to_segment(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = dx * dx + dy * dy;
+    // Project onto the segment, clamping to the endpoints
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    let (closest_x, closest_y) = (start.0 + t * dx, start.1 + t * dy);
+    ((point.0 - closest_x).powi(2) + (point.1 - closest_y).powi(2)).sqrt()
+}
+
+/// Point `t` (0..=1) of the way along the `segment`-th leg of the polyline `points`, or
+/// `None` if there is no such leg.
+pub fn lerp_along_path(points: &[(f64, f64)], segment: usize, t: f64) -> Option<(f64, f64)> {
+    let (start, end) = (points.get(segment)?, points.get(segment + 1)?);
+    let t = t.clamp(0.0, 1.0);
+    Some((
+        start.0 + (end.0 - start.0) * t,
+        start.1 + (end.1 - start.1) * t,
+    ))
+}
+
+/// Corners of an arrowhead `length` long and `width` wide whose point sits at `tip`, aimed
+/// along the line coming in from `approach`. The corners are ordered counter-clockwise on
+/// screen, as raylib's `draw_triangle` expects. `None` when `approach` is `tip`.
+pub fn arrow_head(
+    tip: (f64, f64),
+    approach: (f64, f64),
+    length: f64,
+    width: f64,
+) -> Option<[(f64, f64); 3]> {
+    let (dx, dy) = (tip.0 - approach.0, tip.1 - approach.1);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance == 0.0 {
+        return None;
+    }
+
+    let (ux, uy) = (dx / distance, dy / distance);
+    let base = (tip.0 - ux * length, tip.1 - uy * length);
+    let half = width / 2.0;
+    Some([
+        tip,
+        (base.0 + uy * half, base.1 - ux * half),
+        (base.0 - uy * half, base.1 + ux * half),
+    ])
+}
+
+/// Default spacing of the canvas grid that dragged nodes snap to.
+pub const GRID_SIZE: f64 = 25.0;
+
+/// Multiples of `spacing` between `min` and `max` inclusive: where grid lines cross one
+/// axis of the visible world area. Empty for a non-positive `spacing`.
+pub fn grid_lines(min: f64, max: f64, spacing: f64) -> Vec<f64> {
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+    let first = (min / spacing).ceil() as i64;
+    let last = (max / spacing).floor() as i64;
+    (first..=last).map(|i| i as f64 * spacing).collect()
+}
+
+/// Rounds `value` to the nearest multiple of `grid`, with halfway values rounding away
+/// from zero. A non-positive `grid` leaves the value unchanged.
+pub fn snap_to_grid(value: f64, grid: f64) -> f64 {
+    if grid <= 0.0 {
+        return value;
+    }
+    (value / grid).round() * grid
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_perpendicular_offset_horizontal_edge() {
+        assert_eq!(
+            perpendicular_offset((0.0, 50.0), (100.0, 50.0), 20.0),
+            (0.0, -20.0)
+        );
+        assert_eq!(
+            perpendicular_offset((100.0, 50.0), (0.0, 50.0), 20.0),
+            (0.0, -20.0)
+        );
+    }
+
+    #[test]
+    fn test_perpendicular_offset_vertical_edge() {
+        assert_eq!(
+            perpendicular_offset((50.0, 0.0), (50.0, 100.0), 20.0),
+            (20.0, 0.0)
+        );
+        assert_eq!(
+            perpendicular_offset((50.0, 100.0), (50.0, 0.0), 20.0),
+            (20.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_perpendicular_offset_is_perpendicular() {
+        let (start, end) = ((10.0, 10.0), (70.0, 90.0));
+        let (ox, oy) = perpendicular_offset(start, end, 20.0);
+
+        let dot = ox * (end.0 - start.0) + oy * (end.1 - start.1);
+        assert!(dot.abs() < 1e-9);
+        assert!(((ox * ox + oy * oy).sqrt() - 20.0).abs() < 1e-9);
+        assert!(oy < 0.0);
+    }
+
+    #[test]
+    fn test_fan_offsets() {
+        assert_eq!(fan_offsets(1, 30.0), vec![0.0]);
+        assert_eq!(fan_offsets(2, 30.0), vec![-15.0, 15.0]);
+
+        let offsets = fan_offsets(3, 30.0);
+        assert_eq!(offsets, vec![-30.0, 0.0, 30.0]);
+        // Every edge gets its own curve
+        for (i, a) in offsets.iter().enumerate() {
+            for b in &offsets[i + 1..] {
+                assert!((a - b).abs() >= 30.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inset_area() {
+        // The default 550x600 canvas keeps new nodes 50px away from every edge
+        assert_eq!(
+            inset_area((0.0, 0.0, 550.0, 600.0), 50.0),
+            (50.0, 50.0, 500.0, 550.0)
+        );
+        // Panned viewports are inset in world coordinates
+        assert_eq!(
+            inset_area((-200.0, 100.0, 350.0, 700.0), 50.0),
+            (-150.0, 150.0, 300.0, 650.0)
+        );
+        // A viewport narrower than both margins collapses to its center
+        assert_eq!(
+            inset_area((0.0, 0.0, 60.0, 600.0), 50.0),
+            (30.0, 50.0, 30.0, 550.0)
+        );
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        assert_eq!(snap_to_grid(0.0, 25.0), 0.0);
+        assert_eq!(snap_to_grid(12.0, 25.0), 0.0);
+        assert_eq!(snap_to_grid(12.5, 25.0), 25.0);
+        assert_eq!(snap_to_grid(37.0, 25.0), 25.0);
+        assert_eq!(snap_to_grid(38.0, 25.0), 50.0);
+        assert_eq!(snap_to_grid(-13.0, 25.0), -25.0);
+        assert_eq!(snap_to_grid(-12.0, 25.0), 0.0);
+        assert_eq!(snap_to_grid(42.0, 0.0), 42.0);
+    }
+
+    #[test]
+    fn test_canvas_region_excludes_sidebar() {
+        assert_eq!(canvas_region(800, 600, 250), (250, 0, 550, 600));
+        assert_eq!(canvas_region(1024, 768, 0), (0, 0, 1024, 768));
+        assert_eq!(canvas_region(200, 600, 250), (200, 0, 0, 600));
+    }
+
+    #[test]
+    fn test_canvas_region_reflows_with_window_size() {
+        for (screen_width, screen_height) in [(800, 600), (1280, 720), (1920, 1080)] {
+            for sidebar_width in [180, 250, 500] {
+                let (x, y, width, height) =
+                    canvas_region(screen_width, screen_height, sidebar_width);
+                assert_eq!((x, y), (sidebar_width, 0));
+                assert_eq!(width, screen_width - sidebar_width);
+                assert_eq!(height, screen_height);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        let (start, end) = ((0.0, 0.0), (100.0, 0.0));
+        assert_eq!(distance_to_segment((50.0, 3.0), start, end), 3.0);
+        assert_eq!(distance_to_segment((50.0, -4.0), start, end), 4.0);
+        // Beyond an endpoint the distance is measured to that endpoint
+        assert_eq!(distance_to_segment((103.0, 4.0), start, end), 5.0);
+        assert_eq!(distance_to_segment((-3.0, 4.0), start, end), 5.0);
+        assert_eq!(distance_to_segment((3.0, 4.0), (0.0, 0.0), (0.0, 0.0)), 5.0);
+    }
+
+    #[test]
+    fn test_lerp_along_path() {
+        let points = [(0.0, 0.0), (100.0, 0.0), (100.0, 50.0)];
+        assert_eq!(lerp_along_path(&points, 0, 0.0), Some((0.0, 0.0)));
+        assert_eq!(lerp_along_path(&points, 0, 0.25), Some((25.0, 0.0)));
+        assert_eq!(lerp_along_path(&points, 1, 0.5), Some((100.0, 25.0)));
+        assert_eq!(lerp_along_path(&points, 1, 1.0), Some((100.0, 50.0)));
+        // Progress is clamped to the leg
+        assert_eq!(lerp_along_path(&points, 0, 1.5), Some((100.0, 0.0)));
+        assert_eq!(lerp_along_path(&points, 2, 0.5), None);
+        assert_eq!(lerp_along_path(&[], 0, 0.5), None);
+    }
+
+    #[test]
+    fn test_arrow_head() {
+        // Pointing right: the base is behind the tip, spread vertically
+        let [tip, left, right] = arrow_head((100.0, 50.0), (0.0, 50.0), 10.0, 8.0).unwrap();
+        assert_eq!(tip, (100.0, 50.0));
+        assert_eq!(left, (90.0, 46.0));
+        assert_eq!(right, (90.0, 54.0));
+
+        for approach in [(0.0, 0.0), (200.0, 10.0), (100.0, 300.0), (-40.0, 60.0)] {
+            let [a, b, c] = arrow_head((100.0, 50.0), approach, 10.0, 8.0).unwrap();
+            // Counter-clockwise on a y-down screen means a negative cross product
+            let cross = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+            assert!(cross < 0.0, "{:?}", approach);
+        }
+
+        assert_eq!(arrow_head((5.0, 5.0), (5.0, 5.0), 10.0, 8.0), None);
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        assert_eq!(
+            grid_lines(0.0, 100.0, 25.0),
+            vec![0.0, 25.0, 50.0, 75.0, 100.0]
+        );
+        assert_eq!(grid_lines(-30.0, 30.0, 25.0), vec![-25.0, 0.0, 25.0]);
+        assert_eq!(grid_lines(10.0, 20.0, 25.0), Vec::<f64>::new());
+        assert_eq!(grid_lines(0.0, 100.0, 0.0), Vec::<f64>::new());
+    }
+}
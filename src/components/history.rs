@@ -0,0 +1,145 @@
+use petgraph::graph::NodeIndex;
+
+use crate::components::network::Network;
+
+/// A node with the position it was moved from and the one it was moved to.
+type Move = (NodeIndex, (f64, f64), (f64, f64));
+
+/// One step that can be undone: nodes moved from one position to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMove {
+    pub moves: Vec<Move>,
+}
+
+/// Undo and redo stacks of node moves. A drag sends a stream of small updates, so the
+/// positions are only captured where it starts and ends and the whole drag becomes a
+/// single step.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<NodeMove>,
+    redo: Vec<NodeMove>,
+    /// Where the dragged nodes were when the drag in progress started.
+    drag_start: Option<Vec<(NodeIndex, (f64, f64))>>,
+}
+
+impl History {
+    /// Remembers where `nodes` are as a drag of them starts.
+    pub fn begin_drag(&mut self, network: &Network, nodes: impl IntoIterator<Item = NodeIndex>) {
+        self.drag_start = Some(
+            nodes
+                .into_iter()
+                .filter_map(|idx| network.graph.node_weight(idx).map(|node| (idx, node.point)))
+                .collect(),
+        );
+    }
+
+    /// Closes the drag in progress, recording one undo step for every node it moved.
+    /// Returns whether a step was recorded; a drag that ends where it started is not one.
+    pub fn end_drag(&mut self, network: &Network) -> bool {
+        let Some(start) = self.drag_start.take() else {
+            return false;
+        };
+        let moves: Vec<_> = start
+            .into_iter()
+            .filter_map(|(idx, from)| {
+                let to = network.graph.node_weight(idx)?.point;
+                (to != from).then_some((idx, from, to))
+            })
+            .collect();
+        if moves.is_empty() {
+            return false;
+        }
+        self.undo.push(NodeMove { moves });
+        self.redo.clear();
+        true
+    }
+
+    /// Puts the nodes of the last step back where they were. Returns whether there was a
+    /// step to undo.
+    pub fn undo(&mut self, network: &mut Network) -> bool {
+        let Some(step) = self.undo.pop() else {
+            return false;
+        };
+        for &(idx, from, _) in &step.moves {
+            if let Some(node) = network.graph.node_weight_mut(idx) {
+                node.point = from;
+            }
+        }
+        self.redo.push(step);
+        true
+    }
+
+    /// Replays the last undone step. Returns whether there was one.
+    pub fn redo(&mut self, network: &mut Network) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+        for &(idx, _, to) in &step.moves {
+            if let Some(node) = network.graph.node_weight_mut(idx) {
+                node.point = to;
+            }
+        }
+        self.undo.push(step);
+        true
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Forgets every step, for when node indices stop meaning what they did.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.drag_start = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::network::Node;
+
+    fn single_node() -> Network {
+        let mut network = Network::new();
+        network.add_node(Node {
+            id: "A".to_string(),
+            point: (10.0, 10.0),
+            ..Node::default()
+        });
+        network
+    }
+
+    #[test]
+    fn test_drag_is_one_undo_step() {
+        let mut network = single_node();
+        let a = network.node_indices["A"];
+        let mut history = History::default();
+
+        history.begin_drag(&network, [a]);
+        for step in 1..=50 {
+            network.graph[a].point = (10.0 + step as f64, 10.0 + 2.0 * step as f64);
+        }
+        assert!(history.end_drag(&network));
+        assert_eq!(history.undo_len(), 1);
+
+        assert!(history.undo(&mut network));
+        assert_eq!(network.graph[a].point, (10.0, 10.0));
+        assert!(!history.undo(&mut network));
+
+        assert!(history.redo(&mut network));
+        assert_eq!(network.graph[a].point, (60.0, 110.0));
+    }
+
+    #[test]
+    fn test_drag_without_movement_is_not_recorded() {
+        let network = single_node();
+        let a = network.node_indices["A"];
+        let mut history = History::default();
+
+        history.begin_drag(&network, [a]);
+        assert!(!history.end_drag(&network));
+        assert!(!history.end_drag(&network));
+        assert_eq!(history.undo_len(), 0);
+    }
+}
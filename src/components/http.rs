@@ -0,0 +1,154 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use crate::components::network::{Link, NetworkError, read_network_links};
+
+/// How long fetching a network over HTTP may take altogether, from resolving the host to
+/// reading the last byte of the body, before giving up.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fetches a links CSV from an `http://` or `https://` URL, in the same format as the local
+/// files. Blocks until the download finishes; see [`Download`] to run it in the background.
+pub fn load_network_from_url(url: &str) -> Result<Vec<Link>, NetworkError> {
+    let body = http_get(url, HTTP_TIMEOUT)?;
+    read_network_links(csv::Reader::from_reader(body.as_bytes()))
+}
+
+/// Body of a `GET` of `url`, following redirects. Any status other than 2xx, and any
+/// connection failure or timeout, is reported as `NetworkError::Http`.
+fn http_get(url: &str, timeout: Duration) -> Result<String, NetworkError> {
+    let http_error = |err: ureq::Error| NetworkError::Http(err.to_string());
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    agent
+        .get(url)
+        .call()
+        .map_err(http_error)?
+        .body_mut()
+        .read_to_string()
+        .map_err(http_error)
+}
+
+/// A [`load_network_from_url`] running on its own thread, so the window keeps drawing while
+/// the server answers.
+#[derive(Debug)]
+pub struct Download {
+    url: String,
+    result: Receiver<Result<Vec<Link>, NetworkError>>,
+}
+
+impl Download {
+    pub fn start(url: String) -> Self {
+        let (sender, result) = mpsc::channel();
+        let thread_url = url.clone();
+        thread::spawn(move || {
+            // Nobody is listening any more if the download was abandoned
+            let _ = sender.send(load_network_from_url(&thread_url));
+        });
+        Download { url, result }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The links once the download has finished, `None` while it is still running.
+    pub fn poll(&self) -> Option<Result<Vec<Link>, NetworkError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(NetworkError::Http(
+                "the download stopped unexpectedly".to_string(),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        time::Instant,
+    };
+
+    /// Serves `response` to the first connection on a local port and returns its URL.
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the whole request head, so the client never sees its write cut off
+            let mut request = Vec::new();
+            let mut chunk = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut chunk) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => request.extend_from_slice(&chunk[..read]),
+                }
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}/links.csv", address)
+    }
+
+    /// Waits for `download` to finish, failing the test if it takes too long.
+    fn finish(download: &Download) -> Result<Vec<Link>, NetworkError> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(result) = download.poll() {
+                return result;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "download of {} hung",
+                download.url()
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_load_network_from_url() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\nContent-Length: 79\r\n\
+             Connection: close\r\n\r\n\
+             link_id,source_node,destination_node,capacity,weight\n\
+             1,A,B,100,5\n2,B,C,1Gbps,3\n",
+        );
+        let links = finish(&Download::start(url)).unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].source_node, "A");
+        assert_eq!(links[1].capacity, 1000);
+    }
+
+    #[test]
+    fn test_load_network_from_url_errors() {
+        let url = serve_once(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 7\r\nConnection: close\r\n\r\nmissing",
+        );
+        match load_network_from_url(&url) {
+            Err(NetworkError::Http(msg)) => assert!(msg.contains("404"), "{}", msg),
+            other => panic!("expected an HTTP error, got {:?}", other),
+        }
+
+        assert!(matches!(
+            load_network_from_url("ftp://example.com/links.csv"),
+            Err(NetworkError::Http(_))
+        ));
+
+        // A server that accepts but never answers times out
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        assert!(matches!(
+            http_get(&url, Duration::from_millis(100)),
+            Err(NetworkError::Http(_))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+use raylib::prelude::*;
+
+use crate::components::network::Network;
+
+const MINIMAP_WIDTH: f64 = 160.0;
+const MINIMAP_HEIGHT: f64 = 120.0;
+const MINIMAP_MARGIN: f64 = 10.0;
+const MINIMAP_PADDING: f64 = 8.0;
+
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    x: f64,
+    y: f64,
+    world: (f64, f64, f64, f64),
+    scale: f64,
+    inset: (f64, f64),
+}
+
+impl Minimap {
+    /// Places the minimap in the bottom-right corner of the canvas, scaled so that both the
+    /// graph bounds and the current viewport (all in world coordinates) fit inside it.
+    pub fn new(
+        canvas_right: f64,
+        canvas_bottom: f64,
+        graph_bounds: (f64, f64, f64, f64),
+        viewport: (f64, f64, f64, f64),
+    ) -> Self {
+        let world = (
+            graph_bounds.0.min(viewport.0),
+            graph_bounds.1.min(viewport.1),
+            graph_bounds.2.max(viewport.2),
+            graph_bounds.3.max(viewport.3),
+        );
+        let world_width = (world.2 - world.0).max(1.0);
+        let world_height = (world.3 - world.1).max(1.0);
+
+        let scale = ((MINIMAP_WIDTH - 2.0 * MINIMAP_PADDING) / world_width)
+            .min((MINIMAP_HEIGHT - 2.0 * MINIMAP_PADDING) / world_height);
+
+        // Center the scaled content inside the minimap frame
+        let inset = (
+            (MINIMAP_WIDTH - world_width * scale) / 2.0,
+            (MINIMAP_HEIGHT - world_height * scale) / 2.0,
+        );
+
+        Minimap {
+            x: canvas_right - MINIMAP_WIDTH - MINIMAP_MARGIN,
+            y: canvas_bottom - MINIMAP_HEIGHT - MINIMAP_MARGIN,
+            world,
+            scale,
+            inset,
+        }
+    }
+
+    pub fn contains(&self, screen_x: f64, screen_y: f64) -> bool {
+        (self.x..=self.x + MINIMAP_WIDTH).contains(&screen_x)
+            && (self.y..=self.y + MINIMAP_HEIGHT).contains(&screen_y)
+    }
+
+    pub fn to_screen(&self, world_x: f64, world_y: f64) -> (f64, f64) {
+        (
+            self.x + self.inset.0 + (world_x - self.world.0) * self.scale,
+            self.y + self.inset.1 + (world_y - self.world.1) * self.scale,
+        )
+    }
+
+    pub fn to_world(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
+        (
+            (screen_x - self.x - self.inset.0) / self.scale + self.world.0,
+            (screen_y - self.y - self.inset.1) / self.scale + self.world.1,
+        )
+    }
+
+    pub fn draw(
+        &self,
+        handle: &mut impl RaylibDraw,
+        network: &Network,
+        viewport: (f64, f64, f64, f64),
+    ) {
+        handle.draw_rectangle(
+            self.x as i32,
+            self.y as i32,
+            MINIMAP_WIDTH as i32,
+            MINIMAP_HEIGHT as i32,
+            Color::new(30, 30, 30, 220),
+        );
+        handle.draw_rectangle_lines(
+            self.x as i32,
+            self.y as i32,
+            MINIMAP_WIDTH as i32,
+            MINIMAP_HEIGHT as i32,
+            Color::GRAY,
+        );
+
+        for node in network.nodes() {
+            let (x, y) = self.to_screen(node.point.0, node.point.1);
+            handle.draw_circle(x as i32, y as i32, 2.0, Color::WHITE);
+        }
+
+        let (left, top) = self.to_screen(viewport.0, viewport.1);
+        let (right, bottom) = self.to_screen(viewport.2, viewport.3);
+        handle.draw_rectangle_lines(
+            left as i32,
+            top as i32,
+            (right - left) as i32,
+            (bottom - top) as i32,
+            Color::YELLOW,
+        );
+    }
+}
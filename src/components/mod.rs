@@ -1,2 +1,10 @@
+pub mod bundling;
+pub mod commands;
+pub mod generators;
+pub mod geometry;
+pub mod history;
+pub mod http;
+pub mod minimap;
 pub mod network;
+pub mod theme;
 pub mod ui;
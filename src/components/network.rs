@@ -1,21 +1,35 @@
 use petgraph::{
-    graph::{NodeIndex, UnGraph},
+    algo::connected_components,
+    graph::{DiGraph, EdgeIndex, EdgeReference, NodeIndex, UnGraph},
     visit::EdgeRef,
 };
 
-use serde::Deserialize;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
+    cell::OnceCell,
     cmp::Reverse,
-    collections::{BinaryHeap, HashMap},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque, hash_map::Entry},
     error::Error,
     fmt::Display,
+    io::Read,
 };
 
+use crate::components::geometry::{distance_to_segment, inset_area};
+
 #[derive(Debug)]
 pub enum NetworkError {
     NodeNotFound(String),
     Io(std::io::Error),
     Csv(csv::Error),
+    Json(serde_json::Error),
+    Parse(String),
+    UnresolvedReferences(Vec<String>),
+    NegativeCycle,
+    Http(String),
+    Xml(String),
+    /// A network JSON `version` this build does not know how to read.
+    UnsupportedVersion(u32),
 }
 
 impl Display for NetworkError {
@@ -24,6 +38,19 @@ impl Display for NetworkError {
             NetworkError::NodeNotFound(id) => write!(f, "Node not found: {}", id),
             NetworkError::Io(err) => write!(f, "IO error: {}", err),
             NetworkError::Csv(err) => write!(f, "CSV error: {}", err),
+            NetworkError::Json(err) => write!(f, "JSON error: {}", err),
+            NetworkError::Parse(msg) => write!(f, "Parse error: {}", msg),
+            NetworkError::UnresolvedReferences(ids) => {
+                write!(f, "Unresolved node references: {}", ids.join(", "))
+            }
+            NetworkError::NegativeCycle => write!(f, "Negative cost cycle"),
+            NetworkError::Http(msg) => write!(f, "HTTP error: {}", msg),
+            NetworkError::Xml(msg) => write!(f, "XML error: {}", msg),
+            NetworkError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported network file version {}: expected 1 to {}",
+                version, NETWORK_JSON_VERSION
+            ),
         }
     }
 }
@@ -33,6 +60,7 @@ impl Error for NetworkError {
         match self {
             NetworkError::Io(err) => Some(err),
             NetworkError::Csv(err) => Some(err),
+            NetworkError::Json(err) => Some(err),
             _ => None,
         }
     }
@@ -50,25 +78,460 @@ impl From<csv::Error> for NetworkError {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+impl From<serde_json::Error> for NetworkError {
+    fn from(value: serde_json::Error) -> Self {
+        NetworkError::Json(value)
+    }
+}
+
+/// How quickly the force layout's temperature, the furthest a node may move in one
+/// iteration, falls from its initial value towards zero over the iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cooling {
+    #[default]
+    Linear,
+    /// Falls by the same factor every iteration, to 1% of the start by the end.
+    Exponential,
+    /// Falls fast early on and levels out towards the end.
+    Quadratic,
+}
+
+impl Cooling {
+    /// Temperature for `iteration` of `iterations`, starting from `initial`.
+    fn temperature(self, initial: f64, iteration: usize, iterations: usize) -> f64 {
+        let progress = iteration as f64 / iterations as f64;
+        match self {
+            Cooling::Linear => initial * (1.0 - progress),
+            Cooling::Exponential => initial * 0.01_f64.powf(progress),
+            Cooling::Quadratic => initial * (1.0 - progress).powi(2),
+        }
+    }
+}
+
+/// How [`Network::simplify`] combines the weights of parallel links. Capacities are
+/// always summed, since the links carry traffic side by side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombinePolicy {
+    /// Keep the cheapest weight, as a router would prefer that link.
+    #[default]
+    Min,
+    /// Mean of the weights.
+    Average,
+}
+
+/// Settings for [`Network::apply_force_directed_layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutConfig {
+    /// Most iterations to run.
+    pub iterations: usize,
+    pub cooling: Cooling,
+    /// Stop as soon as no node moved further than this in an iteration.
+    pub epsilon: f64,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            iterations: 100,
+            cooling: Cooling::default(),
+            epsilon: 0.01,
+        }
+    }
+}
+
+/// How a force layout run went.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LayoutRun {
+    /// Iterations actually run, fewer than configured when the layout settled early.
+    pub iterations: usize,
+    /// Largest distance any node moved in the final iteration.
+    pub max_displacement: f64,
+}
+
+/// A force layout run one iteration at a time with [`Network::layout_step`], so a long
+/// run can be spread over several frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutProgress {
+    width: f64,
+    height: f64,
+    config: LayoutConfig,
+    pinned_node: Option<NodeIndex>,
+    /// How the iterations so far went.
+    pub run: LayoutRun,
+    done: bool,
+}
+
+impl LayoutProgress {
+    /// A run of the layout over a `width` x `height` area that has not started yet; the
+    /// arguments are those of [`Network::apply_force_directed_layout`].
+    pub fn new(
+        width: f64,
+        height: f64,
+        config: LayoutConfig,
+        pinned_node: Option<NodeIndex>,
+    ) -> Self {
+        LayoutProgress {
+            width,
+            height,
+            config,
+            pinned_node,
+            run: LayoutRun::default(),
+            done: false,
+        }
+    }
+
+    /// Share of the configured iterations run so far, 1.0 once the layout has settled.
+    pub fn fraction(&self) -> f32 {
+        if self.done || self.config.iterations == 0 {
+            1.0
+        } else {
+            self.run.iterations as f32 / self.config.iterations as f32
+        }
+    }
+}
+
+/// Default radius of a rendered node, shared by drawing and hit-testing.
+pub const NODE_RADIUS: f64 = 18.0;
+
+/// Capacity given to links from formats that only describe weights.
+pub const DEFAULT_LINK_CAPACITY: u64 = 100;
+
+/// Attribute names mapped to their values, e.g. `vendor` to `Acme`.
+pub type Metadata = HashMap<String, String>;
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Node {
     pub id: String,
-    pub point: (i32, i32),
+    pub point: (f64, f64),
+    /// Geographic `(latitude, longitude)` in degrees, when known.
+    #[serde(default)]
+    pub lat_lon: Option<(f64, f64)>,
+    /// Free-form attributes such as vendor, model or site.
+    #[serde(default)]
+    pub metadata: Metadata,
+    /// Held in place by the force layout, like a site whose location is known.
+    #[serde(default)]
+    pub anchored: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct Link {
     pub link_id: String,
     pub source_node: String,
     pub destination_node: String,
-    pub capacity: u8,
-    pub weight: u8,
+    /// Capacity in Mbps. Files may also write it with a unit, like `1Gbps`.
+    #[serde(deserialize_with = "deserialize_capacity")]
+    pub capacity: u64,
+    /// Routing cost, such as a latency or a price. Fractions are allowed, negatives and NaN
+    /// are rejected when reading.
+    #[serde(deserialize_with = "deserialize_weight")]
+    pub weight: f64,
+    #[serde(default)]
+    pub latency_ms: f64,
+    /// Signed cost for algorithms that allow negative costs; the weight when absent.
+    #[serde(default)]
+    pub cost: Option<i32>,
+    /// RGB the link is drawn in instead of the theme's, written `#RRGGBB` in files.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_color",
+        serialize_with = "serialize_color"
+    )]
+    pub color: Option<(u8, u8, u8)>,
+    /// Attributes for the source and destination node, read from `source_<key>` and
+    /// `destination_<key>` columns. [`Network::add_link`] moves them onto the nodes, so
+    /// links in a network carry none.
+    #[serde(skip)]
+    pub endpoint_metadata: (Metadata, Metadata),
+}
+
+/// Parses a capacity into Mbps. A bare number is already in Mbps; `Mbps`, `Gbps` and `Tbps`
+/// suffixes are accepted in any case, with fractional amounts rounded to the nearest Mbps.
+pub fn parse_capacity(value: &str) -> Result<u64, String> {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let (amount, scale) = [("tbps", 1_000_000.0), ("gbps", 1_000.0), ("mbps", 1.0)]
+        .into_iter()
+        .find_map(|(unit, scale)| lower.strip_suffix(unit).map(|amount| (amount, scale)))
+        .unwrap_or((lower.as_str(), 1.0));
+    match amount.trim().parse::<f64>() {
+        Ok(amount) if amount.is_finite() && amount >= 0.0 => Ok((amount * scale).round() as u64),
+        _ => Err(format!(
+            "Invalid capacity '{}': expected a number with an optional Mbps, Gbps or Tbps suffix",
+            trimmed
+        )),
+    }
+}
+
+fn deserialize_capacity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawCapacity {
+        Number(u64),
+        Text(String),
+    }
+
+    match RawCapacity::deserialize(deserializer)? {
+        RawCapacity::Number(capacity) => Ok(capacity),
+        RawCapacity::Text(text) => parse_capacity(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Whether `weight` can be a link weight: finite and not negative.
+fn is_valid_weight(weight: f64) -> bool {
+    weight.is_finite() && weight >= 0.0
+}
+
+/// Parses a link weight: any finite, non-negative number, like `3` or `2.5`.
+pub fn parse_weight(value: &str) -> Result<f64, String> {
+    let trimmed = value.trim();
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|&weight| is_valid_weight(weight))
+        .ok_or_else(|| {
+            format!(
+                "Invalid weight '{}': expected a non-negative number",
+                trimmed
+            )
+        })
+}
+
+/// A weight as shown on screen: at most two decimals, without trailing zeros, like `3`,
+/// `2.5` or `0.33`.
+pub fn format_weight(weight: f64) -> String {
+    let text = format!("{:.2}", weight);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn deserialize_weight<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let weight = f64::deserialize(deserializer)?;
+    if !is_valid_weight(weight) {
+        return Err(serde::de::Error::custom(format!(
+            "Invalid weight '{}': expected a non-negative number",
+            weight
+        )));
+    }
+    Ok(weight)
+}
+
+/// Parses a hex color like `#FF8800` into its RGB components. The `#` is optional and the
+/// digits may be in either case.
+pub fn parse_hex_color(value: &str) -> Result<(u8, u8, u8), NetworkError> {
+    let trimmed = value.trim();
+    let digits = trimmed.strip_prefix('#').unwrap_or(trimmed);
+    let invalid = || NetworkError::Parse(format!("Invalid color '{}': expected #RRGGBB", trimmed));
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let component = |at: usize| u8::from_str_radix(&digits[at..at + 2], 16).map_err(|_| invalid());
+    Ok((component(0)?, component(2)?, component(4)?))
+}
+
+/// An empty cell, like a missing column, leaves the link in the theme's color.
+fn deserialize_color<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<(u8, u8, u8)>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(text) if !text.trim().is_empty() => parse_hex_color(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+fn serialize_color<S: Serializer>(
+    color: &Option<(u8, u8, u8)>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match color {
+        Some((r, g, b)) => serializer.serialize_str(&format!("#{:02X}{:02X}{:02X}", r, g, b)),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl Link {
+    /// The signed cost, falling back to the weight rounded to a whole number.
+    pub fn signed_cost(&self) -> i64 {
+        self.cost.map_or(self.weight.round() as i64, i64::from)
+    }
+
+    /// The weight as a Dijkstra cost. `None` for a negative or NaN weight, which would break
+    /// the search's ordering, so such a link is treated as impassable.
+    fn path_cost(&self) -> Option<f64> {
+        (self.weight >= 0.0).then_some(self.weight)
+    }
+}
+
+/// A path cost ordered by [`f64::total_cmp`], so it can key a `BinaryHeap`. Negative and NaN
+/// costs are filtered out before any are compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cost(f64);
+
+impl Eq for Cost {}
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// What loading a batch of links into a network would change, as computed by
+/// [`Network::dry_run_load`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoadReport {
+    /// Distinct node ids the batch references that the network does not have yet.
+    pub new_nodes: usize,
+    pub new_edges: usize,
+    /// Links whose id is already in the network or earlier in the batch; they are skipped.
+    pub duplicate_edges: usize,
+    /// New links with at least one endpoint the network does not have yet.
+    pub dangling_references: usize,
+}
+
+/// Aggregate capacity and weight figures over all links.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityStats {
+    pub total_capacity: u64,
+    pub total_weight: f64,
+    /// Mean link capacity, 0.0 for a network without links.
+    pub average_capacity: f64,
+    /// Id of the link with the highest capacity; the first one wins ties.
+    pub max_capacity_link: Option<String>,
+}
+
+/// How many of the highest scoring nodes an [`AnalysisReport`] lists per centrality.
+const REPORT_TOP_NODES: usize = 5;
+
+/// A node and its score in one of the centrality rankings of an [`AnalysisReport`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankedNode {
+    pub id: String,
+    pub score: f64,
+}
+
+/// One-shot summary of the network's structure, serialized to JSON for scripts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalysisReport {
+    pub node_count: usize,
+    pub link_count: usize,
+    pub density: f64,
+    /// `None` when no two nodes are connected.
+    pub diameter: Option<u32>,
+    pub components: usize,
+    /// Most central nodes first, ties broken by id.
+    pub top_closeness: Vec<RankedNode>,
+    pub top_betweenness: Vec<RankedNode>,
+    pub top_pagerank: Vec<RankedNode>,
+}
+
+/// Node-link JSON as consumed by D3 force layouts:
+/// `{"nodes": [{"id": ..}], "links": [{"source": .., "target": .., "value": ..}]}`.
+#[derive(Debug, Deserialize)]
+struct D3Graph {
+    nodes: Vec<D3Node>,
+    links: Vec<D3Link>,
+}
+
+#[derive(Debug, Deserialize)]
+struct D3Node {
+    id: D3Id,
+    #[serde(default)]
+    x: f64,
+    #[serde(default)]
+    y: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct D3Link {
+    source: D3Id,
+    target: D3Id,
+    #[serde(default = "default_d3_value")]
+    value: f64,
+}
+
+/// Version of the JSON written by [`Network::to_json`]. No release ever wrote version 1:
+/// it is a synthetic legacy shape, the document without a `version` tag and with
+/// whole-number weights, kept so the migration path is exercised before a real format
+/// change needs it. Version 2 is the first format actually written.
+pub const NETWORK_JSON_VERSION: u32 = 2;
+
+/// Root of the JSON written by [`Network::to_json`].
+#[derive(Debug, Deserialize, Serialize)]
+struct NetworkDocument {
+    version: u32,
+    nodes: Vec<Node>,
+    links: Vec<Link>,
+}
+
+/// Rewrites a version 1 network JSON as version 2: its whole-number weights become floats.
+/// serde would read those as floats anyway, so this only tags the document; a real format
+/// change adds its own `migrate_vN_to_vM` step after this one.
+fn migrate_v1_to_v2(document: &mut serde_json::Map<String, serde_json::Value>) {
+    let links = document
+        .get_mut("links")
+        .and_then(serde_json::Value::as_array_mut);
+    for link in links.into_iter().flatten() {
+        if let Some(link) = link.as_object_mut()
+            && let Some(weight) = link.get("weight").and_then(serde_json::Value::as_f64)
+        {
+            link.insert("weight".to_string(), weight.into());
+        }
+    }
+    document.insert("version".to_string(), 2.into());
+}
+
+/// One entry of the positions JSON written by [`Network::export_layout_json`].
+#[derive(Debug, Deserialize, Serialize)]
+struct NodePosition {
+    id: String,
+    x: f64,
+    y: f64,
+}
+
+/// D3 accepts both string and numeric node ids.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum D3Id {
+    Text(String),
+    Number(u64),
+}
+
+impl From<D3Id> for String {
+    fn from(value: D3Id) -> Self {
+        match value {
+            D3Id::Text(id) => id,
+            D3Id::Number(id) => id.to_string(),
+        }
+    }
+}
+
+fn default_d3_value() -> f64 {
+    1.0
+}
+
+/// Shortest-path results shared by the analysis metrics. Computed on first use and
+/// dropped whenever a node or link is added or removed.
+#[derive(Debug, Clone, Default)]
+struct AnalysisCache {
+    /// Hop distances from every node to each node it can reach.
+    hops: OnceCell<HashMap<NodeIndex, HashMap<NodeIndex, u32>>>,
+    /// Number of distinct shortest paths from every node to each node it can reach.
+    path_counts: OnceCell<HashMap<NodeIndex, HashMap<NodeIndex, f64>>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Network {
     pub graph: UnGraph<Node, Link>,
     pub node_indices: HashMap<String, NodeIndex>,
+    pub link_indices: HashMap<String, EdgeIndex>,
+    analysis: AnalysisCache,
 }
 
 impl Network {
@@ -76,13 +539,89 @@ impl Network {
         Network {
             graph: UnGraph::default(),
             node_indices: HashMap::new(),
+            link_indices: HashMap::new(),
+            analysis: AnalysisCache::default(),
+        }
+    }
+
+    /// Builds a network from `links`, placing every endpoint at a random point inside
+    /// `area` (`(min_x, min_y, max_x, max_y)`) drawn from `rng`. An axis with no room, e.g.
+    /// from a canvas too small for its margins, puts every node on its midpoint.
+    pub fn with_random_placement(
+        links: Vec<Link>,
+        area: (f64, f64, f64, f64),
+        rng: &mut impl Rng,
+    ) -> Result<Self, NetworkError> {
+        let mut network = Network::new();
+        let mut coordinate = |min: f64, max: f64| {
+            if min < max {
+                rng.random_range(min..max)
+            } else {
+                (min + max) / 2.0
+            }
+        };
+
+        for link in &links {
+            for id in [&link.source_node, &link.destination_node] {
+                if !network.node_indices.contains_key(id) {
+                    network.add_node(Node {
+                        id: id.clone(),
+                        point: (coordinate(area.0, area.2), coordinate(area.1, area.3)),
+                        ..Node::default()
+                    });
+                }
+            }
+        }
+
+        for link in links {
+            network.add_link(link)?;
+        }
+
+        Ok(network)
+    }
+
+    /// Parses a D3-style node-link JSON document. `target` becomes the destination node,
+    /// `value` the weight (1 when absent), and every link gets `DEFAULT_LINK_CAPACITY`.
+    /// Node positions are taken from `x`/`y` when present and default to the origin.
+    pub fn from_d3_json(s: &str) -> Result<Network, NetworkError> {
+        let d3: D3Graph = serde_json::from_str(s)?;
+        let mut network = Network::new();
+
+        for node in d3.nodes {
+            network.add_node(Node {
+                id: node.id.into(),
+                point: (node.x, node.y),
+                ..Node::default()
+            });
+        }
+
+        for (i, link) in d3.links.into_iter().enumerate() {
+            network.add_link(Link {
+                link_id: (i + 1).to_string(),
+                source_node: link.source.into(),
+                destination_node: link.target.into(),
+                capacity: DEFAULT_LINK_CAPACITY,
+                weight: link.value,
+                ..Link::default()
+            })?;
         }
+
+        Ok(network)
+    }
+
+    /// Reads a Gephi GEXF file, covering the static-graph subset: each `node`'s `id` and
+    /// `viz:position`, and each `edge`'s `id`, `source`, `target` and `weight`. Gephi's y
+    /// axis points up, so y is flipped to keep the drawing the same way round. Weights are 1
+    /// when absent and must not be negative, and every link gets `DEFAULT_LINK_CAPACITY`;
+    /// edges without an id are numbered in file order.
+    pub fn from_gexf(path: &str) -> Result<Network, NetworkError> {
+        parse_gexf(&std::fs::read_to_string(path)?)
     }
 
     pub fn find_node_at_point(&self, x: f64, y: f64, radius: f64) -> Option<NodeIndex> {
         for (i, node) in self.nodes().enumerate() {
-            let dx = x - node.point.0 as f64;
-            let dy = y - node.point.1 as f64;
+            let dx = x - node.point.0;
+            let dy = y - node.point.1;
             if (dx * dx + dy * dy).sqrt() <= radius {
                 return Some(NodeIndex::new(i));
             }
@@ -90,15 +629,119 @@ impl Network {
         None
     }
 
+    /// The link closest to `(x, y)` among those within `tolerance` of where they are drawn:
+    /// the polyline through their points in `curves` for bundled links, otherwise the
+    /// straight line between their endpoints. Self-loops are not hit-tested and parallel
+    /// links share the same straight line, so the first of them wins.
+    pub fn find_edge_at_point(
+        &self,
+        x: f64,
+        y: f64,
+        tolerance: f64,
+        curves: &HashMap<EdgeIndex, Vec<(f64, f64)>>,
+    ) -> Option<EdgeIndex> {
+        self.graph
+            .edge_references()
+            .filter(|edge| edge.source() != edge.target())
+            .map(|edge| {
+                let distance = match curves.get(&edge.id()) {
+                    Some(curve) => curve
+                        .windows(2)
+                        .map(|pair| distance_to_segment((x, y), pair[0], pair[1]))
+                        .fold(f64::INFINITY, f64::min),
+                    None => {
+                        let start = self.graph[edge.source()].point;
+                        let end = self.graph[edge.target()].point;
+                        distance_to_segment((x, y), start, end)
+                    }
+                };
+                (edge.id(), distance)
+            })
+            .filter(|&(_, distance)| distance <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(edge, _)| edge)
+    }
+
+    /// Returns every node whose point lies inside the rectangle spanned by the two corners,
+    /// in whichever order the corners are given.
+    pub fn nodes_in_rect(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> HashSet<NodeIndex> {
+        let (min_x, max_x) = (x0.min(x1), x0.max(x1));
+        let (min_y, max_y) = (y0.min(y1), y0.max(y1));
+
+        self.graph
+            .node_indices()
+            .filter(|&idx| {
+                let (x, y) = self.graph[idx].point;
+                (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+            })
+            .collect()
+    }
+
+    /// Returns each node adjacent to `idx` together with the link connecting them.
+    pub fn neighbors(&self, idx: NodeIndex) -> Vec<(NodeIndex, Link)> {
+        self.graph
+            .edges(idx)
+            .map(|edge| {
+                let neighbor = if edge.source() == idx {
+                    edge.target()
+                } else {
+                    edge.source()
+                };
+                (neighbor, edge.weight().clone())
+            })
+            .collect()
+    }
+
     pub fn add_node(&mut self, node: Node) -> NodeIndex {
         if let Some(&index) = self.node_indices.get(&node.id) {
             index
         } else {
             let index = self.graph.add_node(node.clone());
             self.node_indices.insert(node.id, index);
+            self.invalidate_analysis();
             index
         }
     }
+
+    /// Adds a copy of node `idx` next to it, linked to the same neighbors with the same link
+    /// attributes. The copy is named `<id>_copy`, or `<id>_copy2`, `<id>_copy3`, ... when that
+    /// id is taken, and its links get fresh ids. A link from the node to itself becomes a link
+    /// from the copy to itself.
+    pub fn duplicate_node(&mut self, idx: NodeIndex) -> NodeIndex {
+        let original = self.graph[idx].clone();
+        let id = (1..)
+            .map(|n| match n {
+                1 => format!("{}_copy", original.id),
+                n => format!("{}_copy{}", original.id, n),
+            })
+            .find(|id| !self.node_indices.contains_key(id))
+            .unwrap();
+        let (x, y) = original.point;
+        let copy = self.add_node(Node {
+            id: id.clone(),
+            point: (x + NODE_RADIUS, y + NODE_RADIUS),
+            lat_lon: original.lat_lon,
+            metadata: original.metadata.clone(),
+            ..Node::default()
+        });
+
+        let mut edges: Vec<EdgeIndex> = self.graph.edges(idx).map(|edge| edge.id()).collect();
+        edges.sort();
+        edges.dedup();
+        for edge in edges {
+            let mut link = self.graph[edge].clone();
+            link.link_id = self.next_link_id();
+            for endpoint in [&mut link.source_node, &mut link.destination_node] {
+                if *endpoint == original.id {
+                    *endpoint = id.clone();
+                }
+            }
+            // Both endpoints exist, so adding cannot fail
+            let _ = self.add_link(link);
+        }
+        copy
+    }
+
     pub fn find_shortest_path(
         &self,
         start_node_id: &str,
@@ -113,263 +756,5369 @@ impl Network {
             .get(end_node_id)
             .ok_or_else(|| NetworkError::NodeNotFound(end_node_id.to_string()))?;
 
-        // Initialize data structures
-        let mut distances: HashMap<NodeIndex, u32> = HashMap::new();
-        let mut predecessors: HashMap<NodeIndex, Option<NodeIndex>> = HashMap::new();
-        let mut heap = BinaryHeap::new();
+        let (_, predecessors) = self.dijkstra(*start_idx, Some(*end_idx), |edge| {
+            Some(edge.weight().weight)
+        });
 
-        // Initialize distances and predecessors
-        for node in self.graph.node_indices() {
-            distances.insert(node, u32::MAX);
-            predecessors.insert(node, None);
+        // Reconstruct the path
+        let mut path_edges = Vec::new();
+        let mut current_idx = *end_idx;
+        while current_idx != *start_idx {
+            let &prev_idx = predecessors
+                .get(&current_idx)
+                .ok_or_else(|| NetworkError::NodeNotFound("No path exists".to_string()))?;
+            path_edges.push((prev_idx, current_idx));
+            current_idx = prev_idx;
         }
-        distances.insert(*start_idx, 0);
-        heap.push(Reverse((0, *start_idx)));
+        path_edges.reverse(); // Get start-to-end order
+        Ok(path_edges)
+    }
+
+    /// Dijkstra from `source`, where `cost` gives the cost of crossing a link from the node
+    /// being expanded, or `None` to skip it. Negative and NaN costs would break the search's
+    /// ordering, so such links are skipped as well. Stops once `target` is settled, if given.
+    /// Returns the distance to every node reached, and the node before each one other than
+    /// `source` on a shortest path there.
+    fn dijkstra(
+        &self,
+        source: NodeIndex,
+        target: Option<NodeIndex>,
+        cost: impl Fn(EdgeReference<'_, Link>) -> Option<f64>,
+    ) -> (HashMap<NodeIndex, f64>, HashMap<NodeIndex, NodeIndex>) {
+        let mut distances: HashMap<NodeIndex, f64> = HashMap::from([(source, 0.0)]);
+        let mut predecessors: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut heap = BinaryHeap::from([Reverse((Cost(0.0), source))]);
 
-        // Dijkstra's algorithm
-        while let Some(Reverse((dist, current))) = heap.pop() {
+        while let Some(Reverse((Cost(dist), current))) = heap.pop() {
+            if Some(current) == target {
+                break;
+            }
             if dist > distances[&current] {
                 continue; // Skip if we've found a better path
             }
-
             for edge in self.graph.edges(current) {
                 let next = edge.target();
-                let weight = edge.weight().weight as u32;
-                let new_dist = dist.saturating_add(weight);
-
-                if new_dist < distances[&next] {
+                let Some(weight) = cost(edge).filter(|&weight| weight >= 0.0) else {
+                    continue;
+                };
+                let new_dist = dist + weight;
+                if distances.get(&next).is_none_or(|&known| new_dist < known) {
                     distances.insert(next, new_dist);
-                    predecessors.insert(next, Some(current));
-                    heap.push(Reverse((new_dist, next)));
+                    predecessors.insert(next, current);
+                    heap.push(Reverse((Cost(new_dist), next)));
                 }
             }
         }
+        (distances, predecessors)
+    }
 
-        // Reconstruct the path
-        let mut path_edges = Vec::new();
-        let mut current_idx = *end_idx;
-        while let Some(prev_idx) = predecessors.get(&current_idx).copied().flatten() {
-            if current_idx == *start_idx {
+    /// Path from `start` to `end` whose narrowest link is as wide as possible, with that
+    /// bottleneck capacity: the best throughput route. Found with Dijkstra, keeping the
+    /// widest bottleneck per node instead of the shortest distance. Returns `None` when
+    /// `end` cannot be reached or is `start` itself.
+    pub fn widest_path(&self, start: NodeIndex, end: NodeIndex) -> Option<(Vec<NodeIndex>, u64)> {
+        if start == end {
+            return None;
+        }
+        let mut widths: HashMap<NodeIndex, u64> = HashMap::from([(start, u64::MAX)]);
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut heap = BinaryHeap::from([(u64::MAX, start)]);
+
+        while let Some((width, node)) = heap.pop() {
+            if node == end {
                 break;
             }
-            path_edges.push((prev_idx, current_idx));
-            current_idx = prev_idx;
+            if width < widths[&node] {
+                continue;
+            }
+            for (edge, next) in self.incident_edges(node) {
+                let candidate = width.min(self.graph[edge].capacity);
+                if candidate > widths.get(&next).copied().unwrap_or(0) {
+                    widths.insert(next, candidate);
+                    previous.insert(next, node);
+                    heap.push((candidate, next));
+                }
+            }
         }
-        if current_idx != *start_idx {
-            return Err(NetworkError::NodeNotFound("No path exists".to_string()));
+
+        let &width = widths.get(&end)?;
+        let mut path = vec![end];
+        let mut node = end;
+        while node != start {
+            node = previous[&node];
+            path.push(node);
         }
-        path_edges.reverse(); // Get start-to-end order
-        Ok(path_edges)
+        path.reverse();
+        Some((path, width))
     }
 
-    pub fn add_link(&mut self, link: Link) -> Result<(), NetworkError> {
-        let source_index = *self
+    /// Cheapest path from `start_node_id` to `end_node_id` by [`Link::signed_cost`], found
+    /// with Bellman–Ford so costs may be negative. Links are followed from `source_node` to
+    /// `destination_node` only, since an undirected negative link would be a negative cycle
+    /// by itself. Returns `Ok(None)` when `end_node_id` cannot be reached and
+    /// `NetworkError::NegativeCycle` when a negative cycle is reachable from the start.
+    pub fn shortest_path_bellman_ford(
+        &self,
+        start_node_id: &str,
+        end_node_id: &str,
+    ) -> Result<Option<(Vec<NodeIndex>, i64)>, NetworkError> {
+        let &start = self
             .node_indices
-            .get(&link.source_node)
-            .ok_or_else(|| NetworkError::NodeNotFound(link.source_node.clone()))?;
-
-        let destination_source = *self
+            .get(start_node_id)
+            .ok_or_else(|| NetworkError::NodeNotFound(start_node_id.to_string()))?;
+        let &end = self
             .node_indices
-            .get(&link.destination_node)
-            .ok_or_else(|| NetworkError::NodeNotFound(link.destination_node.clone()))?;
+            .get(end_node_id)
+            .ok_or_else(|| NetworkError::NodeNotFound(end_node_id.to_string()))?;
 
-        self.graph.add_edge(source_index, destination_source, link);
-        Ok(())
-    }
+        let arcs: Vec<(NodeIndex, NodeIndex, i64)> = self
+            .to_directed()
+            .edge_references()
+            .map(|edge| (edge.source(), edge.target(), edge.weight().signed_cost()))
+            .collect();
+        let mut distances: HashMap<NodeIndex, i64> = HashMap::from([(start, 0)]);
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
 
-    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
-        self.graph.node_weights()
-    }
+        // One extra round: anything still improving then lies on or behind a negative cycle
+        for round in 0..self.graph.node_count() {
+            let mut relaxed = false;
+            for &(from, to, cost) in &arcs {
+                let Some(&from_distance) = distances.get(&from) else {
+                    continue;
+                };
+                let candidate = from_distance + cost;
+                if distances
+                    .get(&to)
+                    .is_none_or(|&current| candidate < current)
+                {
+                    distances.insert(to, candidate);
+                    previous.insert(to, from);
+                    relaxed = true;
+                }
+            }
+            if !relaxed {
+                break;
+            }
+            if round + 1 == self.graph.node_count() {
+                return Err(NetworkError::NegativeCycle);
+            }
+        }
 
-    pub fn links(&self) -> impl Iterator<Item = (Link, &Node, &Node)> {
-        self.graph.edge_references().map(|edge_ref| {
-            let (source_idx, dest_idx) = self.graph.edge_endpoints(edge_ref.id()).unwrap();
-            let source_node = self.graph.node_weight(source_idx).unwrap();
-            let dest_node = self.graph.node_weight(dest_idx).unwrap();
-            (edge_ref.weight().clone(), source_node, dest_node)
-        })
+        let Some(&cost) = distances.get(&end) else {
+            return Ok(None);
+        };
+        let mut path = vec![end];
+        let mut node = end;
+        while node != start {
+            node = previous[&node];
+            path.push(node);
+        }
+        path.reverse();
+        Ok(Some((path, cost)))
     }
-}
 
-pub fn load_network_links(csv_path: &str) -> Result<Vec<Link>, NetworkError> {
-    let mut rdr = csv::Reader::from_path(csv_path)?;
-    let mut network_links: Vec<Link> = Vec::new();
+    /// Adds the nodes and links of `other`. Nodes are matched by id, and a node present in
+    /// both keeps its position here. A link whose id is already taken is dropped when it
+    /// joins the same two nodes, and otherwise kept under the id with a `#2`, `#3`, ...
+    /// suffix.
+    pub fn merge(&mut self, other: &Network) {
+        for node in other.nodes() {
+            self.add_node(node.clone());
+        }
 
-    for network in rdr.deserialize() {
-        let loaded_link: Link = network?;
-        network_links.push(loaded_link);
+        for link in other.graph.edge_weights() {
+            let mut link = link.clone();
+            if let Some((_, existing)) = self.link_by_id(&link.link_id) {
+                let same_ends = (existing.source_node == link.source_node
+                    && existing.destination_node == link.destination_node)
+                    || (existing.source_node == link.destination_node
+                        && existing.destination_node == link.source_node);
+                if same_ends {
+                    continue;
+                }
+                link.link_id = (2..)
+                    .map(|n| format!("{}#{}", link.link_id, n))
+                    .find(|id| !self.link_indices.contains_key(id))
+                    .unwrap();
+            }
+            // Both endpoints were added above
+            let _ = self.add_link(link);
+        }
     }
 
-    Ok(network_links)
-}
+    /// Reports what [`Network::apply_load`] would do with `links`, without touching the
+    /// network.
+    pub fn dry_run_load(&self, links: &[Link]) -> LoadReport {
+        let mut report = LoadReport::default();
+        let mut seen_links = HashSet::new();
+        let mut new_nodes = HashSet::new();
+        for link in links {
+            if self.link_indices.contains_key(&link.link_id) || !seen_links.insert(&link.link_id) {
+                report.duplicate_edges += 1;
+                continue;
+            }
+            report.new_edges += 1;
 
-#[cfg(test)]
-mod test {
-    use super::*;
+            let mut dangling = false;
+            for id in [&link.source_node, &link.destination_node] {
+                if !self.node_indices.contains_key(id) {
+                    dangling = true;
+                    new_nodes.insert(id);
+                }
+            }
+            if dangling {
+                report.dangling_references += 1;
+            }
+        }
+        report.new_nodes = new_nodes.len();
+        report
+    }
 
-    #[test]
-    fn network_load_successful() {
-        let csv_content = "link_id,source_node,destination_node,capacity,weight\n\
-                           link_A,Node1,Node2,100,10\n\
-                           link_B,Node2,Node3,50,5\n\
-                           link_C,Node1,Node3,75,8\n";
+    /// Adds `links` to the network, skipping ids it already has. Missing endpoints are
+    /// created on a small circle around `center`.
+    pub fn apply_load(&mut self, links: &[Link], center: (f64, f64)) {
+        let new_nodes = self.dry_run_load(links).new_nodes.max(1);
+        let mut placed = 0;
+        for link in links {
+            if self.link_indices.contains_key(&link.link_id) {
+                continue;
+            }
+            for id in [&link.source_node, &link.destination_node] {
+                if self.node_indices.contains_key(id) {
+                    continue;
+                }
+                let angle = placed as f64 * std::f64::consts::TAU / new_nodes as f64;
+                placed += 1;
+                self.add_node(Node {
+                    id: id.clone(),
+                    point: (center.0 + 60.0 * angle.cos(), center.1 + 60.0 * angle.sin()),
+                    ..Node::default()
+                });
+            }
+            // Both endpoints exist now
+            let _ = self.add_link(link.clone());
+        }
+    }
 
-        let path = "test_configuration/test-network.csv";
-        std::fs::create_dir_all("test_configuration")
-            .expect("Failed to create test configuration directory");
-        std::fs::write(path, csv_content).expect("Failed to write dummy CSV");
+    /// Shortest-path tree over `Link::weight` rooted at `source`, found with Dijkstra: every
+    /// node reachable from `source` mapped to the node before it on a shortest path there,
+    /// `None` for `source` itself. Unreachable nodes are left out.
+    pub fn shortest_path_tree(&self, source: NodeIndex) -> HashMap<NodeIndex, Option<NodeIndex>> {
+        let (_, predecessors) = self.dijkstra(source, None, |edge| Some(edge.weight().weight));
+        predecessors
+            .into_iter()
+            .map(|(node, previous)| (node, Some(previous)))
+            .chain([(source, None)])
+            .collect()
+    }
 
-        let mut rdr = csv::Reader::from_path(path).unwrap();
-        let mut network_links: Vec<Link> = Vec::new();
+    /// Every node connected to `start` by some chain of links, `start` included.
+    pub fn reachable_from(&self, start: NodeIndex) -> HashSet<NodeIndex> {
+        let mut reached = HashSet::new();
+        if self.graph.node_weight(start).is_none() {
+            return reached;
+        }
 
-        for network in rdr.deserialize() {
-            let loaded_link: Link = network.unwrap();
-            network_links.push(loaded_link);
+        let mut queue = VecDeque::from([start]);
+        reached.insert(start);
+        while let Some(node) = queue.pop_front() {
+            for neighbor in self.graph.neighbors(node) {
+                if reached.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
         }
+        reached
+    }
 
-        let mut network = Network::new();
+    /// A copy of the part of the network within `radius` hops of `center`: those nodes, at
+    /// their current positions, and every link between two of them. Node and edge indices
+    /// are not preserved; ids are.
+    pub fn ego_subgraph(&self, center: NodeIndex, radius: usize) -> Network {
+        if self.graph.node_weight(center).is_none() {
+            return Network::new();
+        }
 
-        // First, add nodes
+        let nodes = self
+            .hop_distances(center)
+            .into_iter()
+            .filter(|&(_, hops)| hops as usize <= radius)
+            .map(|(idx, _)| idx)
+            .collect();
+        self.induced_subgraph(&nodes)
+    }
+
+    /// A copy of `nodes`, at their current positions, and every link with both ends among
+    /// them. Node and edge indices are not preserved; ids are.
+    pub fn induced_subgraph(&self, nodes: &HashSet<NodeIndex>) -> Network {
+        let mut subgraph = Network::new();
+        // Insert in index order so the copy lists nodes in the same order as the original
+        for idx in self.graph.node_indices() {
+            if nodes.contains(&idx) {
+                subgraph.add_node(self.graph[idx].clone());
+            }
+        }
+        for link in self.graph.edge_weights() {
+            if subgraph.node_indices.contains_key(&link.source_node)
+                && subgraph.node_indices.contains_key(&link.destination_node)
+            {
+                // Both endpoints were just added, so this cannot fail
+                let _ = subgraph.add_link(link.clone());
+            }
+        }
+        subgraph
+    }
+
+    /// Writes back the edits made to `part`, a copy of the nodes with ids in `region` and
+    /// the links among them such as [`Network::induced_subgraph`] returns. Region nodes
+    /// missing from `part` are removed along with all their links, the rest take `part`'s
+    /// version, and the links among region nodes are replaced by `part`'s. Nodes and links
+    /// new in `part` are added as by [`Network::merge`]. Indices shift as with
+    /// [`Network::remove_node`].
+    pub fn write_back(&mut self, region: &HashSet<String>, part: &Network) {
+        let mut inner_links: Vec<EdgeIndex> = self
+            .graph
+            .edge_indices()
+            .filter(|&edge| {
+                let link = &self.graph[edge];
+                region.contains(&link.source_node) && region.contains(&link.destination_node)
+            })
+            .collect();
+        // From the back, so the edge petgraph swaps into a freed slot is never one still to go
+        inner_links.sort_by(|a, b| b.cmp(a));
+        for edge in inner_links {
+            self.remove_link(edge);
+        }
+
+        let mut deleted: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| {
+                let id = &self.graph[idx].id;
+                region.contains(id) && !part.node_indices.contains_key(id)
+            })
+            .collect();
+        deleted.sort_by(|a, b| b.cmp(a));
+        for idx in deleted {
+            self.remove_node(idx);
+        }
+
+        for node in part.nodes() {
+            if let Some(&idx) = self.node_indices.get(&node.id) {
+                self.graph[idx] = node.clone();
+            }
+        }
+        self.invalidate_analysis();
+        self.merge(part);
+    }
+
+    /// Hop counts from `start` to every node reachable from it, found by BFS.
+    fn hop_distances(&self, start: NodeIndex) -> HashMap<NodeIndex, u32> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(current) = queue.pop_front() {
+            let next_distance = distances[&current] + 1;
+            for next in self.graph.neighbors(current) {
+                if let Entry::Vacant(entry) = distances.entry(next) {
+                    entry.insert(next_distance);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Hop distances between all pairs of connected nodes, from the analysis cache. Found
+    /// by a BFS from every node, or by Floyd–Warshall when the graph is dense enough that
+    /// the BFS runs would visit nearly every link from every node anyway.
+    fn all_pairs_hops(&self) -> &HashMap<NodeIndex, HashMap<NodeIndex, u32>> {
+        self.analysis.hops.get_or_init(|| {
+            let nodes = self.graph.node_count();
+            if 4 * self.graph.edge_count() >= nodes * nodes {
+                return self.floyd_warshall_hops();
+            }
+            self.graph
+                .node_indices()
+                .map(|start| (start, self.hop_distances(start)))
+                .collect()
+        })
+    }
+
+    /// Hop distances between all pairs of connected nodes by Floyd–Warshall over a dense
+    /// matrix, in the same shape as [`Network::all_pairs_hops`].
+    fn floyd_warshall_hops(&self) -> HashMap<NodeIndex, HashMap<NodeIndex, u32>> {
+        let nodes = self.graph.node_count();
+        let mut distances = vec![vec![u32::MAX; nodes]; nodes];
+        for (i, row) in distances.iter_mut().enumerate() {
+            row[i] = 0;
+        }
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            if a != b {
+                distances[a][b] = 1;
+                distances[b][a] = 1;
+            }
+        }
+
+        for k in 0..nodes {
+            let onward = distances[k].clone();
+            for row in &mut distances {
+                let through = row[k];
+                if through == u32::MAX {
+                    continue;
+                }
+                for (distance, &rest) in row.iter_mut().zip(&onward) {
+                    *distance = (*distance).min(through.saturating_add(rest));
+                }
+            }
+        }
+
+        self.graph
+            .node_indices()
+            .map(|start| {
+                let reachable = self
+                    .graph
+                    .node_indices()
+                    .filter(|end| distances[start.index()][end.index()] != u32::MAX)
+                    .map(|end| (end, distances[start.index()][end.index()]))
+                    .collect();
+                (start, reachable)
+            })
+            .collect()
+    }
+
+    /// Number of distinct shortest paths between all pairs of connected nodes, counted
+    /// from the cached hop distances: a path to a node continues one to any neighbour a hop
+    /// closer to the start. Parallel links do not make a path count twice.
+    fn all_pairs_path_counts(&self) -> &HashMap<NodeIndex, HashMap<NodeIndex, f64>> {
+        self.analysis.path_counts.get_or_init(|| {
+            self.all_pairs_hops()
+                .iter()
+                .map(|(&start, distances)| {
+                    let mut by_distance: Vec<(NodeIndex, u32)> = distances
+                        .iter()
+                        .map(|(&node, &hops)| (node, hops))
+                        .collect();
+                    by_distance.sort_by_key(|&(node, hops)| (hops, node));
+
+                    let mut counts = HashMap::from([(start, 1.0)]);
+                    for (node, hops) in by_distance.into_iter().skip(1) {
+                        let mut previous: Vec<NodeIndex> = self
+                            .graph
+                            .neighbors(node)
+                            .filter(|neighbor| distances.get(neighbor) == Some(&(hops - 1)))
+                            .collect();
+                        previous.sort();
+                        previous.dedup();
+                        let count = previous.iter().map(|neighbor| counts[neighbor]).sum();
+                        counts.insert(node, count);
+                    }
+                    (start, counts)
+                })
+                .collect()
+        })
+    }
+
+    /// Drops cached analysis results after the topology changed.
+    fn invalidate_analysis(&mut self) {
+        self.analysis = AnalysisCache::default();
+    }
+
+    /// Hop distances between every unordered pair of distinct, connected nodes.
+    fn connected_pair_distances(&self) -> Vec<u32> {
+        self.all_pairs_hops()
+            .iter()
+            .flat_map(|(&start, distances)| {
+                distances
+                    .iter()
+                    .filter(move |&(&end, _)| start < end)
+                    .map(|(_, &distance)| distance)
+            })
+            .collect()
+    }
+
+    /// The longest shortest path, in hops, between any two connected nodes. Pairs in
+    /// different components are ignored; returns `None` when no two nodes are connected.
+    pub fn diameter(&self) -> Option<u32> {
+        self.connected_pair_distances().into_iter().max()
+    }
+
+    /// The mean shortest-path length, in hops, over all connected pairs of nodes. Returns
+    /// `None` when no two nodes are connected.
+    pub fn average_path_length(&self) -> Option<f64> {
+        let distances = self.connected_pair_distances();
+        if distances.is_empty() {
+            return None;
+        }
+        let total: u32 = distances.iter().sum();
+        Some(total as f64 / distances.len() as f64)
+    }
+
+    /// Up to `k` loopless paths from `start` to `end` in increasing order of total
+    /// `Link::weight`, found with Yen's algorithm. Each path is returned as its node
+    /// sequence together with its cost; ties are broken in favour of fewer hops.
+    pub fn k_shortest_paths(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        k: usize,
+    ) -> Vec<(Vec<NodeIndex>, f64)> {
+        let mut found: Vec<(Vec<NodeIndex>, f64)> = Vec::new();
+        if k == 0 {
+            return found;
+        }
+        let Some(first) =
+            self.restricted_shortest_path(start, end, &HashSet::new(), &HashSet::new())
+        else {
+            return found;
+        };
+        found.push(first);
+
+        let mut candidates: Vec<(Vec<NodeIndex>, f64)> = Vec::new();
+        while found.len() < k {
+            let last_path = found[found.len() - 1].0.clone();
+            for i in 0..last_path.len() - 1 {
+                let spur = last_path[i];
+                let root = &last_path[..=i];
+
+                // Forbid the next hop of every accepted path that shares this root, and the
+                // root's own nodes, so the spur path deviates and stays loopless
+                let mut removed_edges: HashSet<EdgeIndex> = HashSet::new();
+                for (path, _) in &found {
+                    if path.len() > i + 1 && path[..=i] == *root {
+                        removed_edges.extend(
+                            self.graph
+                                .edges_connecting(path[i], path[i + 1])
+                                .map(|edge| edge.id()),
+                        );
+                    }
+                }
+                let removed_nodes: HashSet<NodeIndex> = root[..i].iter().copied().collect();
+
+                if let Some((spur_path, spur_cost)) =
+                    self.restricted_shortest_path(spur, end, &removed_edges, &removed_nodes)
+                {
+                    let mut path = root[..i].to_vec();
+                    path.extend(spur_path);
+                    let cost = self.path_weight(root) + spur_cost;
+                    let known = found.iter().chain(&candidates).any(|(p, _)| *p == path);
+                    if !known {
+                        candidates.push((path, cost));
+                    }
+                }
+            }
+
+            let Some(best) = candidates
+                .iter()
+                .enumerate()
+                .min_by(|(_, (a, a_cost)), (_, (b, b_cost))| {
+                    a_cost.total_cmp(b_cost).then(a.len().cmp(&b.len()))
+                })
+                .map(|(i, _)| i)
+            else {
+                break;
+            };
+            found.push(candidates.remove(best));
+        }
+
+        found
+    }
+
+    /// What each node would hold as its router table: for every other node it can reach, the
+    /// neighbor to forward to and the total `Link::weight` cost of the shortest path there,
+    /// as `(destination, next_hop, cost)` sorted by destination.
+    pub fn routing_tables(&self) -> HashMap<NodeIndex, Vec<(NodeIndex, NodeIndex, f64)>> {
+        self.graph
+            .node_indices()
+            .map(|start| {
+                let (distances, predecessors) =
+                    self.dijkstra(start, None, |edge| Some(edge.weight().weight));
+                // Paths leaving the start take its neighbor as their next hop
+                let first_hops = predecessors.keys().map(|&destination| {
+                    let mut hop = destination;
+                    while predecessors[&hop] != start {
+                        hop = predecessors[&hop];
+                    }
+                    (destination, hop)
+                });
+
+                let mut table: Vec<(NodeIndex, NodeIndex, f64)> = first_hops
+                    .map(|(destination, hop)| (destination, hop, distances[&destination]))
+                    .collect();
+                table.sort_by_key(|&(destination, _, _)| destination);
+                (start, table)
+            })
+            .collect()
+    }
+
+    /// Dijkstra from `start` to `end` over `Link::weight`, ignoring `removed_edges` and
+    /// never entering `removed_nodes`. Returns the node sequence and its cost.
+    fn restricted_shortest_path(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        removed_edges: &HashSet<EdgeIndex>,
+        removed_nodes: &HashSet<NodeIndex>,
+    ) -> Option<(Vec<NodeIndex>, f64)> {
+        let (distances, predecessors) = self.dijkstra(start, Some(end), |edge| {
+            let blocked =
+                removed_edges.contains(&edge.id()) || removed_nodes.contains(&edge.target());
+            (!blocked).then_some(edge.weight().weight)
+        });
+
+        let cost = *distances.get(&end)?;
+        let mut path = vec![end];
+        while let Some(&prev) = predecessors.get(&path[path.len() - 1]) {
+            path.push(prev);
+        }
+        path.reverse();
+        Some((path, cost))
+    }
+
+    /// Total `Link::weight` along a sequence of nodes, taking the lightest of any parallel
+    /// links between consecutive nodes. Infinite when two consecutive nodes are not linked.
+    fn path_weight(&self, path: &[NodeIndex]) -> f64 {
+        path.windows(2)
+            .map(|hop| {
+                self.graph
+                    .edges_connecting(hop[0], hop[1])
+                    .filter_map(|edge| edge.weight().path_cost())
+                    .min_by(f64::total_cmp)
+                    .unwrap_or(f64::INFINITY)
+            })
+            .sum()
+    }
+
+    /// Total latency in milliseconds along a sequence of nodes. Between consecutive nodes the
+    /// fastest of any parallel links is used; if two consecutive nodes are not linked at all
+    /// the path is impassable and the result is infinite.
+    pub fn path_latency(&self, path: &[NodeIndex]) -> f64 {
+        path.windows(2)
+            .map(|hop| {
+                self.graph
+                    .edges_connecting(hop[0], hop[1])
+                    .map(|edge| edge.weight().latency_ms)
+                    .min_by(f64::total_cmp)
+                    .unwrap_or(f64::INFINITY)
+            })
+            .sum()
+    }
+
+    /// Nodes whose removal would split their connected component (cut vertices), sorted by
+    /// index.
+    pub fn articulation_points(&self) -> Vec<NodeIndex> {
+        self.low_link_cuts().0
+    }
+
+    /// Links whose removal would increase the number of connected components, sorted by
+    /// index. One of several parallel links is never a bridge.
+    pub fn bridges(&self) -> Vec<EdgeIndex> {
+        self.low_link_cuts().1
+    }
+
+    /// Articulation points and bridges, found together with an iterative DFS that tracks
+    /// discovery times and low-links. Parents are skipped by edge rather than by node so
+    /// parallel links count as back edges.
+    fn low_link_cuts(&self) -> (Vec<NodeIndex>, Vec<EdgeIndex>) {
+        let mut bridges: Vec<EdgeIndex> = Vec::new();
+        let mut discovery: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut low: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut points: HashSet<NodeIndex> = HashSet::new();
+
+        for root in self.graph.node_indices() {
+            if discovery.contains_key(&root) {
+                continue;
+            }
+            discovery.insert(root, discovery.len());
+            low.insert(root, discovery[&root]);
+            let mut root_children = 0;
+
+            // Each frame holds a node, the edge it was reached through and its next edge
+            let mut stack = vec![(root, None, self.incident_edges(root), 0)];
+            while let Some((node, parent_edge, edges, next)) = stack.last_mut() {
+                let node = *node;
+                if let Some(&(edge, other)) = edges.get(*next) {
+                    *next += 1;
+                    if Some(edge) == *parent_edge {
+                        continue;
+                    }
+                    if let Some(&time) = discovery.get(&other) {
+                        low.insert(node, low[&node].min(time));
+                    } else {
+                        discovery.insert(other, discovery.len());
+                        low.insert(other, discovery[&other]);
+                        stack.push((other, Some(edge), self.incident_edges(other), 0));
+                    }
+                    continue;
+                }
+
+                let parent_edge = *parent_edge;
+                stack.pop();
+                if let Some((parent, ..)) = stack.last() {
+                    let parent = *parent;
+                    low.insert(parent, low[&parent].min(low[&node]));
+                    if low[&node] > discovery[&parent]
+                        && let Some(edge) = parent_edge
+                    {
+                        bridges.push(edge);
+                    }
+                    if parent == root {
+                        root_children += 1;
+                    } else if low[&node] >= discovery[&parent] {
+                        points.insert(parent);
+                    }
+                }
+            }
+
+            if root_children > 1 {
+                points.insert(root);
+            }
+        }
+
+        let mut points: Vec<NodeIndex> = points.into_iter().collect();
+        points.sort();
+        bridges.sort();
+        (points, bridges)
+    }
+
+    /// Maximum flow from `source` to `sink`, with the capacity each link has left over.
+    /// Links are undirected, so each carries flow one way or the other up to its capacity.
+    /// Returns zero flow and untouched capacities when `source == sink`.
+    pub fn max_flow_with_residuals(
+        &self,
+        source: NodeIndex,
+        sink: NodeIndex,
+    ) -> (u64, HashMap<EdgeIndex, u64>) {
+        let (total, flows) = self.max_flow_assignment(source, sink);
+        let residuals = self
+            .graph
+            .edge_indices()
+            .map(|edge| {
+                let used = flows.get(&edge).map_or(0, |flow| flow.unsigned_abs());
+                (edge, self.graph[edge].capacity - used)
+            })
+            .collect();
+        (total, residuals)
+    }
+
+    /// Edmonds–Karp over the undirected links. Flow on a link is signed: positive runs from
+    /// its source endpoint to its destination endpoint, negative the other way.
+    fn max_flow_assignment(
+        &self,
+        source: NodeIndex,
+        sink: NodeIndex,
+    ) -> (u64, HashMap<EdgeIndex, i64>) {
+        let mut flows: HashMap<EdgeIndex, i64> = HashMap::new();
+        let mut total = 0u64;
+        if source == sink {
+            return (total, flows);
+        }
+
+        // Spare capacity for pushing flow across `edge` out of `from`
+        let residual = |flows: &HashMap<EdgeIndex, i64>, edge: EdgeIndex, from: NodeIndex| {
+            let capacity = self.graph[edge].capacity as i64;
+            let flow = flows.get(&edge).copied().unwrap_or(0);
+            let forward = self.graph.edge_endpoints(edge).unwrap().0 == from;
+            if forward {
+                capacity - flow
+            } else {
+                capacity + flow
+            }
+        };
+
+        loop {
+            // Breadth-first search for the shortest augmenting path
+            let mut previous: HashMap<NodeIndex, (NodeIndex, EdgeIndex)> = HashMap::new();
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                if node == sink {
+                    break;
+                }
+                for (edge, next) in self.incident_edges(node) {
+                    if next != source
+                        && !previous.contains_key(&next)
+                        && residual(&flows, edge, node) > 0
+                    {
+                        previous.insert(next, (node, edge));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if !previous.contains_key(&sink) {
+                return (total, flows);
+            }
+
+            let mut hops = Vec::new();
+            let mut node = sink;
+            while let Some(&(from, edge)) = previous.get(&node) {
+                hops.push((from, edge));
+                node = from;
+            }
+            let bottleneck = hops
+                .iter()
+                .map(|&(from, edge)| residual(&flows, edge, from))
+                .min()
+                .unwrap_or(0);
+            for (from, edge) in hops {
+                let forward = self.graph.edge_endpoints(edge).unwrap().0 == from;
+                *flows.entry(edge).or_insert(0) += if forward { bottleneck } else { -bottleneck };
+            }
+            total += bottleneck as u64;
+        }
+    }
+
+    /// Edges touching `node` paired with the node at their other end.
+    fn incident_edges(&self, node: NodeIndex) -> Vec<(EdgeIndex, NodeIndex)> {
+        self.graph
+            .edges(node)
+            .map(|edge| {
+                let other = if edge.source() == node {
+                    edge.target()
+                } else {
+                    edge.source()
+                };
+                (edge.id(), other)
+            })
+            .collect()
+    }
+
+    pub fn capacity_stats(&self) -> CapacityStats {
+        let mut total_capacity = 0u64;
+        let mut total_weight = 0.0;
+        let mut max_link: Option<&Link> = None;
+        for link in self.graph.edge_weights() {
+            total_capacity += link.capacity;
+            total_weight += link.weight;
+            if max_link.is_none_or(|max| link.capacity > max.capacity) {
+                max_link = Some(link);
+            }
+        }
+
+        let link_count = self.graph.edge_count();
+        CapacityStats {
+            total_capacity,
+            total_weight,
+            average_capacity: if link_count == 0 {
+                0.0
+            } else {
+                total_capacity as f64 / link_count as f64
+            },
+            max_capacity_link: max_link.map(|link| link.link_id.clone()),
+        }
+    }
+
+    /// Counts, connectivity and the most central nodes, see [`AnalysisReport`].
+    pub fn analysis_report(&self) -> AnalysisReport {
+        let top_nodes = |scores: HashMap<NodeIndex, f64>| {
+            let mut ranked: Vec<RankedNode> = scores
+                .into_iter()
+                .map(|(idx, score)| RankedNode {
+                    id: self.graph[idx].id.clone(),
+                    score,
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+            ranked.truncate(REPORT_TOP_NODES);
+            ranked
+        };
+
+        AnalysisReport {
+            node_count: self.graph.node_count(),
+            link_count: self.graph.edge_count(),
+            density: self.density(),
+            diameter: self.diameter(),
+            components: connected_components(&self.graph),
+            top_closeness: top_nodes(self.closeness_centrality()),
+            top_betweenness: top_nodes(self.betweenness_centrality()),
+            top_pagerank: top_nodes(self.pagerank(0.85, 30)),
+        }
+    }
+
+    /// Share of possible node pairs that are linked, `2E / (N(N - 1))`. Parallel links and
+    /// self-loops count towards `E`, so a multigraph can exceed 1.0. Returns 0.0 with fewer
+    /// than two nodes.
+    pub fn density(&self) -> f64 {
+        let nodes = self.graph.node_count() as f64;
+        if nodes < 2.0 {
+            return 0.0;
+        }
+        2.0 * self.graph.edge_count() as f64 / (nodes * (nodes - 1.0))
+    }
+
+    /// Human-readable warnings about suspicious structure: nodes without any links and link
+    /// ids shared by more than one link. Empty when nothing looks off.
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| self.graph.edges(idx).next().is_none())
+            .map(|idx| format!("Node '{}' has no links", self.graph[idx].id))
+            .collect();
+
+        let mut link_counts: HashMap<&str, usize> = HashMap::new();
+        for link in self.graph.edge_weights() {
+            *link_counts.entry(link.link_id.as_str()).or_insert(0) += 1;
+        }
+        let mut duplicates: Vec<(&str, usize)> = link_counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .collect();
+        duplicates.sort();
+        warnings.extend(
+            duplicates
+                .into_iter()
+                .map(|(id, count)| format!("Link id '{}' is used by {} links", id, count)),
+        );
+        warnings
+    }
+
+    /// Lowest and highest link capacity, `(0, 0)` for a network without links.
+    pub fn capacity_range(&self) -> (u64, u64) {
+        self.graph
+            .edge_weights()
+            .map(|link| link.capacity)
+            .fold(None, |range, capacity| match range {
+                None => Some((capacity, capacity)),
+                Some((min, max)) => Some((capacity.min(min), capacity.max(max))),
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// Link counts per bucket, splitting the range between the lowest and highest link weight
+    /// into `buckets` equal-width buckets; the highest weight goes in the last one. All zeros
+    /// when there are no links.
+    pub fn weight_histogram(&self, buckets: usize) -> Vec<usize> {
+        let mut counts = vec![0; buckets];
+        let weights: Vec<f64> = self.graph.edge_weights().map(|link| link.weight).collect();
+        let Some(min) = weights.iter().copied().min_by(f64::total_cmp) else {
+            return counts;
+        };
+        let max = weights.iter().copied().fold(min, f64::max);
+        if buckets == 0 {
+            return counts;
+        }
+
+        let range = max - min;
+        for weight in weights {
+            let bucket = if range > 0.0 {
+                ((weight - min) / range * buckets as f64) as usize
+            } else {
+                0
+            };
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        counts
+    }
+
+    /// Degree assortativity: the Pearson correlation between the degrees at either end of
+    /// each link. Positive values mean hubs link to hubs, negative values mean hubs link to
+    /// low-degree nodes. `None` with fewer than two links or when every link joins nodes of
+    /// equal degree, where the correlation is undefined.
+    pub fn degree_assortativity(&self) -> Option<f64> {
+        let edge_count = self.graph.edge_count();
+        if edge_count < 2 {
+            return None;
+        }
+
+        let degree = |idx: NodeIndex| self.graph.edges(idx).count() as f64;
+        let (mut product, mut mean, mut square) = (0.0, 0.0, 0.0);
+        for edge in self.graph.edge_references() {
+            let (j, k) = (degree(edge.source()), degree(edge.target()));
+            product += j * k;
+            mean += (j + k) / 2.0;
+            square += (j * j + k * k) / 2.0;
+        }
+        let m = edge_count as f64;
+        let (product, mean, square) = (product / m, mean / m, square / m);
+
+        let variance = square - mean * mean;
+        if variance.abs() < 1e-12 {
+            return None;
+        }
+        Some((product - mean * mean) / variance)
+    }
+
+    /// Community id of every node, found with the Louvain method: nodes greedily move to the
+    /// neighbouring community with the best modularity gain (weighted by `Link::weight`),
+    /// then communities are merged into single nodes and the process repeats until nothing
+    /// moves. Ids are numbered from 0 in order of each community's lowest node index.
+    pub fn louvain_communities(&self) -> HashMap<NodeIndex, usize> {
+        let nodes: Vec<NodeIndex> = self.graph.node_indices().collect();
+        let position: HashMap<NodeIndex, usize> =
+            nodes.iter().enumerate().map(|(i, &idx)| (idx, i)).collect();
+
+        // Symmetric weighted adjacency; a self-loop is stored once on the diagonal
+        let mut adjacency: Vec<HashMap<usize, f64>> = vec![HashMap::new(); nodes.len()];
+        for edge in self.graph.edge_references() {
+            let (a, b) = (position[&edge.source()], position[&edge.target()]);
+            let weight = edge.weight().weight;
+            *adjacency[a].entry(b).or_default() += weight;
+            if a != b {
+                *adjacency[b].entry(a).or_default() += weight;
+            }
+        }
+
+        // Community of each original node, refined level by level
+        let mut membership: Vec<usize> = (0..nodes.len()).collect();
+        loop {
+            let (communities, moved) = louvain_local_moves(&adjacency);
+            if !moved {
+                break;
+            }
+
+            let (communities, count) = renumber(&communities);
+            for community in &mut membership {
+                *community = communities[*community];
+            }
+
+            let mut aggregated: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+            for (a, neighbors) in adjacency.iter().enumerate() {
+                for (&b, &weight) in neighbors {
+                    let (ca, cb) = (communities[a], communities[b]);
+                    // Internal pairs are seen from both ends, so each side adds half
+                    let share = if ca == cb && a != b {
+                        weight / 2.0
+                    } else {
+                        weight
+                    };
+                    *aggregated[ca].entry(cb).or_default() += share;
+                }
+            }
+            adjacency = aggregated;
+        }
+
+        let (membership, _) = renumber(&membership);
+        nodes.into_iter().zip(membership).collect()
+    }
+
+    /// Number of link ends at node `idx`. A link from the node to itself has both ends
+    /// there and counts twice.
+    pub fn degree(&self, idx: NodeIndex) -> usize {
+        self.graph
+            .edges(idx)
+            .map(|edge| if edge.source() == edge.target() { 2 } else { 1 })
+            .sum()
+    }
+
+    /// Degree centrality of every node as a plain [`Network::degree`], unnormalized.
+    pub fn degree_centrality(&self) -> HashMap<NodeIndex, usize> {
+        self.graph
+            .node_indices()
+            .map(|idx| (idx, self.degree(idx)))
+            .collect()
+    }
+
+    /// Betweenness centrality of every node in hops, from the analysis cache: summed over
+    /// every unordered pair of other nodes, the share of their shortest paths that pass
+    /// through it. Unnormalized, so a star's center scores the number of leaf pairs.
+    pub fn betweenness_centrality(&self) -> HashMap<NodeIndex, f64> {
+        let hops = self.all_pairs_hops();
+        let counts = self.all_pairs_path_counts();
+        let nodes: Vec<NodeIndex> = self.graph.node_indices().collect();
+        nodes
+            .iter()
+            .map(|&through| {
+                let mut score = 0.0;
+                for (i, &start) in nodes.iter().enumerate() {
+                    let Some(&to_through) = hops[&start].get(&through) else {
+                        continue;
+                    };
+                    if start == through {
+                        continue;
+                    }
+                    for &end in &nodes[i + 1..] {
+                        if end == through {
+                            continue;
+                        }
+                        let (Some(&total), Some(&from_through)) =
+                            (hops[&start].get(&end), hops[&through].get(&end))
+                        else {
+                            continue;
+                        };
+                        if to_through + from_through == total {
+                            score += counts[&start][&through] * counts[&through][&end]
+                                / counts[&start][&end];
+                        }
+                    }
+                }
+                (through, score)
+            })
+            .collect()
+    }
+
+    /// Closeness centrality of every node in hops, from the analysis cache. Uses the
+    /// Wasserman–Faust correction so nodes in small components do not look central just
+    /// because their few neighbours are close: the reciprocal mean distance to the `r - 1`
+    /// other reachable nodes is scaled by `(r - 1) / (n - 1)`. Isolated nodes score 0.
+    pub fn closeness_centrality(&self) -> HashMap<NodeIndex, f64> {
+        let node_count = self.graph.node_count();
+        self.all_pairs_hops()
+            .iter()
+            .map(|(&node, distances)| {
+                let others = distances.len() - 1;
+                let total: u32 = distances.values().sum();
+                let closeness = if others == 0 || total == 0 {
+                    0.0
+                } else {
+                    (others as f64 / total as f64) * (others as f64 / (node_count - 1) as f64)
+                };
+                (node, closeness)
+            })
+            .collect()
+    }
+
+    /// PageRank of every node, treating each undirected link as a pair of opposite arcs.
+    /// Rank held by isolated nodes is spread evenly over the graph, so the scores always
+    /// sum to 1.0.
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> HashMap<NodeIndex, f64> {
+        let node_count = self.graph.node_count();
+        if node_count == 0 {
+            return HashMap::new();
+        }
+        let n = node_count as f64;
+
+        let mut ranks: HashMap<NodeIndex, f64> = self
+            .graph
+            .node_indices()
+            .map(|idx| (idx, 1.0 / n))
+            .collect();
+
+        for _ in 0..iterations {
+            let dangling_rank: f64 = self
+                .graph
+                .node_indices()
+                .filter(|&idx| self.graph.neighbors(idx).next().is_none())
+                .map(|idx| ranks[&idx])
+                .sum();
+            let base = (1.0 - damping) / n + damping * dangling_rank / n;
+
+            let mut next: HashMap<NodeIndex, f64> =
+                self.graph.node_indices().map(|idx| (idx, base)).collect();
+            for idx in self.graph.node_indices() {
+                let neighbors: Vec<NodeIndex> = self.graph.neighbors(idx).collect();
+                let share = damping * ranks[&idx] / neighbors.len().max(1) as f64;
+                for neighbor in neighbors {
+                    *next.get_mut(&neighbor).unwrap() += share;
+                }
+            }
+            ranks = next;
+        }
+
+        ranks
+    }
+
+    /// The lowest numeric link id, counting up from the number of links, not yet in use.
+    pub fn next_link_id(&self) -> String {
+        (self.link_indices.len() + 1..)
+            .map(|n| n.to_string())
+            .find(|id| !self.link_indices.contains_key(id))
+            .unwrap()
+    }
+
+    pub fn add_link(&mut self, mut link: Link) -> Result<(), NetworkError> {
+        let source_index = *self
+            .node_indices
+            .get(&link.source_node)
+            .ok_or_else(|| NetworkError::NodeNotFound(link.source_node.clone()))?;
+
+        let destination_source = *self
+            .node_indices
+            .get(&link.destination_node)
+            .ok_or_else(|| NetworkError::NodeNotFound(link.destination_node.clone()))?;
+
+        let (source_metadata, destination_metadata) = std::mem::take(&mut link.endpoint_metadata);
+        self.graph[source_index].metadata.extend(source_metadata);
+        self.graph[destination_source]
+            .metadata
+            .extend(destination_metadata);
+
+        let link_id = link.link_id.clone();
+        let edge = self.graph.add_edge(source_index, destination_source, link);
+        self.link_indices.insert(link_id, edge);
+        self.invalidate_analysis();
+        Ok(())
+    }
+
+    /// Moves nodes apart until no two are closer than `min_distance`, e.g. when a file gave
+    /// every node the same position. Nodes are visited in index order and one that is too
+    /// close to an earlier node walks out along a spiral around its position, so the result
+    /// is deterministic and nodes that are already clear of the others stay put.
+    pub fn spread_overlapping(&mut self, min_distance: f64) {
+        // Golden angle, so consecutive spiral steps never line up
+        let step_angle = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+        let mut placed: Vec<(f64, f64)> = Vec::with_capacity(self.graph.node_count());
+        for idx in self.graph.node_indices() {
+            let origin = self.graph[idx].point;
+            let clear = |(x, y): (f64, f64)| {
+                placed
+                    .iter()
+                    .all(|&(px, py)| ((x - px).powi(2) + (y - py).powi(2)).sqrt() >= min_distance)
+            };
+            let point = (0..)
+                .map(|step: u32| {
+                    let radius = min_distance * (step as f64).sqrt();
+                    let angle = step as f64 * step_angle;
+                    (
+                        origin.0 + radius * angle.cos(),
+                        origin.1 + radius * angle.sin(),
+                    )
+                })
+                .find(|&point| clear(point))
+                .unwrap();
+            self.graph[idx].point = point;
+            placed.push(point);
+        }
+    }
+
+    /// Returns the bounding box of all node positions as `(min_x, min_y, max_x, max_y)`.
+    /// An empty network has a zero-sized box at the origin.
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        let mut nodes = self.nodes();
+        let Some(first) = nodes.next() else {
+            return (0.0, 0.0, 0.0, 0.0);
+        };
+        let (x, y) = first.point;
+
+        nodes.fold((x, y, x, y), |(min_x, min_y, max_x, max_y), node| {
+            let (x, y) = node.point;
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        })
+    }
+
+    /// Runs a Fruchterman–Reingold force-directed layout for up to `config.iterations`
+    /// steps, keeping nodes inside a `width` x `height` area with a 50px margin, or on its
+    /// middle along an axis too short for the margins. `pinned_node` and every anchored
+    /// node are left where they are. Stops early once no node moves further than
+    /// `config.epsilon` in an iteration.
+    ///
+    /// `Link::weight` is read as a desired length: each link's ideal length is the global
+    /// one scaled by its weight relative to the mean weight, so light links pull their ends
+    /// closer together than heavy ones. With uniform weights this is plain
+    /// Fruchterman–Reingold.
+    pub fn apply_force_directed_layout(
+        &mut self,
+        width: f64,
+        height: f64,
+        config: &LayoutConfig,
+        pinned_node: Option<NodeIndex>,
+    ) -> LayoutRun {
+        let mut progress = LayoutProgress::new(width, height, *config, pinned_node);
+        while self.layout_step(&mut progress) {}
+        progress.run
+    }
+
+    /// Runs the next iteration of the layout `progress` tracks, as
+    /// [`Network::apply_force_directed_layout`] would. Returns whether there are iterations
+    /// left to run.
+    pub fn layout_step(&mut self, progress: &mut LayoutProgress) -> bool {
+        let LayoutProgress {
+            width,
+            height,
+            config,
+            pinned_node,
+            ..
+        } = *progress;
+        let iteration = progress.run.iterations;
+        let indices: Vec<NodeIndex> = self.graph.node_indices().collect();
+        if progress.done || indices.is_empty() || iteration >= config.iterations {
+            progress.done = true;
+            return false;
+        }
+
+        let (min_x, min_y, max_x, max_y) = inset_area((0.0, 0.0, width, height), 50.0);
+        // Kept positive so a canvas with no area does not divide by zero
+        let ideal_length = ((width * height) / indices.len() as f64).sqrt().max(1.0);
+        let initial_temperature = width.min(height) / 10.0;
+
+        // Zero weights are treated as 1 so no link gets a zero ideal length
+        let link_weight = |link: &Link| link.weight.max(1.0);
+        let mean_weight = if self.graph.edge_count() == 0 {
+            1.0
+        } else {
+            self.graph.edge_weights().map(link_weight).sum::<f64>() / self.graph.edge_count() as f64
+        };
+
+        let mut displacements: HashMap<NodeIndex, (f64, f64)> =
+            indices.iter().map(|&idx| (idx, (0.0, 0.0))).collect();
+
+        // Every pair of nodes repels
+        for (i, &a) in indices.iter().enumerate() {
+            for &b in &indices[i + 1..] {
+                let (dx, dy, distance) = self.separation(a, b);
+                let force = ideal_length * ideal_length / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                let disp_a = displacements.get_mut(&a).unwrap();
+                disp_a.0 += fx;
+                disp_a.1 += fy;
+                let disp_b = displacements.get_mut(&b).unwrap();
+                disp_b.0 -= fx;
+                disp_b.1 -= fy;
+            }
+        }
+
+        // Linked nodes attract; self-loops exert no force
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source(), edge.target());
+            if a == b {
+                continue;
+            }
+            let (dx, dy, distance) = self.separation(a, b);
+            let link_length = ideal_length * link_weight(edge.weight()) / mean_weight;
+            let force = distance * distance / link_length;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            let disp_a = displacements.get_mut(&a).unwrap();
+            disp_a.0 -= fx;
+            disp_a.1 -= fy;
+            let disp_b = displacements.get_mut(&b).unwrap();
+            disp_b.0 += fx;
+            disp_b.1 += fy;
+        }
+
+        // Cool down so the layout settles by the final iteration
+        let temperature =
+            config
+                .cooling
+                .temperature(initial_temperature, iteration, config.iterations);
+        let mut max_displacement: f64 = 0.0;
+
+        for (idx, (dx, dy)) in displacements {
+            if Some(idx) == pinned_node || self.graph[idx].anchored {
+                continue;
+            }
+            let length = (dx * dx + dy * dy).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let step = length.min(temperature);
+            let node = &mut self.graph[idx];
+            let old_point = node.point;
+            node.point.0 = (node.point.0 + dx / length * step).clamp(min_x, max_x);
+            node.point.1 = (node.point.1 + dy / length * step).clamp(min_y, max_y);
+
+            let moved_x = node.point.0 - old_point.0;
+            let moved_y = node.point.1 - old_point.1;
+            max_displacement = f64::max(
+                max_displacement,
+                (moved_x * moved_x + moved_y * moved_y).sqrt(),
+            );
+        }
+
+        progress.run = LayoutRun {
+            iterations: iteration + 1,
+            max_displacement,
+        };
+        progress.done =
+            max_displacement < config.epsilon || progress.run.iterations >= config.iterations;
+        !progress.done
+    }
+
+    /// Runs the force layout over only the `movable` nodes, treating every other node as
+    /// anchored for the run, so nodes added to a settled layout find a place without
+    /// disturbing the rest. Anchors set by the user are left as they were.
+    pub fn apply_layout_to(
+        &mut self,
+        width: f64,
+        height: f64,
+        config: &LayoutConfig,
+        movable: &HashSet<NodeIndex>,
+    ) -> LayoutRun {
+        let fixed: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|idx| !movable.contains(idx) && !self.graph[*idx].anchored)
+            .collect();
+        for &idx in &fixed {
+            self.graph[idx].anchored = true;
+        }
+        let run = self.apply_force_directed_layout(width, height, config, None);
+        for &idx in &fixed {
+            self.graph[idx].anchored = false;
+        }
+        run
+    }
+
+    /// Vector from `b` to `a` and its length. Coincident nodes are pushed apart along a
+    /// direction derived from their indices so the forces never divide by zero.
+    fn separation(&self, a: NodeIndex, b: NodeIndex) -> (f64, f64, f64) {
+        let (ax, ay) = self.graph[a].point;
+        let (bx, by) = self.graph[b].point;
+        let (dx, dy) = (ax - bx, ay - by);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance > 0.01 {
+            return (dx, dy, distance);
+        }
+
+        let angle = (a.index() * 31 + b.index() * 17) as f64;
+        (angle.cos() * 0.01, angle.sin() * 0.01, 0.01)
+    }
+
+    /// Lays the network out as a tree rooted at `root`: a BFS assigns each node a depth, with
+    /// the root at `y = level_height` and every further level `level_height` below, and each
+    /// subtree gets a share of `width` proportional to its number of leaves, with parents
+    /// centered over their children. Edges outside the BFS tree keep being drawn as usual;
+    /// nodes unreachable from `root` are spread along one extra level at the bottom.
+    pub fn apply_tree_layout(&mut self, root: NodeIndex, width: f64, level_height: f64) {
+        if self.graph.node_weight(root).is_none() {
+            return;
+        }
+
+        let mut depth: HashMap<NodeIndex, usize> = HashMap::from([(root, 0)]);
+        let mut children: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut order = vec![root];
+        let mut queue = VecDeque::from([root]);
+        while let Some(current) = queue.pop_front() {
+            let next_depth = depth[&current] + 1;
+            let mut neighbors: Vec<NodeIndex> = self.graph.neighbors(current).collect();
+            neighbors.sort();
+            neighbors.dedup();
+            for neighbor in neighbors {
+                if let Entry::Vacant(entry) = depth.entry(neighbor) {
+                    entry.insert(next_depth);
+                    children.entry(current).or_default().push(neighbor);
+                    order.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        // Leaf counts bottom-up, so every subtree knows how much horizontal room it needs
+        let mut leaves: HashMap<NodeIndex, usize> = HashMap::new();
+        for &idx in order.iter().rev() {
+            let count = match children.get(&idx) {
+                Some(kids) => kids.iter().map(|kid| leaves[kid]).sum(),
+                None => 1,
+            };
+            leaves.insert(idx, count);
+        }
+
+        let slot = width / leaves[&root] as f64;
+        let mut spans: HashMap<NodeIndex, f64> = HashMap::from([(root, 0.0)]);
+        for &idx in &order {
+            let left = spans[&idx];
+            self.graph[idx].point = (
+                left + leaves[&idx] as f64 * slot / 2.0,
+                (depth[&idx] + 1) as f64 * level_height,
+            );
+
+            let mut offset = left;
+            for kid in children.get(&idx).into_iter().flatten() {
+                spans.insert(*kid, offset);
+                offset += leaves[kid] as f64 * slot;
+            }
+        }
+
+        let unreachable: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|idx| !depth.contains_key(idx))
+            .collect();
+        let bottom = (depth.values().max().copied().unwrap_or(0) + 2) as f64 * level_height;
+        let spacing = width / unreachable.len().max(1) as f64;
+        for (i, idx) in unreachable.into_iter().enumerate() {
+            self.graph[idx].point = ((i as f64 + 0.5) * spacing, bottom);
+        }
+    }
+
+    /// Rounds every node's position to the nearest whole coordinates, halfway values away
+    /// from zero, for formats that prefer integers.
+    pub fn round_positions(&mut self) {
+        for node in self.graph.node_weights_mut() {
+            node.point = (node.point.0.round(), node.point.1.round());
+        }
+    }
+
+    /// Uniformly scales and translates all nodes so their bounding box fills a `width` x
+    /// `height` area, leaving `margin` on every side. The aspect ratio is preserved and the
+    /// result is centered; a single point collapses to the middle of the area.
+    pub fn fit_to_bounds(&mut self, width: f64, height: f64, margin: f64) {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let (bounds_width, bounds_height) = (max_x - min_x, max_y - min_y);
+        let (available_width, available_height) = (width - 2.0 * margin, height - 2.0 * margin);
+
+        let scale = match (bounds_width > 0.0, bounds_height > 0.0) {
+            (true, true) => (available_width / bounds_width).min(available_height / bounds_height),
+            (true, false) => available_width / bounds_width,
+            (false, true) => available_height / bounds_height,
+            (false, false) => 0.0,
+        };
+
+        let offset_x = margin + (available_width - bounds_width * scale) / 2.0;
+        let offset_y = margin + (available_height - bounds_height * scale) / 2.0;
+
+        for node in self.graph.node_weights_mut() {
+            node.point.0 = offset_x + (node.point.0 - min_x) * scale;
+            node.point.1 = offset_y + (node.point.1 - min_y) * scale;
+        }
+    }
+
+    pub fn export_positions(&self) -> Vec<(String, f64, f64)> {
+        self.nodes()
+            .map(|node| (node.id.clone(), node.point.0, node.point.1))
+            .collect()
+    }
+
+    /// Node coordinates as a JSON array of `{"id": .., "x": .., "y": ..}` objects, for drawing
+    /// the same layout in a browser.
+    pub fn export_layout_json(&self) -> String {
+        let positions: Vec<NodePosition> = self
+            .nodes()
+            .map(|node| NodePosition {
+                id: node.id.clone(),
+                x: node.point.0,
+                y: node.point.1,
+            })
+            .collect();
+        serde_json::to_string(&positions).expect("positions serialize to JSON")
+    }
+
+    /// The whole network, nodes with their attributes and links, as JSON tagged with
+    /// [`NETWORK_JSON_VERSION`] so [`Network::from_json`] can read it back.
+    pub fn to_json(&self) -> String {
+        let document = NetworkDocument {
+            version: NETWORK_JSON_VERSION,
+            nodes: self.nodes().cloned().collect(),
+            links: self.graph.edge_weights().cloned().collect(),
+        };
+        serde_json::to_string_pretty(&document).expect("network serializes to JSON")
+    }
+
+    /// Reads JSON written by [`Network::to_json`], migrating older versions first. A file
+    /// without a `version` is taken as the synthetic version 1. Versions newer than
+    /// [`NETWORK_JSON_VERSION`] fail with [`NetworkError::UnsupportedVersion`] rather than
+    /// being misread.
+    pub fn from_json(s: &str) -> Result<Network, NetworkError> {
+        let mut document: serde_json::Map<String, serde_json::Value> = serde_json::from_str(s)?;
+        let version = match document.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| NetworkError::Parse(format!("Invalid version {}", version)))?,
+        };
+        match version {
+            1 => migrate_v1_to_v2(&mut document),
+            NETWORK_JSON_VERSION => {}
+            _ => return Err(NetworkError::UnsupportedVersion(version)),
+        }
+
+        let document: NetworkDocument = serde_json::from_value(document.into())?;
+        let mut network = Network::new();
+        for node in document.nodes {
+            network.add_node(node);
+        }
+        for link in document.links {
+            network.add_link(link)?;
+        }
+        Ok(network)
+    }
+
+    /// Moves every node named in `positions` to the stored coordinates. Ids that are not
+    /// part of the network are ignored.
+    pub fn apply_positions(&mut self, positions: &[(String, f64, f64)]) {
+        for (id, x, y) in positions {
+            if let Some(&idx) = self.node_indices.get(id) {
+                self.graph[idx].point = (*x, *y);
+            }
+        }
+    }
+
+    /// Sets the geographic `(id, latitude, longitude)` of every node named in `coordinates`.
+    /// Ids that are not part of the network are ignored.
+    pub fn apply_coordinates(&mut self, coordinates: &[(String, f64, f64)]) {
+        for (id, lat, lon) in coordinates {
+            if let Some(&idx) = self.node_indices.get(id) {
+                self.graph[idx].lat_lon = Some((*lat, *lon));
+            }
+        }
+    }
+
+    pub fn has_coordinates(&self) -> bool {
+        self.nodes().any(|node| node.lat_lon.is_some())
+    }
+
+    /// Places nodes by the eigenvectors of the graph Laplacian `L = D - A` for its second and
+    /// third smallest eigenvalues, x and y respectively, each stretched to fill `width` x
+    /// `height` inside a 50px margin. Symmetric structure tends to show up well. The
+    /// eigenvectors are found by power iteration on `s * I - L`, whose largest eigenvalues
+    /// are the smallest of `L`, deflating the constant eigenvector and each one found.
+    pub fn apply_spectral_layout(&mut self, width: f64, height: f64) {
+        let margin = 50.0;
+        let n = self.graph.node_count();
+        let mut degrees = vec![0.0; n];
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            if a != b {
+                degrees[a] += 1.0;
+                degrees[b] += 1.0;
+                adjacency[a].push(b);
+                adjacency[b].push(a);
+            }
+        }
+        // No eigenvalue of L exceeds twice the largest degree, so the shifted matrix stays
+        // positive semi-definite and the iteration never flips sign
+        let shift = 2.0 * degrees.iter().copied().fold(0.0, f64::max);
+        let multiply = |v: &[f64]| -> Vec<f64> {
+            (0..n)
+                .map(|i| {
+                    let laplacian =
+                        degrees[i] * v[i] - adjacency[i].iter().map(|&j| v[j]).sum::<f64>();
+                    shift * v[i] - laplacian
+                })
+                .collect()
+        };
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+
+        let mut eigenvectors = vec![vec![1.0 / (n as f64).sqrt(); n]];
+        // Irrational strides give start vectors with a share of every eigenvector
+        for stride in [0.618_033_988_7, 0.414_213_562_4] {
+            let mut v: Vec<f64> = (0..n)
+                .map(|i| ((i as f64 + 1.0) * stride).fract() - 0.5)
+                .collect();
+            for _ in 0..2000 {
+                let mut next = multiply(&v);
+                for eigenvector in &eigenvectors {
+                    let projection = dot(&next, eigenvector);
+                    for (x, e) in next.iter_mut().zip(eigenvector) {
+                        *x -= projection * e;
+                    }
+                }
+                let norm = dot(&next, &next).sqrt();
+                if norm < 1e-12 {
+                    // Fewer nodes than eigenvectors asked for
+                    v = vec![0.0; n];
+                    break;
+                }
+                next.iter_mut().for_each(|x| *x /= norm);
+                let change: f64 = next.iter().zip(&v).map(|(a, b)| (a - b).abs()).sum();
+                v = next;
+                if change < 1e-10 {
+                    break;
+                }
+            }
+            eigenvectors.push(v);
+        }
+
+        let fit = |values: &[f64], size: f64| -> Vec<f64> {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            values
+                .iter()
+                .map(|value| {
+                    if max - min < 1e-9 {
+                        size / 2.0
+                    } else {
+                        margin + (value - min) / (max - min) * (size - 2.0 * margin)
+                    }
+                })
+                .collect()
+        };
+        let xs = fit(&eigenvectors[1], width);
+        let ys = fit(&eigenvectors[2], height);
+        for (i, node) in self.graph.node_weights_mut().enumerate() {
+            node.point = (xs[i], ys[i]);
+        }
+    }
+
+    /// Places nodes with known coordinates using an equirectangular projection: longitude
+    /// maps linearly to x and latitude to y (north up), with one shared scale so shapes are
+    /// not stretched. The projected extent is centered in `width` x `height` with a 50px
+    /// margin. Nodes without coordinates keep their current position.
+    pub fn apply_geographic_layout(&mut self, width: f64, height: f64) {
+        let margin = 50.0;
+        let coordinates: Vec<(f64, f64)> = self.nodes().filter_map(|node| node.lat_lon).collect();
+        if coordinates.is_empty() {
+            return;
+        }
+
+        let min_lat = coordinates
+            .iter()
+            .map(|c| c.0)
+            .fold(f64::INFINITY, f64::min);
+        let max_lat = coordinates
+            .iter()
+            .map(|c| c.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_lon = coordinates
+            .iter()
+            .map(|c| c.1)
+            .fold(f64::INFINITY, f64::min);
+        let max_lon = coordinates
+            .iter()
+            .map(|c| c.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let (available_width, available_height) = (width - 2.0 * margin, height - 2.0 * margin);
+        let (lon_span, lat_span) = (max_lon - min_lon, max_lat - min_lat);
+        let scale = match (lon_span > 0.0, lat_span > 0.0) {
+            (true, true) => (available_width / lon_span).min(available_height / lat_span),
+            (true, false) => available_width / lon_span,
+            (false, true) => available_height / lat_span,
+            (false, false) => 0.0,
+        };
+        let offset_x = margin + (available_width - lon_span * scale) / 2.0;
+        let offset_y = margin + (available_height - lat_span * scale) / 2.0;
+
+        for node in self.graph.node_weights_mut() {
+            if let Some((lat, lon)) = node.lat_lon {
+                node.point = (
+                    offset_x + (lon - min_lon) * scale,
+                    offset_y + (max_lat - lat) * scale,
+                );
+            }
+        }
+    }
+
+    /// Sets the weight of the link at `edge`. Returns `false`, leaving the link as it was,
+    /// when there is no such link or `weight` is negative, infinite or NaN.
+    pub fn set_link_weight(&mut self, edge: EdgeIndex, weight: f64) -> bool {
+        if !is_valid_weight(weight) {
+            return false;
+        }
+        let Some(link) = self.graph.edge_weight_mut(edge) else {
+            return false;
+        };
+        link.weight = weight;
+        true
+    }
+
+    /// Removes the link stored at `edge`. petgraph moves the last edge into the freed slot,
+    /// so any `EdgeIndex` held elsewhere may be stale afterwards.
+    pub fn remove_link(&mut self, edge: EdgeIndex) -> Option<Link> {
+        let removed = self.graph.remove_edge(edge)?;
+        self.invalidate_analysis();
+        if self.link_indices.get(&removed.link_id) == Some(&edge) {
+            self.link_indices.remove(&removed.link_id);
+        }
+
+        // Re-point the index of the link that petgraph swapped into the freed slot
+        if let Some(moved) = self.graph.edge_weight(edge) {
+            self.link_indices.insert(moved.link_id.clone(), edge);
+        }
+
+        Some(removed)
+    }
+
+    /// Removes the node at `idx` together with all of its links. petgraph moves the last
+    /// node (and possibly several edges) into the freed slots, so the id lookups are rebuilt
+    /// and any index held elsewhere may be stale afterwards.
+    pub fn remove_node(&mut self, idx: NodeIndex) -> Option<Node> {
+        let removed = self.graph.remove_node(idx)?;
+        self.invalidate_analysis();
+        self.node_indices = self
+            .graph
+            .node_indices()
+            .map(|idx| (self.graph[idx].id.clone(), idx))
+            .collect();
+        self.link_indices = self
+            .graph
+            .edge_indices()
+            .map(|edge| (self.graph[edge].link_id.clone(), edge))
+            .collect();
+        Some(removed)
+    }
+
+    /// `id`, or when a node other than the `replaced` ones already has it, `id` with the
+    /// first free `#2`, `#3`, ... suffix.
+    fn free_node_id(&self, id: String, replaced: &[NodeIndex]) -> String {
+        let taken = |id: &String| {
+            self.node_indices
+                .get(id)
+                .is_some_and(|idx| !replaced.contains(idx))
+        };
+        if !taken(&id) {
+            return id;
+        }
+        (2..)
+            .map(|n| format!("{}#{}", id, n))
+            .find(|candidate| !taken(candidate))
+            .unwrap()
+    }
+
+    /// Merges the two ends of `edge` into a single node named `<source>+<destination>`, or
+    /// `<source>+<destination>#2`, ... when that id is taken, halfway between them. Every
+    /// other link of either end moves to the merged node, while `edge` and any parallel
+    /// links, which would become self-loops, are dropped. Metadata is combined with the
+    /// source's values winning. Node and edge indices shift as with
+    /// [`Network::remove_node`]; returns the merged node, or `None` when `edge` does not
+    /// exist.
+    pub fn contract_edge(&mut self, edge: EdgeIndex) -> Option<NodeIndex> {
+        let (a, b) = self.graph.edge_endpoints(edge)?;
+        if a == b {
+            self.remove_link(edge);
+            return Some(a);
+        }
+        let (first, second) = (self.graph[a].clone(), self.graph[b].clone());
+        let id = self.free_node_id(format!("{}+{}", first.id, second.id), &[a, b]);
+        let mut metadata = second.metadata;
+        metadata.extend(first.metadata);
+        let merged = Node {
+            id: id.clone(),
+            point: (
+                (first.point.0 + second.point.0) / 2.0,
+                (first.point.1 + second.point.1) / 2.0,
+            ),
+            lat_lon: first.lat_lon.or(second.lat_lon),
+            metadata,
+            anchored: first.anchored || second.anchored,
+        };
+
+        let mut links: Vec<EdgeIndex> = self
+            .graph
+            .edges(a)
+            .chain(self.graph.edges(b))
+            .map(|edge| edge.id())
+            .collect();
+        links.sort();
+        links.dedup();
+        let links: Vec<Link> = links
+            .into_iter()
+            .map(|edge| self.graph[edge].clone())
+            .filter(|link| {
+                let ends = [&link.source_node, &link.destination_node];
+                !(ends.contains(&&first.id) && ends.contains(&&second.id))
+            })
+            .map(|mut link| {
+                for endpoint in [&mut link.source_node, &mut link.destination_node] {
+                    if *endpoint == first.id || *endpoint == second.id {
+                        *endpoint = id.clone();
+                    }
+                }
+                link
+            })
+            .collect();
+
+        // Look the second end up again, removing the first may have moved it
+        self.remove_node(a);
+        let b = self.node_indices[&second.id];
+        self.remove_node(b);
+        let merged = self.add_node(merged);
+        for link in links {
+            // Both endpoints exist, so adding cannot fail
+            let _ = self.add_link(link);
+        }
+        Some(merged)
+    }
+
+    /// Collapses `nodes` into a single node named after its members joined by `+`, suffixed
+    /// with `#2`, ... when another node has that id, at their centroid, with the total
+    /// capacity of the links inside the group recorded in its `internal_capacity` metadata.
+    /// Links from the group to each outside node are merged into one, keeping the first
+    /// link's id and direction, summing capacities and taking the lowest weight. Node and
+    /// edge indices shift as with [`Network::remove_node`]; returns the new node, or `None`
+    /// when none of `nodes` exist.
+    pub fn collapse_group(&mut self, nodes: &HashSet<NodeIndex>) -> Option<NodeIndex> {
+        let mut members: Vec<NodeIndex> = nodes
+            .iter()
+            .copied()
+            .filter(|&idx| self.graph.node_weight(idx).is_some())
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+        members.sort();
+        let member_ids: Vec<String> = members
+            .iter()
+            .map(|&idx| self.graph[idx].id.clone())
+            .collect();
+        let id = self.free_node_id(member_ids.join("+"), &members);
+
+        let count = members.len() as f64;
+        let (sum_x, sum_y) = members.iter().fold((0.0, 0.0), |(x, y), &idx| {
+            (x + self.graph[idx].point.0, y + self.graph[idx].point.1)
+        });
+        // Earlier members win conflicting metadata
+        let mut metadata = Metadata::new();
+        for &idx in members.iter().rev() {
+            metadata.extend(self.graph[idx].metadata.clone());
+        }
+
+        let mut edges: Vec<EdgeIndex> = self.graph.edge_indices().collect();
+        edges.retain(|&edge| {
+            let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+            members.contains(&a) || members.contains(&b)
+        });
+        let mut internal_capacity: u64 = 0;
+        let mut external: Vec<Link> = Vec::new();
+        for edge in edges {
+            let link = &self.graph[edge];
+            let inside = |end: &String| member_ids.contains(end);
+            match (inside(&link.source_node), inside(&link.destination_node)) {
+                (true, true) => internal_capacity = internal_capacity.saturating_add(link.capacity),
+                (source_inside, _) => {
+                    let neighbor = if source_inside {
+                        &link.destination_node
+                    } else {
+                        &link.source_node
+                    };
+                    let existing = external.iter_mut().find(|merged| {
+                        merged.source_node == *neighbor || merged.destination_node == *neighbor
+                    });
+                    match existing {
+                        Some(merged) => {
+                            merged.capacity = merged.capacity.saturating_add(link.capacity);
+                            merged.weight = merged.weight.min(link.weight);
+                        }
+                        None => {
+                            let mut merged = link.clone();
+                            if source_inside {
+                                merged.source_node = id.clone();
+                            } else {
+                                merged.destination_node = id.clone();
+                            }
+                            external.push(merged);
+                        }
+                    }
+                }
+            }
+        }
+        metadata.insert(
+            "internal_capacity".to_string(),
+            internal_capacity.to_string(),
+        );
+
+        let collapsed = Node {
+            id: id.clone(),
+            point: (sum_x / count, sum_y / count),
+            metadata,
+            anchored: members.iter().any(|&idx| self.graph[idx].anchored),
+            ..Node::default()
+        };
+        // Look every member up by id, removing one may have moved the others
+        for member in &member_ids {
+            let idx = self.node_indices[member];
+            self.remove_node(idx);
+        }
+        let collapsed = self.add_node(collapsed);
+        for link in external {
+            // Both endpoints exist, so adding cannot fail
+            let _ = self.add_link(link);
+        }
+        Some(collapsed)
+    }
+
+    /// Collapses every group of parallel links into its first link, which takes the summed
+    /// capacity and the weight `combine` picks; the others are removed. Edge indices shift
+    /// as with [`Network::remove_link`]. Returns how many links were removed.
+    pub fn simplify(&mut self, combine: CombinePolicy) -> usize {
+        let mut groups: HashMap<(NodeIndex, NodeIndex), Vec<EdgeIndex>> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source(), edge.target());
+            groups
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push(edge.id());
+        }
+
+        let mut redundant = Vec::new();
+        for mut edges in groups.into_values().filter(|edges| edges.len() > 1) {
+            edges.sort();
+            let links: Vec<&Link> = edges.iter().map(|&edge| &self.graph[edge]).collect();
+            let capacity = links
+                .iter()
+                .fold(0_u64, |total, link| total.saturating_add(link.capacity));
+            let weight = match combine {
+                CombinePolicy::Min => links
+                    .iter()
+                    .map(|link| link.weight)
+                    .fold(f64::INFINITY, f64::min),
+                CombinePolicy::Average => {
+                    links.iter().map(|link| link.weight).sum::<f64>() / links.len() as f64
+                }
+            };
+            let kept = &mut self.graph[edges[0]];
+            kept.capacity = capacity;
+            kept.weight = weight;
+            redundant.extend_from_slice(&edges[1..]);
+        }
+
+        // Highest index first, so the edge petgraph swaps into a freed slot is never one
+        // still waiting to be removed
+        redundant.sort_unstable_by(|a, b| b.cmp(a));
+        for &edge in &redundant {
+            self.remove_link(edge);
+        }
+        redundant.len()
+    }
+
+    pub fn link_by_id(&self, id: &str) -> Option<(EdgeIndex, &Link)> {
+        let &edge = self.link_indices.get(id)?;
+        self.graph.edge_weight(edge).map(|link| (edge, link))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.graph.node_weights()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = (EdgeIndex, Link, &Node, &Node)> {
+        self.graph.edge_references().map(|edge_ref| {
+            let (source_idx, dest_idx) = self.graph.edge_endpoints(edge_ref.id()).unwrap();
+            let source_node = self.graph.node_weight(source_idx).unwrap();
+            let dest_node = self.graph.node_weight(dest_idx).unwrap();
+            (
+                edge_ref.id(),
+                edge_ref.weight().clone(),
+                source_node,
+                dest_node,
+            )
+        })
+    }
+
+    /// A directed copy of the network with one arc per link, running from its declared
+    /// `source_node` to its `destination_node`. Nodes keep their indices; arcs are numbered
+    /// afresh.
+    pub fn to_directed(&self) -> DiGraph<Node, Link> {
+        let mut directed = DiGraph::with_capacity(self.graph.node_count(), self.graph.edge_count());
+        for node in self.graph.node_weights() {
+            directed.add_node(node.clone());
+        }
+        for edge in self.graph.edge_indices() {
+            if let Some((from, to)) = self.link_direction(edge) {
+                directed.add_edge(from, to, self.graph[edge].clone());
+            }
+        }
+        directed
+    }
+
+    /// The link at `edge` as a `(from, to)` pair of nodes, following its declared
+    /// `source_node` and `destination_node` rather than how the undirected graph stores it.
+    pub fn link_direction(&self, edge: EdgeIndex) -> Option<(NodeIndex, NodeIndex)> {
+        let link = self.graph.edge_weight(edge)?;
+        Some((
+            *self.node_indices.get(&link.source_node)?,
+            *self.node_indices.get(&link.destination_node)?,
+        ))
+    }
+
+    /// For every edge, its slot among the edges joining the same pair of nodes and the size
+    /// of that group, so parallel links can be drawn side by side.
+    pub fn parallel_edge_slots(&self) -> HashMap<EdgeIndex, (usize, usize)> {
+        let mut groups: HashMap<(NodeIndex, NodeIndex), Vec<EdgeIndex>> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source(), edge.target());
+            groups
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push(edge.id());
+        }
+
+        groups
+            .into_values()
+            .flat_map(|edges| {
+                let count = edges.len();
+                edges
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(slot, edge)| (edge, (slot, count)))
+            })
+            .collect()
+    }
+}
+
+/// Collects nodes and links in any order and resolves them into a [`Network`] on
+/// [`build`](NetworkBuilder::build). By default, link endpoints that were never declared
+/// with [`node`](NetworkBuilder::node) are created at the origin; in
+/// [`strict`](NetworkBuilder::strict) mode they are reported instead.
+#[derive(Debug, Clone)]
+pub struct NetworkBuilder {
+    nodes: Vec<Node>,
+    links: Vec<Link>,
+    create_missing_nodes: bool,
+}
+
+impl Default for NetworkBuilder {
+    fn default() -> Self {
+        NetworkBuilder {
+            nodes: Vec::new(),
+            links: Vec::new(),
+            create_missing_nodes: true,
+        }
+    }
+}
+
+impl NetworkBuilder {
+    pub fn new() -> Self {
+        NetworkBuilder::default()
+    }
+
+    pub fn node(mut self, id: &str, x: f64, y: f64) -> Self {
+        self.nodes.push(Node {
+            id: id.to_string(),
+            point: (x, y),
+            ..Node::default()
+        });
+        self
+    }
+
+    pub fn link(mut self, id: &str, src: &str, dst: &str, capacity: u64, weight: f64) -> Self {
+        self.links.push(Link {
+            link_id: id.to_string(),
+            source_node: src.to_string(),
+            destination_node: dst.to_string(),
+            capacity,
+            weight,
+            ..Link::default()
+        });
+        self
+    }
+
+    /// Rejects links whose endpoints were not declared instead of creating them.
+    pub fn strict(mut self) -> Self {
+        self.create_missing_nodes = false;
+        self
+    }
+
+    /// Resolves the collected nodes and links. In strict mode every undeclared endpoint is
+    /// reported at once, sorted and deduplicated, in `NetworkError::UnresolvedReferences`.
+    pub fn build(self) -> Result<Network, NetworkError> {
+        let mut network = Network::new();
+        for node in self.nodes {
+            network.add_node(node);
+        }
+
+        let mut unresolved: Vec<String> = Vec::new();
+        for link in &self.links {
+            for id in [&link.source_node, &link.destination_node] {
+                if network.node_indices.contains_key(id) {
+                    continue;
+                }
+                if self.create_missing_nodes {
+                    network.add_node(Node {
+                        id: id.clone(),
+                        point: (0.0, 0.0),
+                        ..Node::default()
+                    });
+                } else {
+                    unresolved.push(id.clone());
+                }
+            }
+        }
+        if !unresolved.is_empty() {
+            unresolved.sort();
+            unresolved.dedup();
+            return Err(NetworkError::UnresolvedReferences(unresolved));
+        }
+
+        for link in self.links {
+            network.add_link(link)?;
+        }
+        Ok(network)
+    }
+}
+
+/// One Louvain phase: starting from singleton communities, moves nodes one at a time into
+/// the neighbouring community with the largest positive modularity gain until a full pass
+/// moves nothing. Returns each node's community and whether any node moved at all.
+fn louvain_local_moves(adjacency: &[HashMap<usize, f64>]) -> (Vec<usize>, bool) {
+    let degree: Vec<f64> = adjacency
+        .iter()
+        .enumerate()
+        .map(|(i, neighbors)| {
+            neighbors
+                .iter()
+                .map(|(&j, &weight)| if i == j { 2.0 * weight } else { weight })
+                .sum()
+        })
+        .collect();
+    let total_weight: f64 = degree.iter().sum::<f64>() / 2.0;
+
+    let mut community: Vec<usize> = (0..adjacency.len()).collect();
+    if total_weight == 0.0 {
+        return (community, false);
+    }
+    let mut community_degree = degree.clone();
+
+    let mut moved = false;
+    loop {
+        let mut improved = false;
+        for node in 0..adjacency.len() {
+            let current = community[node];
+            community_degree[current] -= degree[node];
+
+            let mut links_to: HashMap<usize, f64> = HashMap::new();
+            for (&neighbor, &weight) in &adjacency[node] {
+                if neighbor != node {
+                    *links_to.entry(community[neighbor]).or_default() += weight;
+                }
+            }
+
+            let gain = |target: usize| {
+                links_to.get(&target).copied().unwrap_or(0.0)
+                    - community_degree[target] * degree[node] / (2.0 * total_weight)
+            };
+            let mut best = current;
+            let mut best_gain = gain(current);
+            let mut candidates: Vec<usize> = links_to.keys().copied().collect();
+            candidates.sort();
+            for target in candidates {
+                let target_gain = gain(target);
+                if target_gain > best_gain + 1e-12 {
+                    best = target;
+                    best_gain = target_gain;
+                }
+            }
+
+            community_degree[best] += degree[node];
+            if best != current {
+                community[node] = best;
+                improved = true;
+                moved = true;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    (community, moved)
+}
+
+/// Relabels arbitrary ids to `0..count` in order of first appearance.
+fn renumber(ids: &[usize]) -> (Vec<usize>, usize) {
+    let mut labels: HashMap<usize, usize> = HashMap::new();
+    let renumbered = ids
+        .iter()
+        .map(|id| {
+            let next = labels.len();
+            *labels.entry(*id).or_insert(next)
+        })
+        .collect();
+    (renumbered, labels.len())
+}
+
+pub fn load_network_links(csv_path: &str) -> Result<Vec<Link>, NetworkError> {
+    read_network_links(csv::Reader::from_path(csv_path)?)
+}
+
+/// Links of a links CSV. Besides the link's own columns, `source_<key>` and
+/// `destination_<key>` columns give attributes of its endpoints, which end up in their
+/// metadata once the link is added; empty cells are left out.
+pub fn read_network_links<R: Read>(mut rdr: csv::Reader<R>) -> Result<Vec<Link>, NetworkError> {
+    let mut network_links: Vec<Link> = Vec::new();
+    let headers = rdr.headers()?.clone();
+
+    for record in rdr.records() {
+        let record = record?;
+        let mut loaded_link: Link = record.deserialize(Some(&headers))?;
+        for (header, value) in headers.iter().zip(record.iter()) {
+            let (header, value) = (header.trim(), value.trim());
+            let (key, metadata) = if let Some(key) = header.strip_prefix("source_") {
+                (key, &mut loaded_link.endpoint_metadata.0)
+            } else if let Some(key) = header.strip_prefix("destination_") {
+                (key, &mut loaded_link.endpoint_metadata.1)
+            } else {
+                continue;
+            };
+            if key != "node" && !value.is_empty() {
+                metadata.insert(key.to_string(), value.to_string());
+            }
+        }
+        // Stray whitespace would otherwise make "Node1 " and "Node1" different nodes
+        for id in [
+            &mut loaded_link.link_id,
+            &mut loaded_link.source_node,
+            &mut loaded_link.destination_node,
+        ] {
+            *id = id.trim().to_string();
+        }
+        network_links.push(loaded_link);
+    }
+
+    Ok(network_links)
+}
+
+fn parse_gexf(xml: &str) -> Result<Network, NetworkError> {
+    let mut builder = NetworkBuilder::new().strict();
+    // A node whose children, such as its position, are still being read
+    let mut open_node: Option<(String, f64, f64)> = None;
+    let mut edge_count = 0;
+
+    for tag in xml_tags(xml)? {
+        // Ignore the namespace prefix, as in `viz:position`
+        let name = tag.name.rsplit(':').next().unwrap_or(tag.name);
+        match (name, tag.closing) {
+            ("node", false) => {
+                let id = tag.required("id")?.to_string();
+                if tag.empty {
+                    builder = builder.node(&id, 0.0, 0.0);
+                } else {
+                    open_node = Some((id, 0.0, 0.0));
+                }
+            }
+            ("node", true) => {
+                if let Some((id, x, y)) = open_node.take() {
+                    builder = builder.node(&id, x, y);
+                }
+            }
+            ("position", false) => {
+                if let Some((_, x, y)) = &mut open_node {
+                    *x = tag.number("x", 0.0)?;
+                    *y = -tag.number("y", 0.0)?;
+                }
+            }
+            ("edge", false) => {
+                edge_count += 1;
+                let id = match tag.attributes.get("id") {
+                    Some(id) => id.clone(),
+                    None => edge_count.to_string(),
+                };
+                let weight = tag.number("weight", 1.0)?;
+                if !is_valid_weight(weight) {
+                    return Err(NetworkError::Xml(format!(
+                        "edge '{}' has invalid weight {}",
+                        id, weight
+                    )));
+                }
+                builder = builder.link(
+                    &id,
+                    tag.required("source")?,
+                    tag.required("target")?,
+                    DEFAULT_LINK_CAPACITY,
+                    weight,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    builder.build()
+}
+
+/// A start, end or empty-element tag of an XML document.
+#[derive(Debug)]
+struct XmlTag<'a> {
+    name: &'a str,
+    /// Attribute values with entities decoded.
+    attributes: HashMap<&'a str, String>,
+    /// An end tag, `</name>`.
+    closing: bool,
+    /// An empty-element tag, `<name/>`, which has no end tag.
+    empty: bool,
+}
+
+impl XmlTag<'_> {
+    fn required(&self, attribute: &str) -> Result<&str, NetworkError> {
+        self.attributes
+            .get(attribute)
+            .map(String::as_str)
+            .ok_or_else(|| NetworkError::Xml(format!("<{}> without {}", self.name, attribute)))
+    }
+
+    /// The numeric `attribute`, or `default` when the tag does not have it.
+    fn number(&self, attribute: &str, default: f64) -> Result<f64, NetworkError> {
+        match self.attributes.get(attribute) {
+            Some(value) => value.trim().parse().map_err(|_| {
+                NetworkError::Xml(format!(
+                    "invalid {} '{}' in <{}>",
+                    attribute, value, self.name
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Tags of `xml` in document order. Text, comments, processing instructions and
+/// declarations are skipped, which is all formats made of attributes like GEXF need.
+fn xml_tags(xml: &str) -> Result<Vec<XmlTag<'_>>, NetworkError> {
+    let mut tags = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment
+                .find("-->")
+                .ok_or_else(|| NetworkError::Xml("unterminated comment".to_string()))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+
+        // Attribute values may contain '>', so only an unquoted one ends the tag
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| match quote {
+                Some(open) if c == open => {
+                    quote = None;
+                    false
+                }
+                Some(_) => false,
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    false
+                }
+                None => c == '>',
+            })
+            .map(|(end, _)| end)
+            .ok_or_else(|| NetworkError::Xml("unterminated tag".to_string()))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if !tag.starts_with(['?', '!']) {
+            tags.push(parse_xml_tag(tag)?);
+        }
+    }
+    Ok(tags)
+}
+
+/// Parses the inside of a tag, without its angle brackets.
+fn parse_xml_tag(tag: &str) -> Result<XmlTag<'_>, NetworkError> {
+    let (closing, tag) = match tag.strip_prefix('/') {
+        Some(tag) => (true, tag),
+        None => (false, tag),
+    };
+    let (empty, tag) = match tag.strip_suffix('/') {
+        Some(tag) => (true, tag),
+        None => (false, tag),
+    };
+    let tag = tag.trim();
+    let (name, mut rest) = tag.split_at(tag.find(char::is_whitespace).unwrap_or(tag.len()));
+
+    let malformed = || NetworkError::Xml(format!("malformed attributes in <{}>", name));
+    let mut attributes = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let (attribute, value) = rest.split_once('=').ok_or_else(malformed)?;
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(malformed)?;
+        let end = value[1..].find(quote).ok_or_else(malformed)? + 1;
+        attributes.insert(attribute.trim(), decode_xml_entities(&value[1..end]));
+        rest = &value[end + 1..];
+    }
+
+    Ok(XmlTag {
+        name,
+        attributes,
+        closing,
+        empty,
+    })
+}
+
+/// Replaces XML's predefined entities, `&amp;` last so `&amp;lt;` stays `&lt;`.
+fn decode_xml_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Reads a whitespace-separated edge list: one `<src> <dst> [<capacity> <weight>]` link per
+/// line, e.g. `A B 100 10` or just `A B`. Capacity defaults to `DEFAULT_LINK_CAPACITY` and
+/// may carry a unit like `1Gbps`; weight defaults to 1. Links are numbered in file order.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn load_edge_list(path: &str) -> Result<Vec<Link>, NetworkError> {
+    let text = std::fs::read_to_string(path)?;
+    let mut links = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid =
+            |reason: String| NetworkError::Parse(format!("line {}: {}", number + 1, reason));
+        let (source, destination, capacity, weight) =
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [source, destination] => (source, destination, DEFAULT_LINK_CAPACITY, 1.0),
+                [source, destination, capacity, weight] => (
+                    source,
+                    destination,
+                    parse_capacity(capacity).map_err(invalid)?,
+                    parse_weight(weight).map_err(invalid)?,
+                ),
+                _ => {
+                    return Err(invalid(format!(
+                        "expected '<src> <dst>' or '<src> <dst> <capacity> <weight>', got '{}'",
+                        line
+                    )));
+                }
+            };
+        links.push(Link {
+            link_id: (links.len() + 1).to_string(),
+            source_node: source.to_string(),
+            destination_node: destination.to_string(),
+            capacity,
+            weight,
+            ..Link::default()
+        });
+    }
+    Ok(links)
+}
+
+/// Loads every link CSV in `paths` and merges them into one network, in order, so
+/// topology split across several files comes together. See [`Network::merge`].
+pub fn load_networks(paths: &[&str]) -> Result<Network, NetworkError> {
+    let mut network = Network::new();
+    for path in paths {
+        let mut region = Network::new();
+        for link in load_network_links(path)? {
+            for id in [&link.source_node, &link.destination_node] {
+                region.add_node(Node {
+                    id: id.clone(),
+                    point: (0.0, 0.0),
+                    ..Node::default()
+                });
+            }
+            region.add_link(link)?;
+        }
+        network.merge(&region);
+    }
+    Ok(network)
+}
+
+/// Loads a network from an adjacency-matrix CSV whose first row and first column hold the
+/// node ids (in the same order) and whose cells hold link weights, with empty or zero cells
+/// meaning "no link". The graph is undirected, so each pair of mirrored cells gives one link:
+/// a warning is logged when they disagree, the cell above the diagonal wins when both are
+/// set, and a link given only below the diagonal is kept.
+pub fn load_adjacency_matrix(path: &str) -> Result<Network, NetworkError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(path)?;
+    let mut records = rdr.records();
+
+    let header = records
+        .next()
+        .ok_or_else(|| NetworkError::Parse("adjacency matrix is empty".to_string()))??;
+    let node_ids: Vec<String> = header
+        .iter()
+        .skip(1)
+        .map(|id| id.trim().to_string())
+        .collect();
+
+    let mut weights: Vec<Vec<f64>> = Vec::new();
+    for record in records {
+        let record = record?;
+        let row = weights.len();
+        let row_id = record.get(0).unwrap_or_default().trim();
+        if node_ids.get(row).map(String::as_str) != Some(row_id) {
+            return Err(NetworkError::Parse(format!(
+                "row {} is labelled '{}' but the matching column is '{}'",
+                row + 1,
+                row_id,
+                node_ids.get(row).map(String::as_str).unwrap_or_default()
+            )));
+        }
+
+        let cells: Vec<&str> = record.iter().skip(1).map(str::trim).collect();
+        if cells.len() != node_ids.len() {
+            return Err(NetworkError::Parse(format!(
+                "row '{}' has {} cells, expected {}",
+                row_id,
+                cells.len(),
+                node_ids.len()
+            )));
+        }
+
+        let row_weights = cells
+            .into_iter()
+            .map(|cell| {
+                if cell.is_empty() {
+                    Ok(0.0)
+                } else {
+                    parse_weight(cell).map_err(|_| {
+                        NetworkError::Parse(format!(
+                            "invalid weight '{}' in row '{}'",
+                            cell, row_id
+                        ))
+                    })
+                }
+            })
+            .collect::<Result<Vec<f64>, NetworkError>>()?;
+        weights.push(row_weights);
+    }
+
+    if weights.len() != node_ids.len() {
+        return Err(NetworkError::Parse(format!(
+            "matrix has {} rows but {} columns",
+            weights.len(),
+            node_ids.len()
+        )));
+    }
+
+    let mut network = Network::new();
+    for id in &node_ids {
+        network.add_node(Node {
+            id: id.clone(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        });
+    }
+
+    for i in 0..node_ids.len() {
+        for j in i..node_ids.len() {
+            if weights[i][j] != weights[j][i] {
+                log::warn!(
+                    "Asymmetric adjacency matrix: {}->{} is {} but {}->{} is {}",
+                    node_ids[i],
+                    node_ids[j],
+                    weights[i][j],
+                    node_ids[j],
+                    node_ids[i],
+                    weights[j][i]
+                );
+            }
+            let weight = if weights[i][j] != 0.0 {
+                weights[i][j]
+            } else {
+                weights[j][i]
+            };
+            if weight == 0.0 {
+                continue;
+            }
+            network.add_link(Link {
+                link_id: format!("{}-{}", node_ids[i], node_ids[j]),
+                source_node: node_ids[i].clone(),
+                destination_node: node_ids[j].clone(),
+                capacity: DEFAULT_LINK_CAPACITY,
+                weight,
+                ..Link::default()
+            })?;
+        }
+    }
+
+    Ok(network)
+}
+
+pub fn save_positions(path: &str, positions: &[(String, f64, f64)]) -> Result<(), NetworkError> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["id", "x", "y"])?;
+    for position in positions {
+        wtr.serialize(position)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes the routing table of every node of `network` as a CSV with the columns
+/// `node,destination,next_hop,cost`, see [`Network::routing_tables`].
+pub fn save_routing_tables(path: &str, network: &Network) -> Result<(), NetworkError> {
+    let tables = network.routing_tables();
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["node", "destination", "next_hop", "cost"])?;
+    for node in network.graph.node_indices() {
+        for &(destination, next_hop, cost) in &tables[&node] {
+            wtr.serialize((
+                &network.graph[node].id,
+                &network.graph[destination].id,
+                &network.graph[next_hop].id,
+                cost,
+            ))?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes `links` as a CSV in the format [`load_network_links`] reads.
+pub fn save_links(path: &str, links: &[Link]) -> Result<(), NetworkError> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    for link in links {
+        wtr.serialize(link)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn load_positions(path: &str) -> Result<Vec<(String, f64, f64)>, NetworkError> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut positions = Vec::new();
+
+    for record in rdr.deserialize() {
+        let position: (String, f64, f64) = record?;
+        positions.push(position);
+    }
+
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn network_load_successful() {
+        let csv_content = "link_id,source_node,destination_node,capacity,weight\n\
+                           link_A,Node1,Node2,100,10\n\
+                           link_B,Node2,Node3,50,5\n\
+                           link_C,Node1,Node3,75,8\n";
+
+        let path = "test_configuration/test-network.csv";
+        std::fs::create_dir_all("test_configuration")
+            .expect("Failed to create test configuration directory");
+        std::fs::write(path, csv_content).expect("Failed to write dummy CSV");
+
+        let mut rdr = csv::Reader::from_path(path).unwrap();
+        let mut network_links: Vec<Link> = Vec::new();
+
+        for network in rdr.deserialize() {
+            let loaded_link: Link = network.unwrap();
+            network_links.push(loaded_link);
+        }
+
+        let mut network = Network::new();
+
+        // First, add nodes
+        for link in &network_links {
+            network.add_node(Node {
+                id: link.source_node.clone(),
+                point: (0.0, 0.0),
+                ..Node::default()
+            });
+            network.add_node(Node {
+                id: link.destination_node.clone(),
+                point: (0.0, 0.0),
+                ..Node::default()
+            });
+        }
+
+        // Then, add links
+        for link in network_links {
+            network.add_link(link).expect("Failed to add link");
+        }
+
+        assert_eq!(network.graph.node_count(), 3); // Node1, Node2, Node3
+        assert_eq!(network.graph.edge_count(), 3); // link_A, link_B, link_C
+
+        // Verify specific nodes and edges exist
+        let node1_idx = network.node_indices.get("Node1").expect("Node1 not found");
+        let node2_idx = network.node_indices.get("Node2").expect("Node2 not found");
+        let node3_idx = network.node_indices.get("Node3").expect("Node3 not found");
+
+        assert_eq!(network.graph[*node1_idx].id, "Node1");
+        assert_eq!(network.graph[*node2_idx].id, "Node2");
+        assert_eq!(network.graph[*node3_idx].id, "Node3");
+
+        // Check if an edge exists from Node1 to Node2
+        let edge_ref = network.graph.find_edge(*node1_idx, *node2_idx);
+        assert!(edge_ref.is_some());
+        let edge_weight = network.graph.edge_weight(edge_ref.unwrap()).unwrap();
+        assert_eq!(edge_weight.capacity, 100);
+        assert_eq!(edge_weight.weight, 10.0);
+
+        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+        std::fs::remove_dir("test_configuration").expect("Failed to remove Test Dir");
+    }
+
+    #[test]
+    fn test_node_addition_deduplication() {
+        let mut network = Network::new();
+        let node1 = Node {
+            id: "A".to_string(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        };
+        let node2 = Node {
+            id: "B".to_string(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        };
+
+        let idx_a1 = network.add_node(node1.clone());
+        let idx_b = network.add_node(node2.clone());
+        let idx_a2 = network.add_node(node1.clone()); // Add the same node again
+
+        assert_eq!(network.graph.node_count(), 2);
+        assert_eq!(network.node_indices.len(), 2);
+        assert_eq!(idx_a1, idx_a2); // Should return the same index for the same node ID
+        assert_ne!(idx_a1, idx_b);
+    }
+
+    #[test]
+    fn test_link_addition_error_handling() {
+        let mut network = Network::new();
+        let node_a = Node {
+            id: "A".to_string(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        };
+        network.add_node(node_a);
+
+        // Try to add a link with a non-existent destination node
+        let invalid_link = Link {
+            link_id: "link_invalid".to_string(),
+            source_node: "A".to_string(),
+            destination_node: "NonExistent".to_string(),
+            capacity: 10,
+            weight: 1.0,
+            ..Link::default()
+        };
+        let result = network.add_link(invalid_link);
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            NetworkError::NodeNotFound(id) => assert_eq!(id, "NonExistent"),
+            _ => panic!("Expected NodeNotFound error"),
+        }
+    }
+
+    #[test]
+    fn test_find_shortest_path() {
+        let mut network = Network::new();
+
+        let node_a = Node {
+            id: "A".to_string(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        };
+        let node_b = Node {
+            id: "B".to_string(),
+            point: (50.0, 0.0),
+            ..Node::default()
+        };
+        let node_c = Node {
+            id: "C".to_string(),
+            point: (100.0, 0.0),
+            ..Node::default()
+        };
+
+        let idx_a = network.add_node(node_a);
+        let idx_b = network.add_node(node_b);
+        let idx_c = network.add_node(node_c);
+
+        let link_ab = Link {
+            link_id: "link_ab".to_string(),
+            source_node: "A".to_string(),
+            destination_node: "B".to_string(),
+            capacity: 100,
+            weight: 4.0,
+            ..Link::default()
+        };
+        let link_bc = Link {
+            link_id: "link_bc".to_string(),
+            source_node: "B".to_string(),
+            destination_node: "C".to_string(),
+            capacity: 50,
+            weight: 3.0,
+            ..Link::default()
+        };
+        let link_ac = Link {
+            link_id: "link_ac".to_string(),
+            source_node: "A".to_string(),
+            destination_node: "C".to_string(),
+            capacity: 75,
+            weight: 8.0,
+            ..Link::default()
+        };
+
+        network.add_link(link_ab).expect("Failed to add link_ab");
+        network.add_link(link_bc).expect("Failed to add link_bc");
+        network.add_link(link_ac).expect("Failed to add link_ac");
+
+        let path = network
+            .find_shortest_path("A", "C")
+            .expect("Failed to find path");
+        let expected_path = vec![(idx_a, idx_b), (idx_b, idx_c)]; // A -> B -> C (weight 4 + 3 = 7)
+        assert_eq!(path, expected_path);
+
+        let result = network.find_shortest_path("A", "D");
+        assert!(matches!(result, Err(NetworkError::NodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_find_shortest_path_fractional_weights() {
+        // Two half-weight hops are cheaper than one direct link of 1.5
+        let network = network_from_edges(&[("A", "B", 0.5), ("B", "C", 0.5), ("A", "C", 1.5)]);
+        let [a, b, c] = ["A", "B", "C"].map(|id| network.node_indices[id]);
+        assert_eq!(
+            network.find_shortest_path("A", "C").unwrap(),
+            vec![(a, b), (b, c)]
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut network = Network::new();
+        assert_eq!(network.bounds(), (0.0, 0.0, 0.0, 0.0));
+
+        for (id, point) in [
+            ("A", (10.0, 40.0)),
+            ("B", (-20.0, 5.0)),
+            ("C", (300.0, 120.0)),
+        ] {
+            network.add_node(Node {
+                id: id.to_string(),
+                point,
+                ..Node::default()
+            });
+        }
+
+        assert_eq!(network.bounds(), (-20.0, 5.0, 300.0, 120.0));
+    }
+
+    #[test]
+    fn test_nodes_in_rect() {
+        let mut network = Network::new();
+        let idx_a = network.add_node(Node {
+            id: "A".to_string(),
+            point: (10.0, 10.0),
+            ..Node::default()
+        });
+        let idx_b = network.add_node(Node {
+            id: "B".to_string(),
+            point: (50.0, 50.0),
+            ..Node::default()
+        });
+        network.add_node(Node {
+            id: "C".to_string(),
+            point: (200.0, 20.0),
+            ..Node::default()
+        });
+
+        let selected = network.nodes_in_rect(0.0, 0.0, 50.0, 60.0);
+        assert_eq!(selected, HashSet::from([idx_a, idx_b]));
+
+        // Corners given in reverse order select the same nodes
+        let selected = network.nodes_in_rect(50.0, 60.0, 0.0, 0.0);
+        assert_eq!(selected, HashSet::from([idx_a, idx_b]));
+
+        assert!(network.nodes_in_rect(100.0, 100.0, 150.0, 150.0).is_empty());
+    }
+
+    #[test]
+    fn test_neighbors_of_star_center() {
+        let mut network = Network::new();
+        let center = network.add_node(Node {
+            id: "Hub".to_string(),
+            point: (0.0, 0.0),
+            ..Node::default()
+        });
+
+        let mut leaves = HashSet::new();
+        for (i, id) in ["A", "B", "C"].into_iter().enumerate() {
+            leaves.insert(network.add_node(Node {
+                id: id.to_string(),
+                point: (0.0, 0.0),
+                ..Node::default()
+            }));
+            // Alternate link direction so the hub appears as both source and destination
+            let (source_node, destination_node) = if i % 2 == 0 {
+                ("Hub".to_string(), id.to_string())
+            } else {
+                (id.to_string(), "Hub".to_string())
+            };
+            network
+                .add_link(Link {
+                    link_id: format!("link_{}", id),
+                    source_node,
+                    destination_node,
+                    capacity: 10,
+                    weight: i as f64 + 1.0,
+                    ..Link::default()
+                })
+                .expect("Failed to add link");
+        }
+
+        let neighbors = network.neighbors(center);
+        assert_eq!(neighbors.len(), 3);
+        let neighbor_set: HashSet<NodeIndex> = neighbors.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(neighbor_set, leaves);
+
+        for (idx, link) in &neighbors {
+            assert_eq!(link.link_id, format!("link_{}", network.graph[*idx].id));
+        }
+
+        let leaf = network.node_indices["B"];
+        let leaf_neighbors = network.neighbors(leaf);
+        assert_eq!(leaf_neighbors.len(), 1);
+        assert_eq!(leaf_neighbors[0].0, center);
+        assert_eq!(leaf_neighbors[0].1.weight, 2.0);
+    }
+
+    #[test]
+    fn test_export_and_apply_positions() {
+        let mut network = Network::new();
+        for (id, point) in [
+            ("A", (12.5, 40.25)),
+            ("B", (-3.0, 7.75)),
+            ("C", (300.0, 0.1)),
+        ] {
+            network.add_node(Node {
+                id: id.to_string(),
+                point,
+                ..Node::default()
+            });
+        }
+
+        let snapshot = network.export_positions();
+        let original: Vec<Node> = network.nodes().cloned().collect();
+
+        for idx in network.graph.node_indices().collect::<Vec<_>>() {
+            network.graph[idx].point = (0.0, 0.0);
+        }
+        network.apply_positions(&snapshot);
+
+        let restored: Vec<Node> = network.nodes().cloned().collect();
+        assert_eq!(restored, original);
+    }
+
+    #[test]
+    fn test_seeded_placement_is_reproducible() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let links = vec![
+            Link {
+                link_id: "1".to_string(),
+                source_node: "A".to_string(),
+                destination_node: "B".to_string(),
+                capacity: 10,
+                weight: 5.0,
+                ..Link::default()
+            },
+            Link {
+                link_id: "2".to_string(),
+                source_node: "B".to_string(),
+                destination_node: "C".to_string(),
+                capacity: 10,
+                weight: 5.0,
+                ..Link::default()
+            },
+        ];
+        let area = (50.0, 50.0, 450.0, 550.0);
+
+        let first =
+            Network::with_random_placement(links.clone(), area, &mut StdRng::seed_from_u64(42))
+                .expect("Failed to place network");
+        let second =
+            Network::with_random_placement(links.clone(), area, &mut StdRng::seed_from_u64(42))
+                .expect("Failed to place network");
+
+        assert_eq!(first.export_positions(), second.export_positions());
+        assert_eq!(first.graph.edge_count(), 2);
+        for node in first.nodes() {
+            assert!((area.0..area.2).contains(&node.point.0));
+            assert!((area.1..area.3).contains(&node.point.1));
+        }
+
+        let other = Network::with_random_placement(links, area, &mut StdRng::seed_from_u64(7))
+            .expect("Failed to place network");
+        assert_ne!(first.export_positions(), other.export_positions());
+    }
+
+    #[test]
+    fn test_placement_and_layout_in_too_small_area() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let links = (0..4)
+            .map(|i| Link {
+                link_id: i.to_string(),
+                source_node: i.to_string(),
+                destination_node: ((i + 1) % 4).to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .collect::<Vec<_>>();
+
+        // 40px is too narrow for both margins, so x falls back to the middle
+        let area = inset_area((0.0, 0.0, 40.0, 600.0), 50.0);
+        let mut network =
+            Network::with_random_placement(links, area, &mut StdRng::seed_from_u64(1))
+                .expect("Failed to place network");
+        assert!(network.nodes().all(|node| node.point.0 == 20.0));
+
+        network.apply_force_directed_layout(40.0, 600.0, &LayoutConfig::default(), None);
+        for node in network.nodes() {
+            assert_eq!(node.point.0, 20.0);
+            assert!((50.0..=550.0).contains(&node.point.1), "{:?}", node);
+        }
+
+        network.apply_force_directed_layout(0.0, 0.0, &LayoutConfig::default(), None);
+        assert!(network.nodes().all(|node| node.point == (0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_remove_link() {
+        let mut network = Network::new();
+        for id in ["A", "B", "C"] {
+            network.add_node(Node {
+                id: id.to_string(),
+                point: (0.0, 0.0),
+                ..Node::default()
+            });
+        }
+        for (link_id, source, destination) in [("ab", "A", "B"), ("bc", "B", "C")] {
+            network
+                .add_link(Link {
+                    link_id: link_id.to_string(),
+                    source_node: source.to_string(),
+                    destination_node: destination.to_string(),
+                    capacity: 10,
+                    weight: 1.0,
+                    ..Link::default()
+                })
+                .expect("Failed to add link");
+        }
+
+        let edge = network
+            .graph
+            .find_edge(network.node_indices["A"], network.node_indices["B"])
+            .expect("Missing edge A-B");
+        let removed = network.remove_link(edge).expect("Failed to remove link");
+
+        assert_eq!(removed.link_id, "ab");
+        assert_eq!(network.graph.edge_count(), 1);
+        assert!(network.neighbors(network.node_indices["A"]).is_empty());
+    }
+
+    #[test]
+    fn test_link_by_id() {
+        let csv_content = "link_id,source_node,destination_node,capacity,weight\n\
+                           link_A,Node1,Node2,100,10\n\
+                           link_B,Node2,Node3,50,5\n\
+                           link_C,Node1,Node3,75,8\n";
+
+        let path = std::env::temp_dir().join("net_modeler-link-by-id.csv");
+        std::fs::write(&path, csv_content).expect("Failed to write dummy CSV");
+        let network_links =
+            load_network_links(path.to_str().unwrap()).expect("Failed to load links");
+        std::fs::remove_file(&path).expect("Failed to remove dummy CSV");
+
+        let mut network = Network::new();
         for link in &network_links {
+            for id in [&link.source_node, &link.destination_node] {
+                network.add_node(Node {
+                    id: id.clone(),
+                    point: (0.0, 0.0),
+                    ..Node::default()
+                });
+            }
+        }
+        for link in network_links {
+            network.add_link(link).expect("Failed to add link");
+        }
+
+        let (edge, link) = network.link_by_id("link_B").expect("link_B not found");
+        assert_eq!(link.source_node, "Node2");
+        assert_eq!(link.destination_node, "Node3");
+        assert_eq!(link.capacity, 50);
+        assert!(network.link_by_id("link_Z").is_none());
+
+        // Removing a link keeps the remaining ids pointing at the right edges
+        network.remove_link(edge);
+        assert!(network.link_by_id("link_B").is_none());
+        for id in ["link_A", "link_C"] {
+            let (_, link) = network.link_by_id(id).expect("Link missing after removal");
+            assert_eq!(link.link_id, id);
+        }
+    }
+
+    /// Builds a network from `(source, destination, weight)` triples, creating nodes on demand.
+    fn network_from_edges(edges: &[(&str, &str, f64)]) -> Network {
+        let mut network = Network::new();
+        for (i, (source, destination, weight)) in edges.iter().enumerate() {
+            for id in [source, destination] {
+                network.add_node(Node {
+                    id: id.to_string(),
+                    point: (0.0, 0.0),
+                    ..Node::default()
+                });
+            }
+            network
+                .add_link(Link {
+                    link_id: format!("link{}", i),
+                    source_node: source.to_string(),
+                    destination_node: destination.to_string(),
+                    capacity: 10,
+                    weight: *weight,
+                    ..Link::default()
+                })
+                .expect("Failed to add link");
+        }
+        network
+    }
+
+    #[test]
+    fn test_path_graph_statistics() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+        ]);
+
+        assert_eq!(network.diameter(), Some(4));
+        // Distances 1x4, 2x3, 3x2, 4x1 over 10 pairs
+        assert_eq!(network.average_path_length(), Some(2.0));
+    }
+
+    #[test]
+    fn test_complete_graph_statistics() {
+        let network = network_from_edges(&[
+            ("A", "B", 3.0),
+            ("A", "C", 3.0),
+            ("A", "D", 3.0),
+            ("B", "C", 3.0),
+            ("B", "D", 3.0),
+            ("C", "D", 3.0),
+        ]);
+
+        assert_eq!(network.diameter(), Some(1));
+        assert_eq!(network.average_path_length(), Some(1.0));
+
+        assert_eq!(Network::new().diameter(), None);
+        assert_eq!(Network::new().average_path_length(), None);
+    }
+
+    #[test]
+    fn test_self_loop_is_stored_as_one_edge() {
+        let csv_content = "link_id,source_node,destination_node,capacity,weight\n\
+                           loop,A,A,10,2\n\
+                           link_AB,A,B,10,5\n";
+
+        let path = std::env::temp_dir().join("net_modeler-self-loop.csv");
+        std::fs::write(&path, csv_content).expect("Failed to write dummy CSV");
+        let network_links =
+            load_network_links(path.to_str().unwrap()).expect("Failed to load links");
+        std::fs::remove_file(&path).expect("Failed to remove dummy CSV");
+
+        let network = Network::with_random_placement(
+            network_links,
+            (0.0, 0.0, 100.0, 100.0),
+            &mut rand::rng(),
+        )
+        .expect("Failed to add link");
+
+        assert_eq!(network.graph.node_count(), 2);
+        assert_eq!(network.graph.edge_count(), 2);
+
+        let idx_a = network.node_indices["A"];
+        let loops: Vec<_> = network.graph.edges_connecting(idx_a, idx_a).collect();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].weight().link_id, "loop");
+
+        let (x, y) = network.graph[idx_a].point;
+        assert_eq!(network.find_node_at_point(x, y, NODE_RADIUS), Some(idx_a));
+    }
+
+    #[test]
+    fn test_force_directed_layout_stays_in_bounds() {
+        let mut network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "A", 1.0),
+            ("C", "D", 1.0),
+            ("D", "D", 1.0),
+        ]);
+        // All nodes start coincident, which must not produce NaN positions
+        let pinned = network.node_indices["A"];
+        network.graph[pinned].point = (100.0, 100.0);
+
+        let config = LayoutConfig {
+            iterations: 50,
+            ..LayoutConfig::default()
+        };
+        let run = network.apply_force_directed_layout(550.0, 600.0, &config, Some(pinned));
+
+        assert!(run.max_displacement.is_finite());
+        assert_eq!(network.graph[pinned].point, (100.0, 100.0));
+        for node in network.nodes() {
+            assert!((50.0..=500.0).contains(&node.point.0), "{:?}", node);
+            assert!((50.0..=550.0).contains(&node.point.1), "{:?}", node);
+        }
+    }
+
+    #[test]
+    fn test_load_adjacency_matrix() {
+        let csv_content = ",A,B,C\n\
+                           A,0,5,0\n\
+                           B,5,0,3\n\
+                           C,0,3,0\n";
+
+        let path = std::env::temp_dir().join("net_modeler-adjacency.csv");
+        std::fs::write(&path, csv_content).expect("Failed to write dummy CSV");
+        let network = load_adjacency_matrix(path.to_str().unwrap()).expect("Failed to load matrix");
+        std::fs::remove_file(&path).expect("Failed to remove dummy CSV");
+
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+
+        let (_, link_ab) = network.link_by_id("A-B").expect("A-B not found");
+        assert_eq!(link_ab.weight, 5.0);
+        let (_, link_bc) = network.link_by_id("B-C").expect("B-C not found");
+        assert_eq!(link_bc.weight, 3.0);
+        assert!(
+            network
+                .graph
+                .find_edge(network.node_indices["A"], network.node_indices["C"])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_load_asymmetric_adjacency_matrix() {
+        // A-C is only given below the diagonal, A-B disagrees with its mirror
+        let csv_content = ",A,B,C\n\
+                           A,0,5,0\n\
+                           B,4,0,0\n\
+                           C,2,0,0\n";
+
+        let path = std::env::temp_dir().join("net_modeler-asymmetric-adjacency.csv");
+        std::fs::write(&path, csv_content).expect("Failed to write dummy CSV");
+        let network = load_adjacency_matrix(path.to_str().unwrap()).expect("Failed to load matrix");
+        std::fs::remove_file(&path).expect("Failed to remove dummy CSV");
+
+        assert_eq!(network.graph.edge_count(), 2);
+        assert_eq!(network.link_by_id("A-B").unwrap().1.weight, 5.0);
+        assert_eq!(network.link_by_id("A-C").unwrap().1.weight, 2.0);
+    }
+
+    #[test]
+    fn test_round_positions() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 10.4, 20.6)
+            .node("B", -3.5, 7.5)
+            .node("C", 42.0, 0.49)
+            .link("ab", "A", "B", 10, 1.0)
+            .build()
+            .unwrap();
+
+        network.round_positions();
+
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(
+            network.export_positions(),
+            vec![
+                ("A".to_string(), 10.0, 21.0),
+                ("B".to_string(), -4.0, 8.0),
+                ("C".to_string(), 42.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fit_to_bounds() {
+        let mut network = Network::new();
+        for (id, point) in [
+            ("A", (-400.0, 10.0)),
+            ("B", (0.0, 0.0)),
+            ("C", (1200.0, 90.0)),
+        ] {
             network.add_node(Node {
-                id: link.source_node.clone(),
-                point: (0, 0),
-            });
-            network.add_node(Node {
-                id: link.destination_node.clone(),
-                point: (0, 0),
+                id: id.to_string(),
+                point,
+                ..Node::default()
             });
         }
 
-        // Then, add links
-        for link in network_links {
-            network.add_link(link).expect("Failed to add link");
+        network.fit_to_bounds(550.0, 600.0, 50.0);
+
+        for node in network.nodes() {
+            assert!((50.0..=500.0).contains(&node.point.0), "{:?}", node);
+            assert!((50.0..=550.0).contains(&node.point.1), "{:?}", node);
+        }
+        // The widest axis is stretched to touch both margins
+        let (min_x, _, max_x, _) = network.bounds();
+        assert!((min_x - 50.0).abs() < 1e-9);
+        assert!((max_x - 500.0).abs() < 1e-9);
+
+        let mut single = Network::new();
+        single.add_node(Node {
+            id: "A".to_string(),
+            point: (-10.0, 900.0),
+            ..Node::default()
+        });
+        single.fit_to_bounds(550.0, 600.0, 50.0);
+        assert_eq!(single.graph[NodeIndex::new(0)].point, (275.0, 300.0));
+    }
+
+    #[test]
+    fn test_hit_test_matches_drawn_radius() {
+        let mut network = Network::new();
+        let idx = network.add_node(Node {
+            id: "A".to_string(),
+            point: (100.0, 100.0),
+            ..Node::default()
+        });
+
+        for radius in [NODE_RADIUS, 8.0, 40.0] {
+            // A click on the drawn outline hits, just outside it misses
+            assert_eq!(
+                network.find_node_at_point(100.0 + radius, 100.0, radius),
+                Some(idx)
+            );
+            assert_eq!(
+                network.find_node_at_point(100.0, 100.0 - radius, radius),
+                Some(idx)
+            );
+            assert_eq!(
+                network.find_node_at_point(100.5 + radius, 100.0, radius),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_gexf() {
+        let gexf = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
+              <graph defaultedgetype="undirected">
+                <!-- <node id="ghost"/> -->
+                <nodes>
+                  <node id="a" label="Core &amp; Edge">
+                    <viz:position x="10.5" y="-20" z="0.0"/>
+                  </node>
+                  <node id="b" label="B">
+                    <viz:position x='-3' y='4'/>
+                  </node>
+                  <node id="c"/>
+                </nodes>
+                <edges>
+                  <edge id="e1" source="a" target="b" weight="2.6"/>
+                  <edge source="b" target="c"></edge>
+                </edges>
+              </graph>
+            </gexf>"#;
+
+        let network = parse_gexf(gexf).unwrap();
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+        assert_eq!(network.graph[network.node_indices["a"]].point, (10.5, 20.0));
+        assert_eq!(network.graph[network.node_indices["b"]].point, (-3.0, -4.0));
+        assert_eq!(network.graph[network.node_indices["c"]].point, (0.0, 0.0));
+
+        let (_, link) = network.link_by_id("e1").unwrap();
+        assert_eq!(
+            (link.source_node.as_str(), link.destination_node.as_str()),
+            ("a", "b")
+        );
+        assert_eq!(link.weight, 2.6);
+        assert_eq!(link.capacity, DEFAULT_LINK_CAPACITY);
+        let (_, link) = network.link_by_id("2").unwrap();
+        assert_eq!(
+            (link.source_node.as_str(), link.destination_node.as_str()),
+            ("b", "c")
+        );
+        assert_eq!(link.weight, 1.0);
+
+        assert!(matches!(
+            parse_gexf(r#"<gexf><graph><nodes><node label="x"/></nodes></graph></gexf>"#),
+            Err(NetworkError::Xml(_))
+        ));
+        assert!(matches!(
+            parse_gexf(
+                r#"<gexf><graph><edges><edge source="a" target="z"/></edges></graph></gexf>"#
+            ),
+            Err(NetworkError::UnresolvedReferences(_))
+        ));
+    }
+
+    #[test]
+    fn test_parallel_edge_slots() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "A", 2.0),
+            ("A", "B", 3.0),
+            ("B", "C", 4.0),
+        ]);
+        let slots = network.parallel_edge_slots();
+
+        let (_, single) = network.link_by_id("link3").unwrap();
+        assert_eq!(single.weight, 4.0);
+        assert_eq!(slots[&network.link_by_id("link3").unwrap().0], (0, 1));
+
+        let mut parallel: Vec<(usize, usize)> = ["link0", "link1", "link2"]
+            .iter()
+            .map(|id| slots[&network.link_by_id(id).unwrap().0])
+            .collect();
+        parallel.sort();
+        assert_eq!(parallel, vec![(0, 3), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn test_simplify_parallel_links() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 50.0, 100.0)
+            .link("ab", "A", "B", 10, 7.0)
+            .link("bc", "B", "C", 20, 2.0)
+            .link("ba", "B", "A", 30, 3.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(network.simplify(CombinePolicy::Min), 1);
+
+        assert_eq!(network.graph.edge_count(), 2);
+        assert!(network.link_by_id("ba").is_none());
+        let (_, merged) = network.link_by_id("ab").unwrap();
+        assert_eq!(merged.capacity, 40);
+        assert_eq!(merged.weight, 3.0);
+        let (_, untouched) = network.link_by_id("bc").unwrap();
+        assert_eq!((untouched.capacity, untouched.weight), (20, 2.0));
+
+        // Nothing left to collapse
+        assert_eq!(network.simplify(CombinePolicy::Average), 0);
+    }
+
+    #[test]
+    fn test_duplicate_node_id_is_rejected() {
+        let mut network = Network::new();
+        let original = network.add_node(Node {
+            id: "Router".to_string(),
+            point: (10.0, 20.0),
+            ..Node::default()
+        });
+
+        let duplicate = network.add_node(Node {
+            id: "Router".to_string(),
+            point: (300.0, 400.0),
+            ..Node::default()
+        });
+
+        assert_eq!(duplicate, original);
+        assert_eq!(network.graph.node_count(), 1);
+        // The existing node keeps its position
+        assert_eq!(network.graph[original].point, (10.0, 20.0));
+    }
+
+    #[test]
+    fn test_pagerank_star_graph() {
+        let network = network_from_edges(&[
+            ("Hub", "A", 1.0),
+            ("Hub", "B", 1.0),
+            ("C", "Hub", 1.0),
+            ("Hub", "D", 1.0),
+        ]);
+        let ranks = network.pagerank(0.85, 50);
+
+        let total: f64 = ranks.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let hub = network.node_indices["Hub"];
+        for (idx, rank) in &ranks {
+            if *idx != hub {
+                assert!(ranks[&hub] > *rank);
+            }
+        }
+
+        assert!(Network::new().pagerank(0.85, 50).is_empty());
+    }
+
+    #[test]
+    fn test_from_d3_json() {
+        let json = r#"{
+            "nodes": [{"id": "A"}, {"id": "B"}, {"id": 3}],
+            "links": [
+                {"source": "A", "target": "B", "value": 4},
+                {"source": "B", "target": 3}
+            ]
+        }"#;
+
+        let network = Network::from_d3_json(json).unwrap();
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+
+        let (edge, link) = network.link_by_id("1").unwrap();
+        assert_eq!(link.source_node, "A");
+        assert_eq!(link.destination_node, "B");
+        assert_eq!(link.weight, 4.0);
+        assert_eq!(link.capacity, DEFAULT_LINK_CAPACITY);
+        let (a, b) = network.graph.edge_endpoints(edge).unwrap();
+        assert_eq!(network.graph[a].id, "A");
+        assert_eq!(network.graph[b].id, "B");
+
+        let (_, link) = network.link_by_id("2").unwrap();
+        assert_eq!(link.destination_node, "3");
+        assert_eq!(link.weight, 1.0);
+
+        let missing = r#"{"nodes": [{"id": "A"}], "links": [{"source": "A", "target": "Z"}]}"#;
+        assert!(matches!(
+            Network::from_d3_json(missing),
+            Err(NetworkError::NodeNotFound(id)) if id == "Z"
+        ));
+        assert!(matches!(
+            Network::from_d3_json("not json"),
+            Err(NetworkError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_tree_layout_balanced_binary_tree() {
+        let mut network = network_from_edges(&[
+            ("1", "2", 1.0),
+            ("1", "3", 1.0),
+            ("2", "4", 1.0),
+            ("2", "5", 1.0),
+            ("3", "6", 1.0),
+            ("3", "7", 1.0),
+        ]);
+        let root = network.node_indices["1"];
+        network.apply_tree_layout(root, 400.0, 80.0);
+
+        let point = |id: &str| network.graph[network.node_indices[id]].point;
+        assert_eq!(point("1"), (200.0, 80.0));
+        assert_eq!(point("2"), (100.0, 160.0));
+        assert_eq!(point("3"), (300.0, 160.0));
+        for (id, x) in [("4", 50.0), ("5", 150.0), ("6", 250.0), ("7", 350.0)] {
+            assert_eq!(point(id), (x, 240.0));
+        }
+    }
+
+    #[test]
+    fn test_builder_out_of_order() {
+        let network = NetworkBuilder::new()
+            .link("l1", "A", "B", 10, 2.0)
+            .link("l2", "B", "C", 20, 3.0)
+            .node("B", 5.0, 6.0)
+            .node("A", 1.0, 2.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+        assert_eq!(network.graph[network.node_indices["A"]].point, (1.0, 2.0));
+        assert_eq!(network.graph[network.node_indices["B"]].point, (5.0, 6.0));
+        // C was never declared, so it is created at the default position
+        assert_eq!(network.graph[network.node_indices["C"]].point, (0.0, 0.0));
+
+        let (_, link) = network.link_by_id("l2").unwrap();
+        assert_eq!((link.capacity, link.weight), (20, 3.0));
+    }
+
+    #[test]
+    fn test_builder_dangling_reference() {
+        let result = NetworkBuilder::new()
+            .strict()
+            .node("A", 0.0, 0.0)
+            .link("l1", "A", "Z", 10, 1.0)
+            .link("l2", "Y", "Z", 10, 1.0)
+            .build();
+
+        match result {
+            Err(NetworkError::UnresolvedReferences(ids)) => assert_eq!(ids, ["Y", "Z"]),
+            other => panic!("expected unresolved references, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_articulation_points_path() {
+        let network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "D", 1.0)]);
+        let points = network.articulation_points();
+        assert_eq!(
+            points,
+            vec![network.node_indices["B"], network.node_indices["C"]]
+        );
+    }
+
+    #[test]
+    fn test_articulation_points_cycle() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "A", 1.0),
+        ]);
+        assert!(network.articulation_points().is_empty());
+    }
+
+    #[test]
+    fn test_bridges_between_triangles() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "A", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+            ("E", "F", 1.0),
+            ("F", "D", 1.0),
+        ]);
+        let (bridge, _) = network.link_by_id("link3").unwrap();
+        assert_eq!(network.bridges(), vec![bridge]);
+    }
+
+    #[test]
+    fn test_path_latency() {
+        let mut network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("B", "C", 1.0),
+        ]);
+        for (id, latency) in [
+            ("link0", 2.5),
+            ("link1", 10.0),
+            ("link2", 4.0),
+            ("link3", 7.5),
+        ] {
+            let (edge, _) = network.link_by_id(id).unwrap();
+            network.graph[edge].latency_ms = latency;
+        }
+
+        let path: Vec<NodeIndex> = ["A", "B", "C", "D"]
+            .iter()
+            .map(|id| network.node_indices[*id])
+            .collect();
+        // The faster of the two parallel B-C links is used
+        assert_eq!(network.path_latency(&path), 2.5 + 7.5 + 4.0);
+        assert_eq!(network.path_latency(&path[..1]), 0.0);
+
+        let gap = [network.node_indices["A"], network.node_indices["D"]];
+        assert!(network.path_latency(&gap).is_infinite());
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        // Two routes from A to D: via B costs 1 + 2, via C costs 4 + 5
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "D", 2.0),
+            ("A", "C", 4.0),
+            ("C", "D", 5.0),
+        ]);
+        let idx = |id: &str| network.node_indices[id];
+
+        let paths = network.k_shortest_paths(idx("A"), idx("D"), 5);
+        assert_eq!(
+            paths,
+            vec![
+                (vec![idx("A"), idx("B"), idx("D")], 3.0),
+                (vec![idx("A"), idx("C"), idx("D")], 9.0),
+            ]
+        );
+
+        assert_eq!(network.k_shortest_paths(idx("A"), idx("D"), 1).len(), 1);
+        assert!(network.k_shortest_paths(idx("A"), idx("D"), 0).is_empty());
+    }
+
+    #[test]
+    fn test_geographic_layout() {
+        let mut network = network_from_edges(&[("London", "Paris", 1.0), ("Paris", "Berlin", 1.0)]);
+        network.apply_coordinates(&[
+            ("London".to_string(), 51.51, -0.13),
+            ("Paris".to_string(), 48.86, 2.35),
+            ("Berlin".to_string(), 52.52, 13.40),
+            ("Nowhere".to_string(), 0.0, 0.0),
+        ]);
+        assert!(network.has_coordinates());
+        network.apply_geographic_layout(600.0, 400.0);
+
+        let point = |id: &str| network.graph[network.node_indices[id]].point;
+        let (london, paris, berlin) = (point("London"), point("Paris"), point("Berlin"));
+        // West to east along x, and north is up
+        assert!(london.0 < paris.0 && paris.0 < berlin.0);
+        assert!(berlin.1 < london.1 && london.1 < paris.1);
+        // Longitude spans the full width between the margins
+        assert!((london.0 - 50.0).abs() < 1e-9);
+        assert!((berlin.0 - 550.0).abs() < 1e-9);
+        // Equal scale on both axes keeps the projection undistorted
+        let scale = (berlin.0 - london.0) / (13.40 - -0.13);
+        assert!(((paris.1 - berlin.1) - (52.52 - 48.86) * scale).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_remove_node_drops_its_links() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 2.0), ("C", "D", 3.0)]);
+        let removed = network.remove_node(network.node_indices["B"]).unwrap();
+        assert_eq!(removed.id, "B");
+
+        assert_eq!(network.graph.node_count(), 3);
+        assert!(!network.node_indices.contains_key("B"));
+        assert!(network.link_by_id("link0").is_none());
+        assert!(network.link_by_id("link1").is_none());
+
+        // Lookups still resolve to the right node and link after petgraph's swap-remove
+        for id in ["A", "C", "D"] {
+            assert_eq!(network.graph[network.node_indices[id]].id, id);
+        }
+        let (_, link) = network.link_by_id("link2").unwrap();
+        assert_eq!((link.source_node.as_str(), link.weight), ("C", 3.0));
+    }
+
+    #[test]
+    fn test_louvain_two_clusters() {
+        let mut edges = Vec::new();
+        for cluster in [["A", "B", "C", "D"], ["E", "F", "G", "H"]] {
+            for i in 0..cluster.len() {
+                for j in i + 1..cluster.len() {
+                    edges.push((cluster[i], cluster[j], 1.0));
+                }
+            }
+        }
+        edges.push(("D", "E", 1.0));
+        let network = network_from_edges(&edges);
+
+        let communities = network.louvain_communities();
+        let community = |id: &str| communities[&network.node_indices[id]];
+        let distinct: HashSet<usize> = communities.values().copied().collect();
+        assert_eq!(distinct.len(), 2);
+        for id in ["B", "C", "D"] {
+            assert_eq!(community(id), community("A"));
+        }
+        for id in ["F", "G", "H"] {
+            assert_eq!(community(id), community("E"));
+        }
+        assert_ne!(community("A"), community("E"));
+    }
+
+    #[test]
+    fn test_degree_assortativity() {
+        let star = network_from_edges(&[("hub", "A", 1.0), ("hub", "B", 1.0), ("hub", "C", 1.0)]);
+        assert!((star.degree_assortativity().unwrap() + 1.0).abs() < 1e-9);
+
+        let path = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "D", 1.0)]);
+        assert!((path.degree_assortativity().unwrap() + 0.5).abs() < 1e-9);
+
+        // Every node of a cycle has the same degree, so there is nothing to correlate
+        let cycle = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "A", 1.0)]);
+        assert_eq!(cycle.degree_assortativity(), None);
+        assert_eq!(
+            network_from_edges(&[("A", "B", 1.0)]).degree_assortativity(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_edge_at_point() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0)]);
+        network.apply_positions(&[
+            ("A".to_string(), 0.0, 0.0),
+            ("B".to_string(), 100.0, 0.0),
+            ("C".to_string(), 100.0, 100.0),
+        ]);
+        let (ab, _) = network.link_by_id("link0").unwrap();
+        let (bc, _) = network.link_by_id("link1").unwrap();
+
+        let straight = HashMap::new();
+        assert_eq!(
+            network.find_edge_at_point(50.0, 3.0, 5.0, &straight),
+            Some(ab)
+        );
+        assert_eq!(
+            network.find_edge_at_point(103.0, 60.0, 5.0, &straight),
+            Some(bc)
+        );
+        assert_eq!(network.find_edge_at_point(50.0, 30.0, 5.0, &straight), None);
+        assert_eq!(network.find_edge_at_point(150.0, 0.0, 5.0, &straight), None);
+
+        // A bundled link is hit along its curve rather than its straight line
+        let bundled = HashMap::from([(ab, vec![(0.0, 0.0), (50.0, 30.0), (100.0, 0.0)])]);
+        assert_eq!(
+            network.find_edge_at_point(50.0, 30.0, 5.0, &bundled),
+            Some(ab)
+        );
+        assert_eq!(network.find_edge_at_point(50.0, 3.0, 5.0, &bundled), None);
+    }
+
+    #[test]
+    fn test_force_layout_light_links_are_shorter() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("C", "D", 10.0)]);
+        network.apply_positions(&[
+            ("A".to_string(), 250.0, 250.0),
+            ("B".to_string(), 350.0, 250.0),
+            ("C".to_string(), 250.0, 350.0),
+            ("D".to_string(), 350.0, 350.0),
+        ]);
+        let config = LayoutConfig {
+            iterations: 200,
+            ..LayoutConfig::default()
+        };
+        network.apply_force_directed_layout(600.0, 600.0, &config, None);
+
+        let distance = |a: &str, b: &str| {
+            let (ax, ay) = network.graph[network.node_indices[a]].point;
+            let (bx, by) = network.graph[network.node_indices[b]].point;
+            ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt()
+        };
+        assert!(distance("A", "B") < distance("C", "D"));
+    }
+
+    #[test]
+    fn test_ego_subgraph_radius_one() {
+        let network = network_from_edges(&[
+            ("hub", "A", 1.0),
+            ("hub", "B", 1.0),
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+        ]);
+        let ego = network.ego_subgraph(network.node_indices["hub"], 1);
+
+        let mut ids: Vec<&str> = ego.nodes().map(|node| node.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["A", "B", "hub"]);
+        // Links among the neighbours are kept, links leaving the neighbourhood are not
+        assert_eq!(ego.graph.edge_count(), 3);
+        assert!(ego.link_by_id("link2").is_some());
+        assert!(ego.link_by_id("link3").is_none());
+
+        let wider = network.ego_subgraph(network.node_indices["hub"], 2);
+        assert_eq!(wider.graph.node_count(), 4);
+    }
+
+    #[test]
+    fn test_write_back_isolated_edits() {
+        let mut network = NetworkBuilder::new()
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 10, 1.0)
+            .link("cd", "C", "D", 10, 1.0)
+            .build()
+            .unwrap();
+        let mut part = network.ego_subgraph(network.node_indices["B"], 1);
+        let region: HashSet<String> = part.nodes().map(|node| node.id.clone()).collect();
+
+        let b = part.node_indices["B"];
+        part.graph[b].point = (40.0, 50.0);
+        part.remove_node(part.node_indices["A"]);
+        part.set_link_weight(part.link_by_id("bc").unwrap().0, 7.0);
+        part.add_node(Node {
+            id: "E".to_string(),
+            ..Node::default()
+        });
+        part.add_link(Link {
+            link_id: "cd".to_string(),
+            source_node: "C".to_string(),
+            destination_node: "E".to_string(),
+            capacity: 10,
+            weight: 1.0,
+            ..Link::default()
+        })
+        .unwrap();
+
+        network.write_back(&region, &part);
+
+        let mut ids: Vec<&str> = network.nodes().map(|node| node.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["B", "C", "D", "E"]);
+        assert_eq!(network.graph[network.node_indices["B"]].point, (40.0, 50.0));
+        assert_eq!(network.link_by_id("bc").unwrap().1.weight, 7.0);
+        // The link leaving the region is kept, so the new one with its id is renamed
+        assert_eq!(network.link_by_id("cd").unwrap().1.destination_node, "D");
+        assert_eq!(network.link_by_id("cd#2").unwrap().1.destination_node, "E");
+        assert_eq!(network.graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_capacity_stats() {
+        let network = NetworkBuilder::new()
+            .link("small", "A", "B", 50, 3.0)
+            .link("big", "B", "C", 250, 200.0)
+            .link("tie", "C", "A", 250, 100.0)
+            .build()
+            .unwrap();
+
+        let stats = network.capacity_stats();
+        assert_eq!(stats.total_capacity, 550);
+        assert_eq!(stats.total_weight, 303.0);
+        assert!((stats.average_capacity - 550.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.max_capacity_link.as_deref(), Some("big"));
+
+        let empty = Network::new().capacity_stats();
+        assert_eq!(empty.total_capacity, 0);
+        assert_eq!(empty.average_capacity, 0.0);
+        assert_eq!(empty.max_capacity_link, None);
+    }
+
+    #[test]
+    fn test_merge_shares_node_ids() {
+        let mut west = NetworkBuilder::new()
+            .node("A", 10.0, 10.0)
+            .node("Hub", 100.0, 100.0)
+            .link("1", "A", "Hub", 10, 1.0)
+            .build()
+            .unwrap();
+        let east = NetworkBuilder::new()
+            .node("Hub", 500.0, 500.0)
+            .node("B", 600.0, 600.0)
+            .link("1", "Hub", "B", 20, 2.0)
+            .link("2", "A", "Hub", 10, 1.0)
+            .build()
+            .unwrap();
+        west.merge(&east);
+
+        assert_eq!(west.graph.node_count(), 3);
+        // The shared node keeps its original position
+        assert_eq!(west.graph[west.node_indices["Hub"]].point, (100.0, 100.0));
+        assert_eq!(west.graph.edge_count(), 3);
+        let (_, renamed) = west.link_by_id("1#2").unwrap();
+        assert_eq!(
+            (renamed.source_node.as_str(), renamed.capacity),
+            ("Hub", 20)
+        );
+        assert!(west.link_by_id("2").is_some());
+
+        // Merging the same network again adds nothing
+        let copy = west.clone();
+        west.merge(&copy);
+        assert_eq!(west.graph.node_count(), 3);
+        assert_eq!(west.graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_load_networks() {
+        let dir = std::env::temp_dir();
+        let first = dir.join("net_modeler-region-1.csv");
+        let second = dir.join("net_modeler-region-2.csv");
+        let header = "link_id,source_node,destination_node,capacity,weight\n";
+        std::fs::write(&first, format!("{}1,A,B,10,1\n", header)).unwrap();
+        std::fs::write(&second, format!("{}1,B,C,10,1\n", header)).unwrap();
+
+        let network = load_networks(&[first.to_str().unwrap(), second.to_str().unwrap()]).unwrap();
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+        assert!(load_networks(&["does-not-exist.csv"]).is_err());
+    }
+
+    #[test]
+    fn test_weight_histogram() {
+        let mut builder = NetworkBuilder::new();
+        for (i, weight) in [1, 2, 3, 5, 8, 9, 10, 10].into_iter().enumerate() {
+            builder = builder.link(
+                &format!("l{}", i),
+                &format!("a{}", i),
+                &format!("b{}", i),
+                10,
+                f64::from(weight),
+            );
+        }
+        let network = builder.build().unwrap();
+
+        // Weights 1 to 10 in five buckets 1.8 wide: [1,2.8) [2.8,4.6) [4.6,6.4) [6.4,8.2) [8.2,10]
+        assert_eq!(network.weight_histogram(5), vec![2, 1, 1, 1, 3]);
+        assert_eq!(network.weight_histogram(1), vec![8]);
+        assert!(network.weight_histogram(0).is_empty());
+        assert_eq!(Network::new().weight_histogram(3), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_reachable_from_excludes_island() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "A", 1.0),
+            ("island1", "island2", 1.0),
+        ]);
+
+        let reached = network.reachable_from(network.node_indices["A"]);
+        let mut ids: Vec<&str> = reached
+            .iter()
+            .map(|&idx| network.graph[idx].id.as_str())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["A", "B", "C"]);
+
+        let island = network.reachable_from(network.node_indices["island2"]);
+        assert_eq!(island.len(), 2);
+        assert!(island.contains(&network.node_indices["island1"]));
+    }
+
+    #[test]
+    fn test_dry_run_load_report() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0)]);
+        let link = |id: &str, source: &str, destination: &str| Link {
+            link_id: id.to_string(),
+            source_node: source.to_string(),
+            destination_node: destination.to_string(),
+            capacity: 10,
+            weight: 1.0,
+            ..Link::default()
+        };
+        let batch = [
+            // Existing nodes, new link
+            link("ac", "A", "C"),
+            // Already in the network
+            link("link0", "A", "B"),
+            // One unknown endpoint, twice over
+            link("cd", "C", "D"),
+            link("ad", "A", "D"),
+            // Both endpoints unknown
+            link("ef", "E", "F"),
+            // Repeated within the batch
+            link("ef", "E", "F"),
+        ];
+
+        let report = network.dry_run_load(&batch);
+        assert_eq!(
+            report,
+            LoadReport {
+                new_nodes: 3,
+                new_edges: 4,
+                duplicate_edges: 2,
+                dangling_references: 3,
+            }
+        );
+        // Nothing was loaded
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph.edge_count(), 2);
+
+        network.apply_load(&batch, (0.0, 0.0));
+        assert_eq!(network.graph.node_count(), 3 + report.new_nodes);
+        assert_eq!(network.graph.edge_count(), 2 + report.new_edges);
+        assert_eq!(network.dry_run_load(&batch).new_edges, 0);
+    }
+
+    #[test]
+    fn test_max_flow_conserves_flow() {
+        let network = NetworkBuilder::new()
+            .link("sa", "S", "A", 10, 1.0)
+            .link("sb", "S", "B", 5, 1.0)
+            .link("ab", "A", "B", 15, 1.0)
+            .link("at", "A", "T", 4, 1.0)
+            .link("bt", "T", "B", 10, 1.0)
+            .build()
+            .unwrap();
+        let (source, sink) = (network.node_indices["S"], network.node_indices["T"]);
+
+        let (total, residuals) = network.max_flow_with_residuals(source, sink);
+        // The cut {at, bt} limits the flow
+        assert_eq!(total, 14);
+        let residual = |id: &str| residuals[&network.link_indices[id]];
+        assert_eq!(residual("at"), 0);
+        assert_eq!(residual("bt"), 0);
+        // Everything leaving the source is used capacity
+        assert_eq!((10 - residual("sa")) + (5 - residual("sb")), total);
+
+        let (_, flows) = network.max_flow_assignment(source, sink);
+        for node in network.graph.node_indices() {
+            let net_outflow: i64 = network
+                .incident_edges(node)
+                .into_iter()
+                .map(|(edge, _)| {
+                    let flow = flows.get(&edge).copied().unwrap_or(0);
+                    if network.graph.edge_endpoints(edge).unwrap().0 == node {
+                        flow
+                    } else {
+                        -flow
+                    }
+                })
+                .sum();
+            let expected = if node == source {
+                total as i64
+            } else if node == sink {
+                -(total as i64)
+            } else {
+                0
+            };
+            assert_eq!(net_outflow, expected, "{}", network.graph[node].id);
+        }
+
+        assert_eq!(network.max_flow_with_residuals(source, source).0, 0);
+    }
+
+    #[test]
+    fn test_degree_centrality_counts_self_loops_twice() {
+        let network = NetworkBuilder::new()
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 10, 1.0)
+            .link("aa", "A", "A", 10, 1.0)
+            .build()
+            .unwrap();
+        let degrees = network.degree_centrality();
+        let degree = |id: &str| degrees[&network.node_indices[id]];
+        assert_eq!((degree("A"), degree("B"), degree("C")), (3, 2, 1));
+        assert_eq!(network.degree(network.node_indices["A"]), 3);
+    }
+
+    #[test]
+    fn test_betweenness_centrality() {
+        // A square with a tail: the two routes around the square split the paths evenly
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "D", 1.0),
+            ("A", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+        ]);
+        let betweenness = network.betweenness_centrality();
+        let score = |id: &str| betweenness[&network.node_indices[id]];
+
+        // D lies on every path to E from A, B and C, and on half of those between B and C
+        assert!((score("D") - 3.5).abs() < 1e-9);
+        // B carries half of A-D and half of A-E
+        assert!((score("B") - 1.0).abs() < 1e-9);
+        assert!((score("C") - 1.0).abs() < 1e-9);
+        assert!((score("A") - 0.5).abs() < 1e-9);
+        assert_eq!(score("E"), 0.0);
+    }
+
+    #[test]
+    fn test_floyd_warshall_matches_bfs() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "A", 1.0),
+            ("C", "D", 1.0),
+            ("D", "D", 1.0),
+            ("X", "Y", 1.0),
+        ]);
+        let bfs: HashMap<NodeIndex, HashMap<NodeIndex, u32>> = network
+            .graph
+            .node_indices()
+            .map(|start| (start, network.hop_distances(start)))
+            .collect();
+        assert_eq!(network.floyd_warshall_hops(), bfs);
+
+        // Dense enough for the cache to take the Floyd–Warshall route
+        let complete = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "A", 1.0)]);
+        let (a, c) = (complete.node_indices["A"], complete.node_indices["C"]);
+        assert_eq!(complete.all_pairs_hops()[&a][&c], 1);
+        assert_eq!(complete.diameter(), Some(1));
+    }
+
+    #[test]
+    fn test_analysis_cache_invalidated_by_new_link() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "D", 1.0)]);
+        let (a, d) = (network.node_indices["A"], network.node_indices["D"]);
+        assert_eq!(network.diameter(), Some(3));
+        assert_eq!(network.all_pairs_hops()[&a][&d], 3);
+
+        network
+            .add_link(Link {
+                link_id: "shortcut".to_string(),
+                source_node: "A".to_string(),
+                destination_node: "D".to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .unwrap();
+        assert_eq!(network.all_pairs_hops()[&a][&d], 1);
+        assert_eq!(network.diameter(), Some(2));
+
+        let shortcut = network.link_indices["shortcut"];
+        network.remove_link(shortcut);
+        assert_eq!(network.diameter(), Some(3));
+    }
+
+    #[test]
+    fn test_closeness_centrality_star() {
+        let mut network = network_from_edges(&[
+            ("hub", "A", 1.0),
+            ("hub", "B", 1.0),
+            ("hub", "C", 1.0),
+            ("hub", "D", 1.0),
+        ]);
+        network.add_node(Node {
+            id: "loner".to_string(),
+            ..Node::default()
+        });
+
+        let closeness = network.closeness_centrality();
+        let score = |id: &str| closeness[&network.node_indices[id]];
+        // The hub reaches its 4 leaves in one hop, out of 5 other nodes
+        assert!((score("hub") - 0.8).abs() < 1e-9);
+        // Leaves: 1 hop to the hub and 2 to each other leaf
+        assert!((score("A") - (4.0 / 7.0) * 0.8).abs() < 1e-9);
+        assert!(
+            ["A", "B", "C", "D"]
+                .iter()
+                .all(|leaf| score(leaf) < score("hub"))
+        );
+        assert_eq!(score("loner"), 0.0);
+    }
+
+    #[test]
+    fn test_induced_subgraph_keeps_inner_links() {
+        let network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "A", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+        ]);
+        let selected: HashSet<NodeIndex> = ["A", "B", "C"]
+            .iter()
+            .map(|id| network.node_indices[*id])
+            .collect();
+
+        let subgraph = network.induced_subgraph(&selected);
+        assert_eq!(subgraph.graph.node_count(), 3);
+        let mut link_ids: Vec<&str> = subgraph
+            .graph
+            .edge_weights()
+            .map(|link| link.link_id.as_str())
+            .collect();
+        link_ids.sort();
+        assert_eq!(link_ids, ["link0", "link1", "link2"]);
+        for link in subgraph.graph.edge_weights() {
+            assert!(subgraph.node_indices.contains_key(&link.source_node));
+            assert!(subgraph.node_indices.contains_key(&link.destination_node));
+        }
+
+        let path = std::env::temp_dir().join("net_modeler-induced-links.csv");
+        let path = path.to_str().unwrap();
+        let links: Vec<Link> = subgraph.graph.edge_weights().cloned().collect();
+        save_links(path, &links).unwrap();
+        let reloaded = load_network_links(path).unwrap();
+        assert_eq!(reloaded.len(), 3);
+        assert_eq!(reloaded[0].link_id, links[0].link_id);
+        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+    }
+
+    #[test]
+    fn test_capacity_range() {
+        assert_eq!(Network::new().capacity_range(), (0, 0));
+
+        let single = NetworkBuilder::new()
+            .link("only", "A", "B", 40, 1.0)
+            .build()
+            .unwrap();
+        assert_eq!(single.capacity_range(), (40, 40));
+
+        let network = NetworkBuilder::new()
+            .link("mid", "A", "B", 100, 1.0)
+            .link("low", "B", "C", 5, 1.0)
+            .link("high", "C", "A", 250, 1.0)
+            .build()
+            .unwrap();
+        assert_eq!(network.capacity_range(), (5, 250));
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_costs() {
+        let mut network = NetworkBuilder::new()
+            .link("sa", "S", "A", 10, 4.0)
+            .link("sb", "S", "B", 10, 6.0)
+            .link("ba", "B", "A", 10, 1.0)
+            .link("at", "A", "T", 10, 3.0)
+            .build()
+            .unwrap();
+        let edge = network.link_indices["ba"];
+        network.graph[edge].cost = Some(-5);
+
+        let (path, cost) = network
+            .shortest_path_bellman_ford("S", "T")
+            .unwrap()
+            .unwrap();
+        let ids: Vec<&str> = path
+            .iter()
+            .map(|&idx| network.graph[idx].id.as_str())
+            .collect();
+        // The incentive on B -> A beats the direct S -> A link
+        assert_eq!(ids, ["S", "B", "A", "T"]);
+        assert_eq!(cost, 6 - 5 + 3);
+
+        // Links only run from source to destination
+        assert!(
+            network
+                .shortest_path_bellman_ford("T", "S")
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            network.shortest_path_bellman_ford("S", "missing"),
+            Err(NetworkError::NodeNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_bellman_ford_detects_negative_cycle() {
+        let mut network = NetworkBuilder::new()
+            .link("sa", "S", "A", 10, 1.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 10, 1.0)
+            .link("ca", "C", "A", 10, 1.0)
+            .link("ct", "C", "T", 10, 1.0)
+            .build()
+            .unwrap();
+        let edge = network.link_indices["ca"];
+        network.graph[edge].cost = Some(-3);
+
+        assert!(matches!(
+            network.shortest_path_bellman_ford("S", "T"),
+            Err(NetworkError::NegativeCycle)
+        ));
+    }
+
+    #[test]
+    fn test_spread_overlapping_nodes() {
+        // Every node at the origin, as when a CSV carries no positions
+        let mut network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+            ("E", "F", 1.0),
+        ]);
+        let loner = network.add_node(Node {
+            id: "loner".to_string(),
+            point: (500.0, 500.0),
+            ..Node::default()
+        });
+
+        network.spread_overlapping(36.0);
+        let points: Vec<(f64, f64)> = network.nodes().map(|node| node.point).collect();
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+                assert!(distance >= 36.0 - 1e-9, "{:?} and {:?} overlap", a, b);
+            }
         }
+        // Nodes that were clear of the others keep their position
+        assert_eq!(network.graph[loner].point, (500.0, 500.0));
 
-        assert_eq!(network.graph.node_count(), 3); // Node1, Node2, Node3
-        assert_eq!(network.graph.edge_count(), 3); // link_A, link_B, link_C
+        let mut again = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0)]);
+        again.spread_overlapping(36.0);
+        let mut repeat = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0)]);
+        repeat.spread_overlapping(36.0);
+        let positions = |network: &Network| network.export_positions();
+        assert_eq!(positions(&again), positions(&repeat));
+    }
 
-        // Verify specific nodes and edges exist
-        let node1_idx = network.node_indices.get("Node1").expect("Node1 not found");
-        let node2_idx = network.node_indices.get("Node2").expect("Node2 not found");
-        let node3_idx = network.node_indices.get("Node3").expect("Node3 not found");
+    #[test]
+    fn test_link_direction_follows_declared_source() {
+        let mut network = network_from_edges(&[("A", "B", 1.0)]);
+        let (a, b) = (network.node_indices["A"], network.node_indices["B"]);
+        assert_eq!(
+            network.link_direction(network.link_indices["link0"]),
+            Some((a, b))
+        );
 
-        assert_eq!(network.graph[*node1_idx].id, "Node1");
-        assert_eq!(network.graph[*node2_idx].id, "Node2");
-        assert_eq!(network.graph[*node3_idx].id, "Node3");
+        // Stored with its endpoints swapped, the arrow still runs from the declared source
+        let reversed = network.graph.add_edge(
+            b,
+            a,
+            Link {
+                link_id: "back".to_string(),
+                source_node: "A".to_string(),
+                destination_node: "B".to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            },
+        );
+        assert_eq!(network.graph.edge_endpoints(reversed), Some((b, a)));
+        assert_eq!(network.link_direction(reversed), Some((a, b)));
+    }
 
-        // Check if an edge exists from Node1 to Node2
-        let edge_ref = network.graph.find_edge(*node1_idx, *node2_idx);
-        assert!(edge_ref.is_some());
-        let edge_weight = network.graph.edge_weight(edge_ref.unwrap()).unwrap();
-        assert_eq!(edge_weight.capacity, 100);
-        assert_eq!(edge_weight.weight, 10);
+    #[test]
+    fn test_to_directed_one_arc_per_link() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 2.0), ("C", "A", 3.0)]);
+        // A link stored against its declared direction
+        let (a, c) = (network.node_indices["A"], network.node_indices["C"]);
+        network.graph.add_edge(
+            c,
+            a,
+            Link {
+                link_id: "a-to-c".to_string(),
+                source_node: "A".to_string(),
+                destination_node: "C".to_string(),
+                capacity: 10,
+                weight: 4.0,
+                ..Link::default()
+            },
+        );
 
-        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
-        std::fs::remove_dir("test_configuration").expect("Failed to remove Test Dir");
+        let directed = network.to_directed();
+        assert_eq!(directed.node_count(), network.graph.node_count());
+        assert_eq!(directed.edge_count(), network.graph.edge_count());
+        for arc in directed.edge_references() {
+            let link = arc.weight();
+            assert_eq!(directed[arc.source()].id, link.source_node);
+            assert_eq!(directed[arc.target()].id, link.destination_node);
+        }
+        // Node indices carry over
+        for idx in network.graph.node_indices() {
+            assert_eq!(directed[idx].id, network.graph[idx].id);
+        }
     }
 
     #[test]
-    fn test_node_addition_deduplication() {
+    fn test_set_link_weight() {
+        let mut network = network_from_edges(&[("A", "B", 5.0)]);
+        let edge = network.link_indices["link0"];
+
+        assert!(network.set_link_weight(edge, 42.5));
+        assert_eq!(network.graph[edge].weight, 42.5);
+        assert!(network.set_link_weight(edge, 0.0));
+        assert_eq!(network.graph[edge].weight, 0.0);
+        // Weights a CSV could not hold either are refused
+        for weight in [-7.0, f64::INFINITY, f64::NAN] {
+            assert!(!network.set_link_weight(edge, weight));
+            assert_eq!(network.graph[edge].weight, 0.0);
+        }
+
+        assert!(!network.set_link_weight(EdgeIndex::new(9), 1.0));
+    }
+
+    #[test]
+    fn test_density() {
+        let complete = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("A", "C", 1.0),
+            ("A", "D", 1.0),
+            ("B", "C", 1.0),
+            ("B", "D", 1.0),
+            ("C", "D", 1.0),
+        ]);
+        assert!((complete.density() - 1.0).abs() < 1e-9);
+
+        // 4 links out of the 10 possible among 5 nodes
+        let path = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+        ]);
+        assert!((path.density() - 0.4).abs() < 1e-9);
+
+        assert_eq!(Network::new().density(), 0.0);
+        let mut single = Network::new();
+        single.add_node(Node::default());
+        assert_eq!(single.density(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_capacity_units() {
+        assert_eq!(parse_capacity("100"), Ok(100));
+        assert_eq!(parse_capacity("100Mbps"), Ok(100));
+        assert_eq!(parse_capacity("1Gbps"), Ok(1000));
+        assert_eq!(parse_capacity("2.5 gbps"), Ok(2500));
+        assert_eq!(
+            parse_capacity("abc"),
+            Err(
+                "Invalid capacity 'abc': expected a number with an optional Mbps, Gbps or Tbps suffix"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#FF8800").unwrap(), (255, 136, 0));
+        assert_eq!(parse_hex_color("00ff7f").unwrap(), (0, 255, 127));
+        assert_eq!(parse_hex_color(" #0a0B0c ").unwrap(), (10, 11, 12));
+        for invalid in [
+            "", "#", "#FF880", "#FF88000", "#GG8800", "orange", "#FF 880",
+        ] {
+            assert!(
+                matches!(parse_hex_color(invalid), Err(NetworkError::Parse(_))),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_link_colors() {
+        let text = "link_id,source_node,destination_node,capacity,weight,color\n\
+                   ab,A,B,100,1,#FF8800\n\
+                   bc,B,C,100,1,\n";
+        let links = read_network_links(csv::Reader::from_reader(text.as_bytes())).unwrap();
+        assert_eq!(links[0].color, Some((255, 136, 0)));
+        assert_eq!(links[1].color, None);
+
+        // Files without the column keep the theme's color
+        let text = "link_id,source_node,destination_node,capacity,weight\nab,A,B,100,1\n";
+        let links = read_network_links(csv::Reader::from_reader(text.as_bytes())).unwrap();
+        assert_eq!(links[0].color, None);
+
+        let text = "link_id,source_node,destination_node,capacity,weight,color\nab,A,B,100,1,red\n";
+        assert!(read_network_links(csv::Reader::from_reader(text.as_bytes())).is_err());
+    }
+
+    #[test]
+    fn test_fractional_weights() {
+        let text = "link_id,source_node,destination_node,capacity,weight\n\
+                   ab,A,B,100,2.5\n\
+                   bc,B,C,100,1.25\n\
+                   ac,A,C,100,4\n";
+        let links = read_network_links(csv::Reader::from_reader(text.as_bytes())).unwrap();
+        assert_eq!(links[0].weight, 2.5);
         let mut network = Network::new();
-        let node1 = Node {
+        network.apply_load(&links, (0.0, 0.0));
+        let idx = |id: &str| network.node_indices[id];
+
+        let paths = network.k_shortest_paths(idx("A"), idx("C"), 1);
+        assert_eq!(paths, vec![(vec![idx("A"), idx("B"), idx("C")], 3.75)]);
+
+        // A NaN weight set in code makes its link impassable instead of confusing Dijkstra
+        let ab = network.link_indices["ab"];
+        network.graph[ab].weight = f64::NAN;
+        let paths = network.k_shortest_paths(idx("A"), idx("C"), 1);
+        assert_eq!(paths, vec![(vec![idx("A"), idx("C")], 4.0)]);
+
+        assert_eq!(format_weight(2.5), "2.5");
+        assert_eq!(format_weight(3.0), "3");
+        assert_eq!(format_weight(1.0 / 3.0), "0.33");
+        assert_eq!(format_weight(10.0), "10");
+
+        for weight in ["-1", "NaN"] {
+            let text = format!(
+                "link_id,source_node,destination_node,capacity,weight\nab,A,B,100,{}\n",
+                weight
+            );
+            assert!(read_network_links(csv::Reader::from_reader(text.as_bytes())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_load_capacity_units() {
+        let path = std::env::temp_dir().join("net_modeler-capacity-units.csv");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            "link_id,source_node,destination_node,capacity,weight\n\
+             ab,A,B,100,1\n\
+             bc,B,C,1Gbps,1\n",
+        )
+        .expect("Failed to write dummy CSV");
+        let links = load_network_links(path).expect("Failed to load links");
+        std::fs::write(
+            path,
+            "link_id,source_node,destination_node,capacity,weight\n\
+             ab,A,B,abc,1\n",
+        )
+        .expect("Failed to write dummy CSV");
+        let invalid = load_network_links(path);
+        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+
+        let capacities: Vec<u64> = links.iter().map(|link| link.capacity).collect();
+        assert_eq!(capacities, vec![100, 1000]);
+        match invalid {
+            Err(NetworkError::Csv(err)) => assert!(err.to_string().contains("'abc'")),
+            other => panic!("expected a CSV error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_node() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 0.0, 100.0)
+            .node("A_copy", 50.0, 50.0)
+            .link("1", "A", "B", 10, 2.0)
+            .link("2", "C", "A", 20, 3.0)
+            .build()
+            .unwrap();
+        let original = network.node_indices["A"];
+
+        let copy = network.duplicate_node(original);
+
+        assert_eq!(network.graph[copy].id, "A_copy2");
+        assert_ne!(network.graph[copy].point, network.graph[original].point);
+        assert_eq!(
+            network.neighbors(copy).len(),
+            network.neighbors(original).len()
+        );
+        let mut copied: Vec<(String, String, u64)> = network
+            .graph
+            .edges(copy)
+            .map(|edge| {
+                let link = edge.weight();
+                (
+                    link.source_node.clone(),
+                    link.destination_node.clone(),
+                    link.capacity,
+                )
+            })
+            .collect();
+        copied.sort();
+        assert_eq!(
+            copied,
+            vec![
+                ("A_copy2".to_string(), "B".to_string(), 10),
+                ("C".to_string(), "A_copy2".to_string(), 20),
+            ]
+        );
+        assert_eq!(network.link_indices.len(), 4);
+    }
+
+    #[test]
+    fn test_network_json_round_trip() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 12.5, 40.25)
+            .node("B", -3.0, 7.75)
+            .link("1", "A", "B", 10, 2.5)
+            .build()
+            .unwrap();
+        let a = network.node_indices["A"];
+        network.graph[a]
+            .metadata
+            .insert("vendor".to_string(), "Acme".to_string());
+
+        let json = network.to_json();
+        assert!(json.contains(r#""version": 2"#));
+        let loaded = Network::from_json(&json).unwrap();
+        assert_eq!(
+            loaded.nodes().cloned().collect::<Vec<_>>(),
+            network.nodes().cloned().collect::<Vec<_>>()
+        );
+        let (_, link) = loaded.link_by_id("1").unwrap();
+        assert_eq!((link.capacity, link.weight), (10, 2.5));
+    }
+
+    #[test]
+    fn test_network_json_migrates_v1() {
+        // The synthetic version 1 shape: no version tag, whole-number weights
+        let v1 = r#"{
+            "nodes": [{"id": "A", "point": [0.0, 0.0]}, {"id": "B", "point": [10.0, 5.0]}],
+            "links": [{"link_id": "ab", "source_node": "A", "destination_node": "B",
+                       "capacity": 100, "weight": 3}]
+        }"#;
+        let mut document: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(v1).unwrap();
+        migrate_v1_to_v2(&mut document);
+        assert_eq!(document["version"], 2);
+        assert!(document["links"][0]["weight"].is_f64());
+
+        for json in [v1, &v1.replacen('{', r#"{"version": 1,"#, 1)] {
+            let network = Network::from_json(json).unwrap();
+            assert_eq!(network.graph.node_count(), 2);
+            assert_eq!(network.graph[network.node_indices["B"]].point, (10.0, 5.0));
+            let (_, link) = network.link_by_id("ab").unwrap();
+            assert_eq!((link.capacity, link.weight), (100, 3.0));
+        }
+    }
+
+    #[test]
+    fn test_network_json_rejects_unknown_versions() {
+        let future = r#"{"version": 3, "nodes": [], "links": [], "layers": []}"#;
+        match Network::from_json(future) {
+            Err(err @ NetworkError::UnsupportedVersion(3)) => assert_eq!(
+                err.to_string(),
+                "Unsupported network file version 3: expected 1 to 2"
+            ),
+            other => panic!(
+                "expected an unsupported version, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+        assert!(matches!(
+            Network::from_json(r#"{"version": 0, "nodes": [], "links": []}"#),
+            Err(NetworkError::UnsupportedVersion(0))
+        ));
+        assert!(matches!(
+            Network::from_json(r#"{"version": "two", "nodes": [], "links": []}"#),
+            Err(NetworkError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_export_layout_json_round_trip() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 12.5, 40.25)
+            .node("B", -3.0, 7.75)
+            .link("1", "A", "B", 10, 1.0)
+            .build()
+            .unwrap();
+        let original: Vec<Node> = network.nodes().cloned().collect();
+
+        let json = network.export_layout_json();
+        assert_eq!(
+            json,
+            r#"[{"id":"A","x":12.5,"y":40.25},{"id":"B","x":-3.0,"y":7.75}]"#
+        );
+
+        let positions: Vec<NodePosition> = serde_json::from_str(&json).unwrap();
+        let positions: Vec<(String, f64, f64)> = positions
+            .into_iter()
+            .map(|position| (position.id, position.x, position.y))
+            .collect();
+        for idx in network.graph.node_indices().collect::<Vec<_>>() {
+            network.graph[idx].point = (0.0, 0.0);
+        }
+        network.apply_positions(&positions);
+
+        let restored: Vec<Node> = network.nodes().cloned().collect();
+        assert_eq!(restored, original);
+    }
+
+    #[test]
+    fn test_widest_path() {
+        // The direct route is shortest by weight but squeezes through a thin link
+        let network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 0.0, 100.0)
+            .node("D", 100.0, 100.0)
+            .node("E", 200.0, 200.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bd", "B", "D", 100, 1.0)
+            .link("ac", "A", "C", 80, 5.0)
+            .link("cd", "C", "D", 60, 5.0)
+            .build()
+            .unwrap();
+        let idx = |id: &str| network.node_indices[id];
+
+        let shortest = network.find_shortest_path("A", "D").unwrap();
+        assert_eq!(shortest, vec![(idx("A"), idx("B")), (idx("B"), idx("D"))]);
+        assert_eq!(
+            network.widest_path(idx("A"), idx("D")),
+            Some((vec![idx("A"), idx("C"), idx("D")], 60))
+        );
+        assert_eq!(network.widest_path(idx("A"), idx("E")), None);
+        assert_eq!(network.widest_path(idx("A"), idx("A")), None);
+    }
+
+    #[test]
+    fn test_load_trims_ids() {
+        let path = std::env::temp_dir().join("net_modeler-trim.csv");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            "link_id,source_node,destination_node,capacity,weight\n\
+             1 ,Node1 ,Node2,10,1\n\
+             2, Node2,Node1,10,1\n",
+        )
+        .expect("Failed to write dummy CSV");
+        let links = load_network_links(path).expect("Failed to load links");
+        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+
+        let ids: Vec<(&str, &str, &str)> = links
+            .iter()
+            .map(|link| {
+                (
+                    link.link_id.as_str(),
+                    link.source_node.as_str(),
+                    link.destination_node.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(ids, vec![("1", "Node1", "Node2"), ("2", "Node2", "Node1")]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 0.0, 100.0)
+            .link("1", "A", "B", 10, 1.0)
+            .build()
+            .unwrap();
+        assert_eq!(
+            network.validate(),
+            vec!["Node 'C' has no links".to_string()]
+        );
+
+        network
+            .add_link(Link {
+                link_id: "1".to_string(),
+                source_node: "B".to_string(),
+                destination_node: "C".to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .unwrap();
+        assert_eq!(
+            network.validate(),
+            vec!["Link id '1' is used by 2 links".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_routing_tables_line() {
+        let network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 200.0, 0.0)
+            .node("D", 300.0, 0.0)
+            .node("E", 400.0, 400.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 10, 2.0)
+            .link("cd", "C", "D", 10, 3.0)
+            .build()
+            .unwrap();
+        let idx = |id: &str| network.node_indices[id];
+        let tables = network.routing_tables();
+
+        // Every next hop is the neighbor on the destination's side of the line
+        assert_eq!(
+            tables[&idx("A")],
+            vec![
+                (idx("B"), idx("B"), 1.0),
+                (idx("C"), idx("B"), 3.0),
+                (idx("D"), idx("B"), 6.0),
+            ]
+        );
+        assert_eq!(
+            tables[&idx("C")],
+            vec![
+                (idx("A"), idx("B"), 3.0),
+                (idx("B"), idx("B"), 2.0),
+                (idx("D"), idx("D"), 3.0),
+            ]
+        );
+        assert_eq!(tables[&idx("D")][0], (idx("A"), idx("C"), 6.0));
+        assert!(tables[&idx("E")].is_empty());
+    }
+
+    #[test]
+    fn test_save_routing_tables() {
+        let network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .link("ab", "A", "B", 10, 4.0)
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join("net_modeler-routes.csv");
+        let path = path.to_str().unwrap();
+        save_routing_tables(path, &network).expect("Failed to save routing tables");
+        let saved = std::fs::read_to_string(path).expect("Failed to read routing tables");
+        std::fs::remove_file(path).expect("Failed to remove routing tables");
+
+        assert_eq!(
+            saved,
+            "node,destination,next_hop,cost\nA,B,B,4.0\nB,A,A,4.0\n"
+        );
+    }
+
+    #[test]
+    fn test_load_node_metadata_columns() {
+        let path = std::env::temp_dir().join("net_modeler-node-metadata.csv");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            "link_id,source_node,destination_node,capacity,weight,source_vendor,destination_site\n\
+             ab,A,B,10,1,Acme,Berlin\n\
+             bc,B,C,10,1,Initech,\n",
+        )
+        .expect("Failed to write dummy CSV");
+        let network = load_networks(&[path]).expect("Failed to load network");
+        std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+
+        let node = |id: &str| &network.graph[network.node_indices[id]];
+        assert_eq!(
+            node("A").metadata,
+            HashMap::from([("vendor".to_string(), "Acme".to_string())])
+        );
+        assert_eq!(
+            node("B").metadata,
+            HashMap::from([
+                ("site".to_string(), "Berlin".to_string()),
+                ("vendor".to_string(), "Initech".to_string()),
+            ])
+        );
+        assert!(node("C").metadata.is_empty());
+        assert!(
+            network
+                .graph
+                .edge_weights()
+                .all(|link| link.endpoint_metadata == Default::default())
+        );
+    }
+
+    #[test]
+    fn test_node_json_includes_metadata() {
+        let node = Node {
             id: "A".to_string(),
-            point: (0, 0),
-        };
-        let node2 = Node {
-            id: "B".to_string(),
-            point: (0, 0),
+            point: (1.0, 2.0),
+            metadata: HashMap::from([("vendor".to_string(), "Acme".to_string())]),
+            ..Node::default()
         };
+        let json = serde_json::to_string(&node).unwrap();
+        assert!(json.contains(r#""metadata":{"vendor":"Acme"}"#));
+        assert_eq!(serde_json::from_str::<Node>(&json).unwrap(), node);
 
-        let idx_a1 = network.add_node(node1.clone());
-        let idx_b = network.add_node(node2.clone());
-        let idx_a2 = network.add_node(node1.clone()); // Add the same node again
+        // Nodes written before metadata existed still parse
+        let bare: Node = serde_json::from_str(r#"{"id":"B","point":[0.0,0.0]}"#).unwrap();
+        assert!(bare.metadata.is_empty());
+    }
+
+    #[test]
+    fn test_contract_edge_triangle() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("C", 50.0, 100.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 20, 2.0)
+            .link("ca", "C", "A", 30, 3.0)
+            .build()
+            .unwrap();
+        let (edge, _) = network.link_by_id("ab").unwrap();
+
+        let merged = network.contract_edge(edge).unwrap();
 
         assert_eq!(network.graph.node_count(), 2);
+        assert_eq!(network.graph[merged].id, "A+B");
+        assert_eq!(network.graph[merged].point, (50.0, 0.0));
         assert_eq!(network.node_indices.len(), 2);
-        assert_eq!(idx_a1, idx_a2); // Should return the same index for the same node ID
-        assert_ne!(idx_a1, idx_b);
+        assert_eq!(network.node_indices["A+B"], merged);
+        // Both remaining links now join the merged node and C
+        let mut remaining: Vec<(String, String, String)> = network
+            .graph
+            .edge_weights()
+            .map(|link| {
+                (
+                    link.link_id.clone(),
+                    link.source_node.clone(),
+                    link.destination_node.clone(),
+                )
+            })
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                ("bc".to_string(), "A+B".to_string(), "C".to_string()),
+                ("ca".to_string(), "C".to_string(), "A+B".to_string()),
+            ]
+        );
+        assert!(network.link_by_id("ab").is_none());
+        assert_eq!(network.neighbors(merged).len(), 2);
     }
 
     #[test]
-    fn test_link_addition_error_handling() {
-        let mut network = Network::new();
-        let node_a = Node {
-            id: "A".to_string(),
-            point: (0, 0),
-        };
-        network.add_node(node_a);
+    fn test_collapse_group_clique() {
+        let mut network = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 30.0, 0.0)
+            .node("C", 0.0, 30.0)
+            .node("X", 200.0, 0.0)
+            .node("Y", 0.0, 200.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("bc", "B", "C", 20, 1.0)
+            .link("ca", "C", "A", 30, 1.0)
+            .link("ax", "A", "X", 100, 5.0)
+            .link("xb", "X", "B", 50, 2.0)
+            .link("cy", "C", "Y", 40, 7.0)
+            .link("xy", "X", "Y", 1, 1.0)
+            .build()
+            .unwrap();
+        let clique: HashSet<NodeIndex> = ["A", "B", "C"]
+            .iter()
+            .map(|id| network.node_indices[*id])
+            .collect();
 
-        // Try to add a link with a non-existent destination node
-        let invalid_link = Link {
-            link_id: "link_invalid".to_string(),
-            source_node: "A".to_string(),
-            destination_node: "NonExistent".to_string(),
-            capacity: 10,
-            weight: 1,
-        };
-        let result = network.add_link(invalid_link);
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            NetworkError::NodeNotFound(id) => assert_eq!(id, "NonExistent"),
-            _ => panic!("Expected NodeNotFound error"),
+        let group = network.collapse_group(&clique).unwrap();
+
+        assert_eq!(network.graph.node_count(), 3);
+        assert_eq!(network.graph[group].id, "A+B+C");
+        assert_eq!(network.graph[group].point, (10.0, 10.0));
+        assert_eq!(network.graph[group].metadata["internal_capacity"], "60");
+        assert_eq!(network.graph.edge_count(), 3);
+
+        // Both links to X became one, the single link to Y is kept as is
+        let (_, to_x) = network.link_by_id("ax").unwrap();
+        assert_eq!(
+            (to_x.source_node.as_str(), to_x.destination_node.as_str()),
+            ("A+B+C", "X")
+        );
+        assert_eq!((to_x.capacity, to_x.weight), (150, 2.0));
+        assert!(network.link_by_id("xb").is_none());
+        let (_, to_y) = network.link_by_id("cy").unwrap();
+        assert_eq!((to_y.capacity, to_y.weight), (40, 7.0));
+        assert_eq!(network.neighbors(group).len(), 2);
+        assert!(network.link_by_id("xy").is_some());
+
+        assert_eq!(network.collapse_group(&HashSet::new()), None);
+    }
+
+    #[test]
+    fn test_merged_nodes_get_unused_ids() {
+        let builder = NetworkBuilder::new()
+            .node("A", 0.0, 0.0)
+            .node("B", 100.0, 0.0)
+            .node("A+B", 50.0, 100.0)
+            .link("ab", "A", "B", 10, 1.0)
+            .link("b-ab", "B", "A+B", 10, 1.0);
+
+        let mut network = builder.clone().build().unwrap();
+        let (edge, _) = network.link_by_id("ab").unwrap();
+        let merged = network.contract_edge(edge).unwrap();
+        assert_eq!(network.graph[merged].id, "A+B#2");
+        assert_eq!(network.node_indices.len(), 2);
+        assert_eq!(network.node_indices["A+B#2"], merged);
+        let (_, link) = network.link_by_id("b-ab").unwrap();
+        assert_eq!(
+            (link.source_node.as_str(), link.destination_node.as_str()),
+            ("A+B#2", "A+B")
+        );
+
+        let mut network = builder.build().unwrap();
+        let pair: HashSet<NodeIndex> = [network.node_indices["A"], network.node_indices["B"]]
+            .into_iter()
+            .collect();
+        let group = network.collapse_group(&pair).unwrap();
+        assert_eq!(network.graph[group].id, "A+B#2");
+        assert_eq!(network.node_indices.len(), 2);
+
+        // A lone member keeps its own id
+        let lone = HashSet::from([network.node_indices["A+B"]]);
+        let group = network.collapse_group(&lone).unwrap();
+        assert_eq!(network.graph[group].id, "A+B");
+    }
+
+    #[test]
+    fn test_spectral_layout_path() {
+        let mut network = network_from_edges(&[
+            ("A", "B", 1.0),
+            ("B", "C", 1.0),
+            ("C", "D", 1.0),
+            ("D", "E", 1.0),
+        ]);
+        network.apply_spectral_layout(600.0, 400.0);
+
+        let xs: Vec<f64> = ["A", "B", "C", "D", "E"]
+            .iter()
+            .map(|id| network.graph[network.node_indices[*id]].point.0)
+            .collect();
+        // Nodes line up along x in path order, from one margin to the other
+        let increasing = xs.windows(2).all(|pair| pair[0] < pair[1]);
+        let decreasing = xs.windows(2).all(|pair| pair[0] > pair[1]);
+        assert!(increasing || decreasing, "{:?}", xs);
+        assert!((xs[0] - xs[4]).abs() > 499.0);
+        // The middle node sits in the middle, the rest evenly around it
+        assert!((xs[2] - 300.0).abs() < 1e-6);
+        assert!(((xs[1] - xs[0]) - (xs[4] - xs[3])).abs() < 1e-6);
+        let y = |id: &str| network.graph[network.node_indices[id]].point.1;
+        assert!((y("A") - y("E")).abs() < 1e-6);
+        assert!((y("B") - y("D")).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_load_edge_list() {
+        let path = std::env::temp_dir().join("net_modeler-edge-list.txt");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "# backbone\nA B 100 10\n\n  B\tC  \nC A 1Gbps 3\n")
+            .expect("Failed to write dummy edge list");
+        let links = load_edge_list(path);
+        std::fs::write(path, "A B\nA B 100\n").expect("Failed to write dummy edge list");
+        let malformed = load_edge_list(path);
+        std::fs::remove_file(path).expect("Failed to remove dummy edge list");
+
+        let links: Vec<(String, String, String, u64, f64)> = links
+            .expect("Failed to load edge list")
+            .into_iter()
+            .map(|link| {
+                (
+                    link.link_id,
+                    link.source_node,
+                    link.destination_node,
+                    link.capacity,
+                    link.weight,
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("1".to_string(), "A".to_string(), "B".to_string(), 100, 10.0),
+                (
+                    "2".to_string(),
+                    "B".to_string(),
+                    "C".to_string(),
+                    DEFAULT_LINK_CAPACITY,
+                    1.0
+                ),
+                ("3".to_string(), "C".to_string(), "A".to_string(), 1000, 3.0),
+            ]
+        );
+        match malformed {
+            Err(NetworkError::Parse(message)) => assert!(message.starts_with("line 2:")),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
         }
     }
 
     #[test]
-    fn test_find_shortest_path() {
-        let mut network = Network::new();
+    fn test_shortest_path_tree() {
+        let network = network_from_edges(&[
+            ("S", "A", 1.0),
+            ("S", "B", 4.0),
+            ("A", "B", 2.0),
+            ("B", "C", 1.0),
+            ("A", "C", 5.0),
+            ("D", "E", 1.0),
+        ]);
+        let idx = |id: &str| network.node_indices[id];
+        let tree = network.shortest_path_tree(idx("S"));
 
-        let node_a = Node {
-            id: "A".to_string(),
-            point: (0, 0),
-        };
-        let node_b = Node {
-            id: "B".to_string(),
-            point: (50, 0),
-        };
-        let node_c = Node {
-            id: "C".to_string(),
-            point: (100, 0),
-        };
+        // Only the source's component, with the source as the only root
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree[&idx("S")], None);
+        assert!(!tree.contains_key(&idx("D")));
+        assert_eq!(tree[&idx("A")], Some(idx("S")));
+        assert_eq!(tree[&idx("B")], Some(idx("A")));
+        assert_eq!(tree[&idx("C")], Some(idx("B")));
+        // Following predecessors from any node ends at the source without revisiting a node
+        for &node in tree.keys() {
+            let mut seen = HashSet::from([node]);
+            let mut current = node;
+            while let Some(previous) = tree[&current] {
+                assert!(network.graph.contains_edge(previous, current));
+                assert!(seen.insert(previous));
+                current = previous;
+            }
+            assert_eq!(current, idx("S"));
+        }
+    }
 
-        let idx_a = network.add_node(node_a);
-        let idx_b = network.add_node(node_b);
-        let idx_c = network.add_node(node_c);
+    #[test]
+    fn test_force_layout_stops_early_when_settled() {
+        let mut network = network_from_edges(&[("A", "B", 1.0)]);
+        // Two linked nodes settle exactly one ideal length, sqrt(area / nodes), apart
+        let ideal_length = (600.0_f64 * 600.0 / 2.0).sqrt();
+        network.apply_positions(&[
+            ("A".to_string(), 80.0, 300.0),
+            ("B".to_string(), 80.0 + ideal_length, 300.0),
+        ]);
+        let before = network.export_positions();
 
-        let link_ab = Link {
-            link_id: "link_ab".to_string(),
-            source_node: "A".to_string(),
-            destination_node: "B".to_string(),
-            capacity: 100,
-            weight: 4,
+        for cooling in [Cooling::Linear, Cooling::Exponential, Cooling::Quadratic] {
+            let config = LayoutConfig {
+                cooling,
+                ..LayoutConfig::default()
+            };
+            let run = network.apply_force_directed_layout(600.0, 600.0, &config, None);
+            assert_eq!(run.iterations, 1, "{:?}", cooling);
+            assert!(run.max_displacement < config.epsilon);
+        }
+        assert_eq!(network.export_positions(), before);
+
+        // Pulled apart, the layout needs more than one iteration to settle again
+        network.apply_positions(&[("B".to_string(), 150.0, 300.0)]);
+        let run = network.apply_force_directed_layout(600.0, 600.0, &LayoutConfig::default(), None);
+        assert!(run.iterations > 1);
+    }
+
+    #[test]
+    fn test_cooling_schedules() {
+        for cooling in [Cooling::Linear, Cooling::Exponential, Cooling::Quadratic] {
+            assert_eq!(cooling.temperature(60.0, 0, 100), 60.0);
+            let temperatures: Vec<f64> = (0..100)
+                .map(|iteration| cooling.temperature(60.0, iteration, 100))
+                .collect();
+            assert!(temperatures.windows(2).all(|pair| pair[1] < pair[0]));
+        }
+        assert!((Cooling::Linear.temperature(60.0, 50, 100) - 30.0).abs() < 1e-9);
+        assert!((Cooling::Exponential.temperature(60.0, 50, 100) - 6.0).abs() < 1e-9);
+        assert!((Cooling::Quadratic.temperature(60.0, 50, 100) - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_force_layout_keeps_anchored_nodes() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "D", 1.0)]);
+        network.apply_positions(&[
+            ("A".to_string(), 100.0, 100.0),
+            ("B".to_string(), 110.0, 100.0),
+            ("C".to_string(), 120.0, 100.0),
+            ("D".to_string(), 500.0, 500.0),
+        ]);
+        for id in ["A", "D"] {
+            let idx = network.node_indices[id];
+            network.graph[idx].anchored = true;
+        }
+
+        let config = LayoutConfig {
+            iterations: 50,
+            ..LayoutConfig::default()
         };
-        let link_bc = Link {
-            link_id: "link_bc".to_string(),
-            source_node: "B".to_string(),
-            destination_node: "C".to_string(),
-            capacity: 50,
-            weight: 3,
+        let run = network.apply_force_directed_layout(600.0, 600.0, &config, None);
+        assert!(run.max_displacement > 0.0);
+        let point = |id: &str| network.graph[network.node_indices[id]].point;
+        assert_eq!(point("A"), (100.0, 100.0));
+        assert_eq!(point("D"), (500.0, 500.0));
+        assert_ne!(point("B"), (110.0, 100.0));
+    }
+
+    #[test]
+    fn test_layout_steps_match_full_run() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let links: Vec<Link> = (0..10)
+            .map(|i| Link {
+                link_id: format!("link{}", i),
+                source_node: format!("N{}", i),
+                destination_node: format!("N{}", (i * 3 + 1) % 10),
+                capacity: 10,
+                weight: 1.0 + (i % 3) as f64,
+                ..Link::default()
+            })
+            .collect();
+        let mut stepped = Network::with_random_placement(
+            links,
+            (50.0, 50.0, 550.0, 350.0),
+            &mut StdRng::seed_from_u64(11),
+        )
+        .unwrap();
+        let mut full = stepped.clone();
+        let config = LayoutConfig {
+            iterations: 30,
+            epsilon: 0.0,
+            ..LayoutConfig::default()
         };
-        let link_ac = Link {
-            link_id: "link_ac".to_string(),
-            source_node: "A".to_string(),
-            destination_node: "C".to_string(),
-            capacity: 75,
-            weight: 8,
+
+        let run = full.apply_force_directed_layout(600.0, 400.0, &config, None);
+
+        let mut progress = LayoutProgress::new(600.0, 400.0, config, None);
+        let mut steps = 0;
+        while stepped.layout_step(&mut progress) {
+            steps += 1;
+            assert_eq!(progress.fraction(), steps as f32 / 30.0);
+        }
+        assert_eq!(progress.run, run);
+        assert_eq!(progress.fraction(), 1.0);
+        assert_eq!(stepped.export_positions(), full.export_positions());
+        // A finished run stays finished
+        assert!(!stepped.layout_step(&mut progress));
+        assert_eq!(progress.run.iterations, 30);
+    }
+
+    #[test]
+    fn test_layout_of_new_nodes_keeps_existing_ones() {
+        let mut network = network_from_edges(&[("A", "B", 1.0), ("B", "C", 1.0), ("C", "A", 1.0)]);
+        let config = LayoutConfig {
+            iterations: 50,
+            ..LayoutConfig::default()
         };
+        network.apply_force_directed_layout(600.0, 600.0, &config, None);
+        let settled: Vec<(f64, f64)> = network.nodes().map(|node| node.point).collect();
 
-        network.add_link(link_ab).expect("Failed to add link_ab");
-        network.add_link(link_bc).expect("Failed to add link_bc");
-        network.add_link(link_ac).expect("Failed to add link_ac");
+        // A new node dropped right on top of A
+        let a = network.node_indices["A"];
+        let new = network.add_node(Node {
+            id: "D".to_string(),
+            point: network.graph[a].point,
+            ..Node::default()
+        });
+        network
+            .add_link(Link {
+                link_id: "da".to_string(),
+                source_node: "D".to_string(),
+                destination_node: "A".to_string(),
+                capacity: 10,
+                weight: 1.0,
+                ..Link::default()
+            })
+            .unwrap();
 
-        let path = network
-            .find_shortest_path("A", "C")
-            .expect("Failed to find path");
-        let expected_path = vec![(idx_a, idx_b), (idx_b, idx_c)]; // A -> B -> C (weight 4 + 3 = 7)
-        assert_eq!(path, expected_path);
+        let run = network.apply_layout_to(600.0, 600.0, &config, &HashSet::from([new]));
+        assert!(run.iterations > 0);
+        let existing: Vec<(f64, f64)> = network.nodes().take(3).map(|node| node.point).collect();
+        assert_eq!(existing, settled);
+        assert_ne!(network.graph[new].point, network.graph[a].point);
+        // The temporary anchors are gone again
+        assert!(network.nodes().all(|node| !node.anchored));
+    }
 
-        let result = network.find_shortest_path("A", "D");
-        assert!(matches!(result, Err(NetworkError::NodeNotFound(_))));
+    #[test]
+    fn test_analysis_report() {
+        let network = network_from_edges(&[
+            ("hub", "A", 1.0),
+            ("hub", "B", 1.0),
+            ("hub", "C", 1.0),
+            ("X", "Y", 1.0),
+        ]);
+
+        let report = network.analysis_report();
+        assert_eq!(report.node_count, 6);
+        assert_eq!(report.link_count, 4);
+        assert!((report.density - 4.0 / 15.0).abs() < 1e-9);
+        assert_eq!(report.diameter, Some(2));
+        assert_eq!(report.components, 2);
+        assert_eq!(report.top_closeness.len(), 5);
+        // The hub reaches its 3 leaves in one hop, out of 5 other nodes
+        assert_eq!(report.top_closeness[0].id, "hub");
+        assert!((report.top_closeness[0].score - 0.6).abs() < 1e-9);
+        // Every path between two of the 3 leaves runs through the hub
+        assert_eq!(report.top_betweenness[0].id, "hub");
+        assert_eq!(report.top_betweenness[0].score, 3.0);
+        assert_eq!(report.top_pagerank[0].id, "hub");
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["components"], 2);
+        assert_eq!(json["top_closeness"][0]["id"], "hub");
     }
 }
@@ -0,0 +1,185 @@
+use raylib::prelude::Color;
+
+/// Node fills for community coloring, light enough for black labels in either theme.
+pub const COMMUNITY_PALETTE: [Color; 8] = [
+    Color::SKYBLUE,
+    Color::LIGHTGREEN,
+    Color::GOLD,
+    Color::PINK,
+    Color::PLUM,
+    Color::AQUAMARINE,
+    Color::SANDYBROWN,
+    Color::LIGHTSTEELBLUE,
+];
+
+/// Ends of the gradient links are colored along when colored by capacity.
+pub const CAPACITY_LOW: Color = Color::SKYBLUE;
+pub const CAPACITY_HIGH: Color = Color::RED;
+
+/// Thickness range links are drawn with when sized by capacity.
+pub const CAPACITY_THICKNESS: (f32, f32) = (1.5, 6.0);
+
+/// Where `capacity` falls within `range`, from 0.0 at the low end to 1.0 at the high end.
+/// A range of a single value maps to the middle.
+fn capacity_position(capacity: u64, (min, max): (u64, u64)) -> f32 {
+    if max <= min {
+        return 0.5;
+    }
+    (capacity.clamp(min, max) - min) as f32 / (max - min) as f32
+}
+
+/// Color for a link of `capacity` given the network's capacity range.
+pub fn capacity_color(capacity: u64, range: (u64, u64)) -> Color {
+    CAPACITY_LOW.lerp(CAPACITY_HIGH, capacity_position(capacity, range))
+}
+
+/// Line thickness for a link of `capacity` given the network's capacity range.
+pub fn capacity_thickness(capacity: u64, range: (u64, u64)) -> f32 {
+    let (thin, thick) = CAPACITY_THICKNESS;
+    thin + (thick - thin) * capacity_position(capacity, range)
+}
+
+/// Ends of the gradient the centrality heatmap is colored along, and its strongest alpha.
+pub const HEATMAP_LOW: Color = Color::BLUE;
+pub const HEATMAP_HIGH: Color = Color::RED;
+pub const HEATMAP_MAX_ALPHA: f32 = 0.35;
+
+/// Heatmap color for a centrality normalized to 0.0..=1.0: shifts from blue to red and
+/// grows more opaque as the value rises, so unimportant nodes barely tint the canvas.
+pub fn heatmap_color(intensity: f32) -> Color {
+    let intensity = intensity.clamp(0.0, 1.0);
+    Color {
+        a: (255.0 * HEATMAP_MAX_ALPHA * intensity) as u8,
+        ..HEATMAP_LOW.lerp(HEATMAP_HIGH, intensity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Every color the canvas draws with, so switching themes is a single lookup.
+#[derive(Debug, Clone, Copy)]
+pub struct ThemeColors {
+    pub background: Color,
+    pub grid: Color,
+    pub node_fill: Color,
+    pub node_text: Color,
+    pub edge: Color,
+    pub edge_label: Color,
+    /// Outline around the node the layout keeps in place.
+    pub pinned_ring: Color,
+    /// Outline around the node picked in the sidebar or by clicking.
+    pub selected_ring: Color,
+}
+
+impl Theme {
+    pub fn colors(self) -> ThemeColors {
+        match self {
+            Theme::Dark => ThemeColors {
+                background: Color::BLACK,
+                grid: Color::new(255, 255, 255, 20),
+                node_fill: Color::WHITE,
+                node_text: Color::BLACK,
+                edge: Color::WHEAT,
+                edge_label: Color::RAYWHITE,
+                pinned_ring: Color::MAGENTA,
+                selected_ring: Color::YELLOW,
+            },
+            Theme::Light => ThemeColors {
+                background: Color::RAYWHITE,
+                grid: Color::new(0, 0, 0, 20),
+                node_fill: Color::DARKSLATEGRAY,
+                node_text: Color::WHITE,
+                edge: Color::SADDLEBROWN,
+                edge_label: Color::BLACK,
+                pinned_ring: Color::MAROON,
+                selected_ring: Color::DARKBLUE,
+            },
+        }
+    }
+}
+
+impl ThemeColors {
+    /// Outline for a node in the given state, if it gets one. Selection wins over pinning
+    /// since it is what the user just did.
+    pub fn ring_color(&self, pinned: bool, selected: bool) -> Option<Color> {
+        match (pinned, selected) {
+            (_, true) => Some(self.selected_ring),
+            (true, false) => Some(self.pinned_ring),
+            (false, false) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Relative luminance in 0..=1, enough to tell light colors from dark ones.
+    fn luminance(color: Color) -> f64 {
+        (0.2126 * color.r as f64 + 0.7152 * color.g as f64 + 0.0722 * color.b as f64) / 255.0
+    }
+
+    #[test]
+    fn test_theme_colors_are_legible() {
+        for theme in [Theme::Dark, Theme::Light] {
+            let colors = theme.colors();
+            for (foreground, background) in [
+                (colors.node_fill, colors.background),
+                (colors.node_text, colors.node_fill),
+                (colors.edge, colors.background),
+                (colors.edge_label, colors.background),
+            ] {
+                let contrast = (luminance(foreground) - luminance(background)).abs();
+                assert!(contrast > 0.3, "{:?}: low contrast {}", theme, contrast);
+            }
+        }
+
+        let (dark, light) = (Theme::Dark.colors(), Theme::Light.colors());
+        assert!((luminance(dark.background) - luminance(light.background)).abs() > 0.5);
+    }
+
+    #[test]
+    fn test_ring_color_per_state() {
+        let rgb = |color: Option<Color>| color.map(|color| (color.r, color.g, color.b));
+        for theme in [Theme::Dark, Theme::Light] {
+            let colors = theme.colors();
+            assert_eq!(rgb(colors.ring_color(false, false)), None);
+            assert_eq!(
+                rgb(colors.ring_color(true, false)),
+                rgb(Some(colors.pinned_ring))
+            );
+            assert_eq!(
+                rgb(colors.ring_color(false, true)),
+                rgb(Some(colors.selected_ring))
+            );
+            assert_eq!(
+                rgb(colors.ring_color(true, true)),
+                rgb(Some(colors.selected_ring))
+            );
+            assert_ne!(
+                rgb(Some(colors.pinned_ring)),
+                rgb(Some(colors.selected_ring))
+            );
+        }
+    }
+
+    #[test]
+    fn test_heatmap_color_ramps_with_intensity() {
+        let low = heatmap_color(0.0);
+        let mid = heatmap_color(0.5);
+        let high = heatmap_color(1.0);
+        assert_eq!(low.a, 0);
+        assert!(low.a < mid.a && mid.a < high.a);
+        assert_eq!(high.a, (255.0 * HEATMAP_MAX_ALPHA) as u8);
+        assert!(low.b > low.r && high.r > high.b);
+
+        // Out of range values are clamped
+        assert_eq!(heatmap_color(-1.0).a, low.a);
+        assert_eq!(heatmap_color(2.0).a, high.a);
+    }
+}
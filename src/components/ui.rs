@@ -1,12 +1,275 @@
+use imgui::{Condition, Drag, SliderFlags, Ui};
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
 use raylib::{imgui::RayImGUITrait, prelude::RaylibDrawHandle};
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
-use crate::{app::AppMsg, components::network::Network};
+use crate::{
+    app::AppMsg,
+    components::{
+        commands::parse_command,
+        geometry::{GRID_SIZE, inset_area},
+        network::{CombinePolicy, Cooling, LoadReport, NODE_RADIUS, Network, format_weight},
+        theme::Theme,
+    },
+};
 
-#[derive(Debug, Default)]
+/// Initial width of the sidebar; the user can drag its right edge to resize it.
+pub const DEFAULT_SIDEBAR_WIDTH: f32 = 250.0;
+
+/// Line width of a plain link until the user changes it.
+pub const DEFAULT_EDGE_THICKNESS: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelKind {
+    Edge,
+    Node,
+}
+
+/// How links without a parallel twin are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EdgeStyle {
+    Straight,
+    /// raylib's eased S-curve between the two ends.
+    #[default]
+    Bezier,
+}
+
+impl EdgeStyle {
+    /// Point `t` (0..=1) of the way along a link from `start` to `end` drawn in this style.
+    pub fn point_at(self, start: (f64, f64), end: (f64, f64), t: f64) -> (f64, f64) {
+        let eased = match self {
+            EdgeStyle::Straight => t,
+            // Same easing as raylib's DrawLineBezier applies to the y axis
+            EdgeStyle::Bezier if t < 0.5 => 4.0 * t.powi(3),
+            EdgeStyle::Bezier => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        };
+        (
+            start.0 + (end.0 - start.0) * t,
+            start.1 + (end.1 - start.1) * eased,
+        )
+    }
+}
+
+#[derive(Debug)]
 pub struct UiState {
     selected_start_index: usize,
     selected_end_index: usize,
+    tree_root_index: usize,
+    pub selected_nodes: HashSet<NodeIndex>,
+    pub selected_node: Option<NodeIndex>,
+    /// Node the force layout leaves where it is.
+    pub pinned_node: Option<NodeIndex>,
+    /// Re-run the weighted layout whenever a link weight is edited.
+    pub live_layout: bool,
+    /// Cooling schedule of the force layout.
+    pub layout_cooling: Cooling,
+    /// How "Simplify" combines the weights of parallel links.
+    simplify_weight: CombinePolicy,
+    /// Skip automatic layout passes so nodes can be placed by hand.
+    pub freeze_layout: bool,
+    import_path: String,
+    png_path: String,
+    layout_json_path: String,
+    routes_path: String,
+    report_path: String,
+    session_path: String,
+    selection_path: String,
+    pub node_radius: f64,
+    /// Line width of a plain link; highlighted links are drawn proportionally thicker.
+    pub edge_thickness: f32,
+    /// World units between background grid lines, also the snapping step.
+    pub grid_spacing: f64,
+    /// Width of the tools window, which the canvas starts right of while it is docked.
+    pub sidebar_width: f32,
+    dock_tools: bool,
+    /// Set while imgui wants the mouse, e.g. over a window floating above the canvas.
+    pub mouse_over_ui: bool,
+    /// Set while imgui wants keyboard input, e.g. while a text field has focus.
+    pub keyboard_over_ui: bool,
+    show_analysis: bool,
+    show_inspector: bool,
+    pub path_latency: Option<f64>,
+    /// How far the startup layout has got, while it is still running.
+    pub layout_progress: Option<f32>,
+    pub startup_error: Option<String>,
+    /// URL of the network being downloaded, while the download runs.
+    pub downloading: Option<String>,
+    /// Why the last download failed, until another one starts.
+    pub download_error: Option<String>,
+    pub theme: Theme,
+    pub edge_style: EdgeStyle,
+    pub import_preview: Option<LoadReport>,
+    pub max_flow: Option<u64>,
+    /// Outcome of the last signed-cost path search: its cost or why it failed.
+    pub path_status: Option<String>,
+    pub isolated: bool,
+    pub reachable_count: Option<usize>,
+    pub showing_path_tree: bool,
+    ego_radius: i32,
+    random_nodes: i32,
+    random_probability: f32,
+    random_attachments: i32,
+    pub packet_speed: f32,
+    command: String,
+    command_error: Option<String>,
+    k_paths: i32,
+    pub alternative_paths: Vec<(Vec<NodeIndex>, f64)>,
+    pub alternative_path_index: usize,
+    new_node_id: String,
+    new_node_error: Option<String>,
+    show_edge_labels: bool,
+    show_node_labels: bool,
+    /// Append each node's degree to its label.
+    show_degrees: bool,
+    snap_to_grid: bool,
+    /// Let dragged nodes leave the visible canvas instead of stopping at its margin.
+    free_placement: bool,
+    show_grid: bool,
+    color_communities: bool,
+    show_heatmap: bool,
+    color_by_capacity: bool,
+    show_arrows: bool,
+    /// Draw nearly parallel links merged into bundles. Costly on large networks, so off by
+    /// default.
+    bundle_edges: bool,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        UiState {
+            selected_start_index: 0,
+            selected_end_index: 0,
+            tree_root_index: 0,
+            selected_nodes: HashSet::new(),
+            selected_node: None,
+            pinned_node: None,
+            live_layout: true,
+            layout_cooling: Cooling::default(),
+            simplify_weight: CombinePolicy::default(),
+            freeze_layout: false,
+            import_path: String::new(),
+            png_path: "network.png".to_string(),
+            layout_json_path: "positions.json".to_string(),
+            routes_path: "routes.csv".to_string(),
+            report_path: "report.json".to_string(),
+            session_path: "session.json".to_string(),
+            selection_path: "selection.csv".to_string(),
+            node_radius: NODE_RADIUS,
+            edge_thickness: DEFAULT_EDGE_THICKNESS,
+            grid_spacing: GRID_SIZE,
+            sidebar_width: DEFAULT_SIDEBAR_WIDTH,
+            dock_tools: true,
+            mouse_over_ui: false,
+            keyboard_over_ui: false,
+            show_analysis: true,
+            show_inspector: true,
+            path_latency: None,
+            layout_progress: None,
+            startup_error: None,
+            downloading: None,
+            download_error: None,
+            command: String::new(),
+            command_error: None,
+            theme: Theme::default(),
+            edge_style: EdgeStyle::default(),
+            import_preview: None,
+            max_flow: None,
+            path_status: None,
+            isolated: false,
+            reachable_count: None,
+            showing_path_tree: false,
+            ego_radius: 1,
+            random_nodes: 20,
+            random_probability: 0.15,
+            random_attachments: 2,
+            packet_speed: 150.0,
+            k_paths: 3,
+            alternative_paths: Vec::new(),
+            alternative_path_index: 0,
+            new_node_id: String::new(),
+            new_node_error: None,
+            show_edge_labels: true,
+            show_node_labels: true,
+            show_degrees: false,
+            snap_to_grid: false,
+            free_placement: false,
+            show_grid: false,
+            color_communities: false,
+            show_heatmap: false,
+            color_by_capacity: false,
+            show_arrows: false,
+            bundle_edges: false,
+        }
+    }
+}
+
+impl UiState {
+    /// Whether the canvas should draw text labels of the given kind.
+    pub fn shows_label(&self, kind: LabelKind) -> bool {
+        match kind {
+            LabelKind::Edge => self.show_edge_labels,
+            LabelKind::Node => self.show_node_labels,
+        }
+    }
+
+    /// Whether node labels end with the node's degree, like `A (3)`.
+    pub fn shows_degrees(&self) -> bool {
+        self.show_degrees
+    }
+
+    /// Whether nodes are filled by their detected community instead of the theme color.
+    pub fn colors_communities(&self) -> bool {
+        self.color_communities
+    }
+
+    /// Whether the canvas behind nodes is tinted by their closeness centrality.
+    pub fn shows_heatmap(&self) -> bool {
+        self.show_heatmap
+    }
+
+    /// Whether links are colored and sized by their capacity, with a legend on the canvas.
+    pub fn colors_by_capacity(&self) -> bool {
+        self.color_by_capacity
+    }
+
+    /// Whether links get an arrowhead pointing from their source to their destination.
+    pub fn shows_arrows(&self) -> bool {
+        self.show_arrows
+    }
+
+    /// Whether links are drawn as force-directed bundles instead of straight lines.
+    pub fn bundles_edges(&self) -> bool {
+        self.bundle_edges
+    }
+
+    /// Whether dragged nodes should snap to the canvas grid.
+    pub fn snaps_to_grid(&self) -> bool {
+        self.snap_to_grid
+    }
+
+    /// Whether dragged nodes are kept inside the visible canvas.
+    pub fn clamps_drag(&self) -> bool {
+        !self.free_placement
+    }
+
+    /// Whether the background grid and origin marker are drawn. Snapping always shows the
+    /// grid it snaps to.
+    pub fn shows_grid(&self) -> bool {
+        self.show_grid || self.snap_to_grid
+    }
+
+    /// Screen x where the canvas starts: right of the tools window while it is docked to the
+    /// left edge, the left edge itself while it floats over the canvas.
+    pub fn canvas_offset(&self) -> i32 {
+        if self.dock_tools {
+            self.sidebar_width.round() as i32
+        } else {
+            0
+        }
+    }
 }
 
 pub fn init_ui(
@@ -14,52 +277,753 @@ pub fn init_ui(
     message_queue: &mut VecDeque<AppMsg>,
     network: &Network,
     ui_state: &mut UiState,
+    viewport: (f64, f64, f64, f64),
+    hovered: (Option<NodeIndex>, Option<EdgeIndex>),
 ) {
-    if let Some(ui) = rhandle.begin_imgui() {
-        if let Some(win) = ui
-            .window("Net Modeler")
-            .size([250.0, 600.0], ::imgui::Condition::Always)
-            .position([0.0, 0.0], ::imgui::Condition::Always)
-            .movable(false)
-            .resizable(false)
-            .collapsible(false)
-            .begin()
-        {
-            ui.text("Network Tool");
-            ui.separator();
+    let Some(ui) = rhandle.begin_imgui() else {
+        return;
+    };
+    let screen_size = [
+        rhandle.get_screen_width() as f32,
+        rhandle.get_screen_height() as f32,
+    ];
 
-            ui.button("Add Node").then(|| {
-                let x = rand::random_range(50..750) as f64 + 200.0;
-                let y = rand::random_range(50..750) as f64;
-                message_queue.push_back(AppMsg::AddPoint((x, y)));
-            });
+    match hovered {
+        (Some(node_idx), _) => {
+            if let Some(node) = network.graph.node_weight(node_idx) {
+                ui.tooltip_text(format!(
+                    "{}\ndegree {}\n({:.0}, {:.0})",
+                    node.id,
+                    network.degree(node_idx),
+                    node.point.0,
+                    node.point.1
+                ));
+            }
+        }
+        (None, Some(edge)) => {
+            if let Some(link) = network.graph.edge_weight(edge) {
+                ui.tooltip_text(format!(
+                    "link {}\nweight {}\ncapacity {}",
+                    link.link_id,
+                    format_weight(link.weight),
+                    link.capacity
+                ));
+            }
+        }
+        (None, None) => {}
+    }
 
-            ui.separator();
-            ui.text("Shortest path");
-            let mut node_ids: Vec<String> = network.node_indices.keys().cloned().collect();
-            node_ids.sort();
+    draw_tools_window(&ui, message_queue, network, ui_state, viewport, screen_size);
+    draw_analysis_window(&ui, message_queue, network, ui_state);
+    draw_inspector_window(&ui, message_queue, network, ui_state, screen_size);
+    ui_state.mouse_over_ui = ui.io().want_capture_mouse;
+    ui_state.keyboard_over_ui = ui.io().want_capture_keyboard;
+}
 
-            ui.combo(
-                "select start node",
-                &mut ui_state.selected_start_index,
-                &node_ids,
-                |node| std::borrow::Cow::Borrowed(node.as_str()),
-            );
+/// Editing, display and import/export controls. Docked, it fills the left edge of the
+/// screen and only its width can change; undocked, it floats like the other windows.
+fn draw_tools_window(
+    ui: &Ui,
+    message_queue: &mut VecDeque<AppMsg>,
+    network: &Network,
+    ui_state: &mut UiState,
+    viewport: (f64, f64, f64, f64),
+    screen_size: [f32; 2],
+) {
+    let docked = ui_state.dock_tools;
+    let mut window = ui
+        .window("Tools")
+        .size_constraints([180.0, 0.0], [500.0, f32::MAX])
+        .movable(!docked)
+        .resizable(true)
+        .collapsible(!docked);
+    if docked {
+        window = window
+            .size([ui_state.sidebar_width, screen_size[1]], Condition::Always)
+            .position([0.0, 0.0], Condition::Always);
+    }
+    let Some(win) = window.begin() else {
+        return;
+    };
+    if docked {
+        // Only the width can change; the height always follows the window
+        ui_state.sidebar_width = ui.window_size()[0];
+    }
 
-            ui.combo(
-                "select end node",
-                &mut ui_state.selected_end_index,
-                &node_ids,
-                |node| std::borrow::Cow::Borrowed(node.as_str()),
-            );
+    ui.text("Network Tool");
+    if ui_state.isolated {
+        if ui.button("Back to full") {
+            message_queue.push_back(AppMsg::ShowFullNetwork);
+        }
+        ui.text_wrapped("Edits are kept, but saves and snapshots only cover this neighbourhood.");
+    }
+    if let Some(error) = &ui_state.startup_error {
+        ui.text_colored([1.0, 0.6, 0.2, 1.0], error);
+    }
+    if let Some(fraction) = ui_state.layout_progress {
+        ui.progress_bar(fraction)
+            .overlay_text("Laying out...")
+            .build();
+    }
+    if let Some(url) = &ui_state.downloading {
+        ui.text_wrapped(format!("Downloading {}...", url));
+    }
+    if let Some(error) = &ui_state.download_error {
+        ui.text_colored([1.0, 0.6, 0.2, 1.0], error);
+    }
+    ui.checkbox("dock", &mut ui_state.dock_tools);
+    ui.same_line();
+    ui.checkbox("analysis", &mut ui_state.show_analysis);
+    ui.same_line();
+    ui.checkbox("inspector", &mut ui_state.show_inspector);
+    ui.separator();
 
-            if ui.button("Shortest Path") && !node_ids.is_empty() {
-                let start_id = node_ids[ui_state.selected_start_index].clone();
-                let end_id = node_ids[ui_state.selected_end_index].clone();
-                message_queue.push_back(AppMsg::ComputeShortestPath(start_id, end_id));
+    if ui
+        .input_text("command", &mut ui_state.command)
+        .enter_returns_true(true)
+        .build()
+    {
+        match parse_command(&ui_state.command) {
+            Ok(msg) => {
+                message_queue.push_back(msg);
+                ui_state.command.clear();
+                ui_state.command_error = None;
             }
+            Err(err) => ui_state.command_error = Some(err),
+        }
+    }
+    if let Some(error) = &ui_state.command_error {
+        ui.text_colored([1.0, 0.2, 0.2, 1.0], error);
+    }
+
+    ui.button("Add Node").then(|| {
+        // Keep new nodes inside the visible part of the canvas
+        let (min_x, min_y, max_x, max_y) = inset_area(viewport, 50.0);
+        let x = rand::random_range(min_x..=max_x);
+        let y = rand::random_range(min_y..=max_y);
+        message_queue.push_back(AppMsg::AddPoint((x, y)));
+    });
 
-            win.end();
+    ui.input_text("node id", &mut ui_state.new_node_id).build();
+    if ui.button("Add Node with ID") {
+        let id = ui_state.new_node_id.trim();
+        if id.is_empty() {
+            ui_state.new_node_error = Some("Node id cannot be empty".to_string());
+        } else if network.node_indices.contains_key(id) {
+            ui_state.new_node_error = Some(format!("Node '{}' already exists", id));
+        } else {
+            let center = (
+                (viewport.0 + viewport.2) / 2.0,
+                (viewport.1 + viewport.3) / 2.0,
+            );
+            message_queue.push_back(AppMsg::AddNamedNode(id.to_string(), center));
+            ui_state.new_node_id.clear();
+            ui_state.new_node_error = None;
         }
     }
+    if let Some(error) = &ui_state.new_node_error {
+        ui.text_colored([1.0, 0.2, 0.2, 1.0], error);
+    }
+
+    ui.button("Snapshot layout")
+        .then(|| message_queue.push_back(AppMsg::SnapshotLayout));
+    ui.same_line();
+    ui.button("Restore layout")
+        .then(|| message_queue.push_back(AppMsg::RestoreLayout));
+    ui.same_line();
+    ui.button("Round positions")
+        .then(|| message_queue.push_back(AppMsg::RoundPositions));
+    ui.button("Fit")
+        .then(|| message_queue.push_back(AppMsg::FitToScreen));
+    ui.same_line();
+    ui.button("Relayout")
+        .then(|| message_queue.push_back(AppMsg::RunLayout));
+    ui.same_line();
+    ui.button("Place new")
+        .then(|| message_queue.push_back(AppMsg::LayoutNewNodes));
+    ui.same_line();
+    ui.button("Spectral")
+        .then(|| message_queue.push_back(AppMsg::SpectralLayout));
+    ui.same_line();
+    if ui.checkbox("freeze layout", &mut ui_state.freeze_layout) {
+        message_queue.push_back(AppMsg::SaveSettings);
+    }
+    ui.text("cooling");
+    ui.same_line();
+    ui.radio_button("linear", &mut ui_state.layout_cooling, Cooling::Linear);
+    ui.same_line();
+    ui.radio_button("exp", &mut ui_state.layout_cooling, Cooling::Exponential);
+    ui.same_line();
+    ui.radio_button(
+        "quadratic",
+        &mut ui_state.layout_cooling,
+        Cooling::Quadratic,
+    );
+    if network.has_coordinates() {
+        ui.same_line();
+        ui.button("Geo layout")
+            .then(|| message_queue.push_back(AppMsg::GeographicLayout));
+    }
+    ui.slider("node size", 8.0, 40.0, &mut ui_state.node_radius);
+    ui.slider("edge thickness", 1.0, 8.0, &mut ui_state.edge_thickness);
+    ui.checkbox("edge labels", &mut ui_state.show_edge_labels);
+    ui.same_line();
+    ui.checkbox("node labels", &mut ui_state.show_node_labels);
+    ui.same_line();
+    ui.checkbox("degrees", &mut ui_state.show_degrees);
+    ui.checkbox("grid", &mut ui_state.show_grid);
+    ui.same_line();
+    ui.checkbox("snap to grid", &mut ui_state.snap_to_grid);
+    ui.same_line();
+    ui.checkbox("arrows", &mut ui_state.show_arrows);
+    ui.checkbox("free placement", &mut ui_state.free_placement);
+    ui.same_line();
+    ui.checkbox("bundle edges", &mut ui_state.bundle_edges);
+    if ui_state.shows_grid() {
+        ui.slider("grid spacing", 5.0, 200.0, &mut ui_state.grid_spacing);
+    }
+    ui.checkbox("color communities", &mut ui_state.color_communities);
+    ui.same_line();
+    ui.checkbox("centrality heatmap", &mut ui_state.show_heatmap);
+    ui.checkbox("color links by capacity", &mut ui_state.color_by_capacity);
+    ui.radio_button("dark", &mut ui_state.theme, Theme::Dark);
+    ui.same_line();
+    ui.radio_button("light", &mut ui_state.theme, Theme::Light);
+    ui.radio_button("straight", &mut ui_state.edge_style, EdgeStyle::Straight);
+    ui.same_line();
+    ui.radio_button("curved", &mut ui_state.edge_style, EdgeStyle::Bezier);
+
+    ui.input_text("import path", &mut ui_state.import_path)
+        .build();
+    if ui.button("Load matrix") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadAdjacencyMatrix(ui_state.import_path.clone()));
+    }
+    ui.same_line();
+    if ui.button("Load D3 JSON") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadD3Json(ui_state.import_path.clone()));
+    }
+    ui.same_line();
+    if ui.button("Load edge list") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadEdgeList(ui_state.import_path.clone()));
+    }
+    ui.same_line();
+    if ui.button("Load URL") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadUrl(ui_state.import_path.trim().to_string()));
+    }
+    if ui.button("Load GEXF") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadGexf(ui_state.import_path.clone()));
+    }
+    ui.same_line();
+    // Several link CSVs can be merged by separating their paths with commas
+    if ui.button("Load CSVs") && !ui_state.import_path.is_empty() {
+        let paths = ui_state
+            .import_path
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect();
+        message_queue.push_back(AppMsg::LoadNetworks(paths));
+    }
+    ui.same_line();
+    if ui.button("Preview CSV") && !ui_state.import_path.is_empty() {
+        message_queue.push_back(AppMsg::PreviewImport(
+            ui_state.import_path.trim().to_string(),
+        ));
+    }
+    if let Some(report) = &ui_state.import_preview {
+        ui.open_popup("Import preview");
+        ui.modal_popup_config("Import preview")
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(format!("New nodes: {}", report.new_nodes));
+                ui.text(format!("New links: {}", report.new_edges));
+                ui.text(format!(
+                    "Duplicate links (skipped): {}",
+                    report.duplicate_edges
+                ));
+                ui.text(format!(
+                    "Links to unknown nodes: {}",
+                    report.dangling_references
+                ));
+                if ui.button("Apply") {
+                    message_queue.push_back(AppMsg::ApplyImport);
+                    ui.close_current_popup();
+                }
+                ui.same_line();
+                if ui.button("Cancel") {
+                    message_queue.push_back(AppMsg::CancelImport);
+                    ui.close_current_popup();
+                }
+            });
+    }
+
+    ui.input_text("png path", &mut ui_state.png_path).build();
+    if ui.button("Save PNG") && !ui_state.png_path.is_empty() {
+        message_queue.push_back(AppMsg::SavePng(ui_state.png_path.clone()));
+    }
+    ui.input_text("positions json", &mut ui_state.layout_json_path)
+        .build();
+    if ui.button("Export positions") && !ui_state.layout_json_path.is_empty() {
+        message_queue.push_back(AppMsg::ExportLayoutJson(ui_state.layout_json_path.clone()));
+    }
+    ui.input_text("routes csv", &mut ui_state.routes_path)
+        .build();
+    if ui.button("Export routes") && !ui_state.routes_path.is_empty() {
+        message_queue.push_back(AppMsg::ExportRoutingTables(ui_state.routes_path.clone()));
+    }
+    ui.input_text("report json", &mut ui_state.report_path)
+        .build();
+    if ui.button("Export report") && !ui_state.report_path.is_empty() {
+        message_queue.push_back(AppMsg::ExportReport(ui_state.report_path.clone()));
+    }
+    ui.input_text("session json", &mut ui_state.session_path)
+        .build();
+    if ui.button("Save session") && !ui_state.session_path.is_empty() {
+        message_queue.push_back(AppMsg::SaveSession(ui_state.session_path.clone()));
+    }
+    ui.same_line();
+    if ui.button("Load session") && !ui_state.session_path.is_empty() {
+        message_queue.push_back(AppMsg::LoadSession(ui_state.session_path.clone()));
+    }
+    if !ui_state.selected_nodes.is_empty() {
+        ui.input_text("selection csv", &mut ui_state.selection_path)
+            .build();
+        if ui.button("Export selection") && !ui_state.selection_path.is_empty() {
+            message_queue.push_back(AppMsg::ExportSelection(ui_state.selection_path.clone()));
+        }
+    }
+
+    ui.separator();
+    if ui.button("Simplify") {
+        message_queue.push_back(AppMsg::Simplify(ui_state.simplify_weight));
+    }
+    ui.same_line();
+    ui.radio_button(
+        "min weight",
+        &mut ui_state.simplify_weight,
+        CombinePolicy::Min,
+    );
+    ui.same_line();
+    ui.radio_button(
+        "avg weight",
+        &mut ui_state.simplify_weight,
+        CombinePolicy::Average,
+    );
+
+    ui.separator();
+    ui.text("Random network");
+    ui.input_int("n", &mut ui_state.random_nodes).build();
+    ui_state.random_nodes = ui_state.random_nodes.clamp(1, 200);
+    ui.slider("p", 0.0, 1.0, &mut ui_state.random_probability);
+    if ui.button("Generate random") {
+        message_queue.push_back(AppMsg::GenerateErdosRenyi(
+            ui_state.random_nodes as usize,
+            ui_state.random_probability as f64,
+        ));
+    }
+    ui.input_int("m", &mut ui_state.random_attachments).build();
+    ui_state.random_attachments = ui_state.random_attachments.clamp(1, 10);
+    if ui.button("Generate scale-free") {
+        message_queue.push_back(AppMsg::GenerateBarabasiAlbert(
+            ui_state.random_nodes as usize,
+            ui_state.random_attachments as usize,
+        ));
+    }
+
+    win.end();
+}
+
+/// Path finding, layouts that need a root and network statistics, in a closable window.
+fn draw_analysis_window(
+    ui: &Ui,
+    message_queue: &mut VecDeque<AppMsg>,
+    network: &Network,
+    ui_state: &mut UiState,
+) {
+    if !ui_state.show_analysis {
+        return;
+    }
+    let Some(win) = ui
+        .window("Analysis")
+        .opened(&mut ui_state.show_analysis)
+        .position(
+            [DEFAULT_SIDEBAR_WIDTH + 10.0, 10.0],
+            Condition::FirstUseEver,
+        )
+        .size([300.0, 500.0], Condition::FirstUseEver)
+        .begin()
+    else {
+        return;
+    };
+
+    ui.text("Shortest path");
+    let mut node_ids: Vec<String> = network.node_indices.keys().cloned().collect();
+    node_ids.sort();
+
+    ui.combo(
+        "select start node",
+        &mut ui_state.selected_start_index,
+        &node_ids,
+        |node| std::borrow::Cow::Borrowed(node.as_str()),
+    );
+
+    ui.combo(
+        "select end node",
+        &mut ui_state.selected_end_index,
+        &node_ids,
+        |node| std::borrow::Cow::Borrowed(node.as_str()),
+    );
+
+    if ui.button("Shortest Path")
+        && let (Some(start_id), Some(end_id)) = (
+            node_ids.get(ui_state.selected_start_index),
+            node_ids.get(ui_state.selected_end_index),
+        )
+    {
+        message_queue.push_back(AppMsg::ComputeShortestPath(
+            start_id.clone(),
+            end_id.clone(),
+        ));
+    }
+    ui.same_line();
+    if ui.button("Widest Path")
+        && let (Some(start_id), Some(end_id)) = (
+            node_ids.get(ui_state.selected_start_index),
+            node_ids.get(ui_state.selected_end_index),
+        )
+    {
+        message_queue.push_back(AppMsg::ComputeWidestPath(start_id.clone(), end_id.clone()));
+    }
+    if ui.button("Reachable")
+        && let Some(start_id) = node_ids.get(ui_state.selected_start_index)
+    {
+        message_queue.push_back(AppMsg::ShowReachable(start_id.clone()));
+    }
+    if let Some(count) = ui_state.reachable_count {
+        ui.same_line();
+        ui.text(format!("{}/{} nodes", count, node_ids.len()));
+        ui.same_line();
+        if ui.button("Clear") {
+            message_queue.push_back(AppMsg::ClearReachable);
+        }
+    }
+    ui.same_line();
+    if ui.button("Bellman-Ford")
+        && let (Some(start_id), Some(end_id)) = (
+            node_ids.get(ui_state.selected_start_index),
+            node_ids.get(ui_state.selected_end_index),
+        )
+    {
+        message_queue.push_back(AppMsg::ComputeBellmanFord(start_id.clone(), end_id.clone()));
+    }
+    if let Some(status) = &ui_state.path_status {
+        ui.text(status);
+    }
+    if ui.button("Path tree")
+        && let Some(start_id) = node_ids.get(ui_state.selected_start_index)
+    {
+        message_queue.push_back(AppMsg::ShowPathTree(start_id.clone()));
+    }
+    if ui_state.showing_path_tree {
+        ui.same_line();
+        if ui.button("Clear tree") {
+            message_queue.push_back(AppMsg::ClearPathTree);
+        }
+    }
+    ui.input_int("k", &mut ui_state.k_paths).build();
+    ui_state.k_paths = ui_state.k_paths.clamp(1, 20);
+    if ui.button("K Shortest Paths")
+        && let (Some(start_id), Some(end_id)) = (
+            node_ids.get(ui_state.selected_start_index),
+            node_ids.get(ui_state.selected_end_index),
+        )
+    {
+        message_queue.push_back(AppMsg::ComputeKShortestPaths(
+            start_id.clone(),
+            end_id.clone(),
+            ui_state.k_paths as usize,
+        ));
+    }
+    let path_count = ui_state.alternative_paths.len();
+    if let Some((_, cost)) = ui_state
+        .alternative_paths
+        .get(ui_state.alternative_path_index)
+    {
+        let index = ui_state.alternative_path_index;
+        if ui.small_button("<") {
+            let previous = (index + path_count - 1) % path_count;
+            message_queue.push_back(AppMsg::ShowAlternativePath(previous));
+        }
+        ui.same_line();
+        if ui.small_button(">") {
+            message_queue.push_back(AppMsg::ShowAlternativePath((index + 1) % path_count));
+        }
+        ui.same_line();
+        ui.text(format!(
+            "Path {}/{} (cost {})",
+            index + 1,
+            path_count,
+            format_weight(*cost)
+        ));
+    }
+    if let Some(latency) = ui_state.path_latency {
+        ui.text(format!("Total latency: {:.1} ms", latency));
+        if ui.button("Play") {
+            message_queue.push_back(AppMsg::PlayPacket);
+        }
+        ui.same_line();
+        ui.slider("px/s", 20.0, 600.0, &mut ui_state.packet_speed);
+    }
+    if ui.button("Max Flow")
+        && let (Some(start_id), Some(end_id)) = (
+            node_ids.get(ui_state.selected_start_index),
+            node_ids.get(ui_state.selected_end_index),
+        )
+    {
+        message_queue.push_back(AppMsg::ComputeMaxFlow(start_id.clone(), end_id.clone()));
+    }
+    if let Some(flow) = ui_state.max_flow {
+        ui.same_line();
+        ui.text(format!("Max flow: {}", flow));
+    }
+
+    ui.separator();
+    ui.text("Tree layout");
+    ui.combo(
+        "tree root",
+        &mut ui_state.tree_root_index,
+        &node_ids,
+        |node| std::borrow::Cow::Borrowed(node.as_str()),
+    );
+    if ui.button("Tree Layout")
+        && let Some(root_id) = node_ids.get(ui_state.tree_root_index)
+        && let Some(&root) = network.node_indices.get(root_id)
+    {
+        message_queue.push_back(AppMsg::TreeLayout(root));
+    }
+
+    ui.separator();
+    ui.text("Statistics");
+    match network.diameter() {
+        Some(diameter) => ui.text(format!("Diameter: {} hops", diameter)),
+        None => ui.text("Diameter: n/a"),
+    }
+    match network.average_path_length() {
+        Some(length) => ui.text(format!("Avg path length: {:.2} hops", length)),
+        None => ui.text("Avg path length: n/a"),
+    }
+    ui.text(format!("Density: {:.3}", network.density()));
+    let capacity = network.capacity_stats();
+    ui.text(format!(
+        "Total capacity: {} (avg {:.1})",
+        capacity.total_capacity, capacity.average_capacity
+    ));
+    ui.text(format!(
+        "Total weight: {}",
+        format_weight(capacity.total_weight)
+    ));
+    if let Some(link_id) = &capacity.max_capacity_link {
+        ui.text(format!("Max capacity link: {}", link_id));
+    }
+    let histogram: Vec<f32> = network
+        .weight_histogram(10)
+        .into_iter()
+        .map(|count| count as f32)
+        .collect();
+    ui.plot_histogram("Weights", &histogram)
+        .graph_size([0.0, 60.0])
+        .build();
+    match network.degree_assortativity() {
+        Some(assortativity) => ui.text(format!("Assortativity: {:.3}", assortativity)),
+        None => ui.text("Assortativity: n/a"),
+    }
+    let top_ranked = network
+        .pagerank(0.85, 30)
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((idx, rank)) = top_ranked {
+        ui.text(format!(
+            "Top PageRank: {} ({:.3})",
+            network.graph[idx].id, rank
+        ));
+    }
+    let most_central = network
+        .closeness_centrality()
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((idx, closeness)) = most_central {
+        ui.text(format!(
+            "Top closeness: {} ({:.3})",
+            network.graph[idx].id, closeness
+        ));
+    }
+    let articulation_points = network.articulation_points();
+    if articulation_points.is_empty() {
+        ui.text("Articulation points: none");
+    } else {
+        let ids: Vec<&str> = articulation_points
+            .iter()
+            .map(|&idx| network.graph[idx].id.as_str())
+            .collect();
+        ui.text_wrapped(format!("Articulation points: {}", ids.join(", ")));
+    }
+    ui.text(format!("Bridges: {}", network.bridges().len()));
+    let communities: HashSet<usize> = network.louvain_communities().into_values().collect();
+    ui.text(format!("Communities: {}", communities.len()));
+
+    win.end();
+}
+
+/// Neighbors and per-link controls of the selected node, in a closable window that only
+/// appears while a node is selected.
+fn draw_inspector_window(
+    ui: &Ui,
+    message_queue: &mut VecDeque<AppMsg>,
+    network: &Network,
+    ui_state: &mut UiState,
+    screen_size: [f32; 2],
+) {
+    if !ui_state.show_inspector {
+        return;
+    }
+    let Some(selected_idx) = ui_state.selected_node else {
+        return;
+    };
+    let Some(selected) = network.graph.node_weight(selected_idx) else {
+        return;
+    };
+    let Some(win) = ui
+        .window("Inspector")
+        .opened(&mut ui_state.show_inspector)
+        .position([screen_size[0] - 290.0, 10.0], Condition::FirstUseEver)
+        .size([280.0, 320.0], Condition::FirstUseEver)
+        .begin()
+    else {
+        return;
+    };
+
+    if !selected.metadata.is_empty() {
+        let mut attributes: Vec<(&String, &String)> = selected.metadata.iter().collect();
+        attributes.sort();
+        for (key, value) in attributes {
+            ui.text(format!("{}: {}", key, value));
+        }
+        ui.separator();
+    }
+    ui.text(format!("Neighbors of {}", selected.id));
+
+    let mut pinned = ui_state.pinned_node == Some(selected_idx);
+    if ui.checkbox("pinned", &mut pinned) {
+        message_queue.push_back(AppMsg::TogglePin(selected_idx));
+    }
+    ui.same_line();
+    let mut anchored = selected.anchored;
+    if ui.checkbox("anchored", &mut anchored) {
+        message_queue.push_back(AppMsg::ToggleAnchor(selected_idx));
+    }
+    ui.checkbox("live layout", &mut ui_state.live_layout);
+    ui.input_int("hops", &mut ui_state.ego_radius).build();
+    ui_state.ego_radius = ui_state.ego_radius.clamp(1, 10);
+    if ui.button("Isolate") {
+        message_queue.push_back(AppMsg::Isolate(selected_idx, ui_state.ego_radius as usize));
+    }
+    ui.same_line();
+    if ui.button("Duplicate") {
+        message_queue.push_back(AppMsg::DuplicateNode(selected_idx));
+    }
+    ui.same_line();
+    if ui.button("Collapse community") {
+        message_queue.push_back(AppMsg::CollapseCommunity(selected_idx));
+    }
+
+    // Act on and key the widgets by the edge shown rather than its id, since link ids need
+    // not be unique
+    for edge_ref in network.graph.edges(selected_idx) {
+        let (edge, link) = (edge_ref.id(), edge_ref.weight());
+        let neighbor_idx = if edge_ref.source() == selected_idx {
+            edge_ref.target()
+        } else {
+            edge_ref.source()
+        };
+        let label = format!(
+            "{} (weight {}, capacity {})##{}",
+            network.graph[neighbor_idx].id,
+            format_weight(link.weight),
+            link.capacity,
+            edge.index()
+        );
+        if ui.selectable(label) {
+            message_queue.push_back(AppMsg::SelectNode(neighbor_idx));
+        }
+
+        // Weights have no upper bound, so drag the value rather than slide it over a range
+        let mut weight = link.weight;
+        if Drag::new(format!("weight##{}", edge.index()))
+            .range(0.0, f64::MAX)
+            .speed(0.1)
+            .display_format("%g")
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut weight)
+        {
+            message_queue.push_back(AppMsg::SetLinkWeight(edge, weight));
+        }
+        if ui.small_button(format!("Remove link##{}", edge.index())) {
+            message_queue.push_back(AppMsg::DeleteLink(edge));
+        }
+        ui.same_line();
+        if ui.small_button(format!("Contract##{}", edge.index())) {
+            message_queue.push_back(AppMsg::ContractLink(edge));
+        }
+    }
+
+    win.end();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::geometry::canvas_region;
+
+    #[test]
+    fn test_label_flags() {
+        let mut ui_state = UiState::default();
+        assert!(ui_state.shows_label(LabelKind::Edge));
+        assert!(ui_state.shows_label(LabelKind::Node));
+
+        ui_state.show_edge_labels = false;
+        assert!(!ui_state.shows_label(LabelKind::Edge));
+        assert!(ui_state.shows_label(LabelKind::Node));
+
+        ui_state.show_edge_labels = true;
+        ui_state.show_node_labels = false;
+        assert!(ui_state.shows_label(LabelKind::Edge));
+        assert!(!ui_state.shows_label(LabelKind::Node));
+    }
+
+    #[test]
+    fn test_edge_label_midpoint_independent_of_style() {
+        let (start, end) = ((10.0, 20.0), (110.0, 220.0));
+        for style in [EdgeStyle::Straight, EdgeStyle::Bezier] {
+            assert_eq!(style.point_at(start, end, 0.5), (60.0, 120.0));
+            assert_eq!(style.point_at(start, end, 0.0), start);
+            assert_eq!(style.point_at(start, end, 1.0), end);
+        }
+        // Away from the middle the curve bends away from the straight line
+        assert!(EdgeStyle::Bezier.point_at(start, end, 0.25).1 < 70.0);
+    }
+
+    #[test]
+    fn test_canvas_width_follows_docked_tools() {
+        let canvas_width = |ui_state: &UiState| canvas_region(800, 600, ui_state.canvas_offset()).2;
+        let mut ui_state = UiState::default();
+        assert_eq!(canvas_width(&ui_state), 800 - DEFAULT_SIDEBAR_WIDTH as i32);
+
+        ui_state.sidebar_width = 400.4;
+        assert_eq!(canvas_width(&ui_state), 400);
+
+        // A floating tools window does not take space from the canvas
+        ui_state.dock_tools = false;
+        assert_eq!(canvas_width(&ui_state), 800);
+    }
 }
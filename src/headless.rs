@@ -0,0 +1,121 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    app::AppMsg,
+    components::{
+        commands::parse_command,
+        network::{Link, Network, Node, load_networks},
+    },
+};
+
+/// Runs commands from `input`, one per line, against a network without opening a window,
+/// and writes each result to `output`. Uses the same syntax as the command box (see
+/// [`parse_command`]); blank lines and lines starting with `#` are skipped. Failed commands
+/// print an `error:` line and do not stop the run.
+pub fn run(input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    let mut network = Network::new();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let reply = parse_command(line).and_then(|msg| execute(&mut network, msg));
+        match reply {
+            Ok(text) => writeln!(output, "{}", text)?,
+            Err(err) => writeln!(output, "error: {}", err)?,
+        }
+    }
+    output.flush()
+}
+
+fn execute(network: &mut Network, msg: AppMsg) -> Result<String, String> {
+    match msg {
+        AppMsg::LoadNetworks(paths) => {
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            *network = load_networks(&paths).map_err(|err| err.to_string())?;
+            Ok(format!(
+                "loaded {} nodes, {} links",
+                network.graph.node_count(),
+                network.graph.edge_count()
+            ))
+        }
+        AppMsg::AddNode(id) => {
+            network.add_node(Node {
+                id,
+                ..Node::default()
+            });
+            Ok("ok".to_string())
+        }
+        AppMsg::AddLink(source, destination, capacity, weight) => {
+            let link = Link {
+                link_id: network.next_link_id(),
+                source_node: source,
+                destination_node: destination,
+                capacity,
+                weight,
+                ..Link::default()
+            };
+            network.add_link(link).map_err(|err| err.to_string())?;
+            Ok("ok".to_string())
+        }
+        AppMsg::DeleteNode(id) => {
+            let &idx = network
+                .node_indices
+                .get(&id)
+                .ok_or_else(|| format!("Node not found: {}", id))?;
+            network.remove_node(idx);
+            Ok("ok".to_string())
+        }
+        AppMsg::ComputeShortestPath(start, end) => {
+            let hops = network
+                .find_shortest_path(&start, &end)
+                .map_err(|err| err.to_string())?;
+            if hops.is_empty() {
+                return Ok(if start == end {
+                    start
+                } else {
+                    format!("no path from {} to {}", start, end)
+                });
+            }
+            let ids: Vec<&str> = hops
+                .first()
+                .map(|&(first, _)| first)
+                .into_iter()
+                .chain(hops.iter().map(|&(_, next)| next))
+                .map(|idx| network.graph[idx].id.as_str())
+                .collect();
+            Ok(ids.join(" -> "))
+        }
+        AppMsg::ComputeMaxFlow(source, sink) => {
+            let lookup = |id: &String| {
+                network
+                    .node_indices
+                    .get(id)
+                    .copied()
+                    .ok_or_else(|| format!("Node not found: {}", id))
+            };
+            let (flow, _) = network.max_flow_with_residuals(lookup(&source)?, lookup(&sink)?);
+            Ok(format!("max flow: {}", flow))
+        }
+        AppMsg::ShowStats => {
+            let capacity = network.capacity_stats();
+            let mut lines = vec![
+                format!("nodes: {}", network.graph.node_count()),
+                format!("links: {}", network.graph.edge_count()),
+            ];
+            lines.push(match network.diameter() {
+                Some(diameter) => format!("diameter: {}", diameter),
+                None => "diameter: n/a".to_string(),
+            });
+            lines.push(match network.average_path_length() {
+                Some(length) => format!("average path length: {:.2}", length),
+                None => "average path length: n/a".to_string(),
+            });
+            lines.push(format!("total capacity: {}", capacity.total_capacity));
+            Ok(lines.join("\n"))
+        }
+        other => Err(format!("{:?} is not available headless", other)),
+    }
+}
@@ -3,9 +3,27 @@ mod components;
 use crate::{app::AppModel, components::network::Network};
 use std::collections::VecDeque;
 mod app;
+mod headless;
 
 fn main() {
-    let mut model = AppModel::init("Network Visualization");
+    // `net_modeler --headless` reads commands from stdin instead of opening a window
+    if std::env::args().any(|arg| arg == "--headless") {
+        if let Err(err) = headless::run(std::io::stdin().lock(), std::io::stdout().lock()) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Optional window size: `net_modeler <width> <height>`
+    let args: Vec<i32> = std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    let mut model = match args[..] {
+        [width, height] => AppModel::init_with_size("Network Visualization", width, height),
+        _ => AppModel::init("Network Visualization"),
+    };
     let mut message_queue = VecDeque::new();
 
     while !model.rl.window_should_close() {
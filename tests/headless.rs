@@ -0,0 +1,72 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Runs `net_modeler --headless` with `script` on stdin and returns what it printed.
+fn run_script(script: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_net_modeler"))
+        .arg("--headless")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start net_modeler");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .expect("Failed to write the script");
+    let output = child
+        .wait_with_output()
+        .expect("net_modeler did not finish");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_headless_script() {
+    let path = std::env::temp_dir().join("net_modeler-headless.csv");
+    let path = path.to_str().unwrap();
+    std::fs::write(
+        path,
+        "link_id,source_node,destination_node,capacity,weight\n\
+         sa,S,A,10,1\n\
+         ab,A,B,4,1\n\
+         bt,B,T,10,1\n\
+         st,S,T,3,5\n",
+    )
+    .expect("Failed to write dummy CSV");
+
+    let output = run_script(&format!(
+        "# comment\nload {}\n\npath S B\nmaxflow S T\nstats\n",
+        path
+    ));
+    std::fs::remove_file(path).expect("Failed to remove dummy CSV");
+
+    assert_eq!(
+        output,
+        "loaded 4 nodes, 4 links\n\
+         S -> A -> B\n\
+         max flow: 7\n\
+         nodes: 4\n\
+         links: 4\n\
+         diameter: 2\n\
+         average path length: 1.33\n\
+         total capacity: 27\n"
+    );
+}
+
+#[test]
+fn test_headless_errors_do_not_stop_the_run() {
+    let output = run_script("add A\nadd B\nfly A\nlink A C 10 1\nlink A B 10 1\npath A B\n");
+    assert_eq!(
+        output,
+        "ok\n\
+         ok\n\
+         error: Unknown command 'fly'\n\
+         error: Node not found: C\n\
+         ok\n\
+         A -> B\n"
+    );
+}